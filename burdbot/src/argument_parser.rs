@@ -109,6 +109,7 @@ pub enum ConversionType {
     Member,
     Role,
     NonSelfMember,
+    TimeZone,
 }
 pub struct ArgumentInfo<'a> {
     args: &'a mut Args,
@@ -203,7 +204,7 @@ where
     }
 }
 
-fn parse_user_mention(arg: &str) -> Option<u64> {
+pub fn parse_user_mention(arg: &str) -> Option<u64> {
     lazy_static! {
         static ref USER_MENTION_MATCHER: Regex = Regex::new(r"^<@!?(\d+{17, 20})>$").unwrap();
     }
@@ -0,0 +1,149 @@
+use chrono::{DateTime, Duration, Utc};
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serenity::all::{CreateAllowedMentions, CreateMessage, GuildId, Mentionable, UserId};
+
+use super::BirthdayVisibility;
+use crate::database::Database;
+use crate::discord::DiscordHttp;
+use crate::error::SerenitySQLiteResult;
+use crate::guild_config::GuildConfig;
+
+pub const DEFAULT_ENGLISH_BIRTHDAY_MESSAGE: &str = "Happy birthday, {mention}! 🎂";
+pub const DEFAULT_SPANISH_BIRTHDAY_MESSAGE: &str = "¡Feliz cumpleaños, {mention}! 🎂";
+
+/// What can be put in a birthday message between braces, like `{mention}`.
+pub const BIRTHDAY_MESSAGE_PLACEHOLDERS: [&str; 3] = ["mention", "name", "age"];
+
+/// Discord's limit is 2000 characters, and each placeholder can get longer once filled in.
+pub const MAX_BIRTHDAY_MESSAGE_LENGTH: usize = 500;
+
+lazy_static! {
+    static ref PLACEHOLDER_MATCHER: Regex = Regex::new(r"\{(\w+)\}").unwrap();
+}
+
+/// Checks that a birthday message only uses known placeholders and isn't too long.
+pub fn is_valid_birthday_message(template: &str) -> bool {
+    let template = template.trim();
+
+    !template.is_empty()
+        && template.chars().count() <= MAX_BIRTHDAY_MESSAGE_LENGTH
+        && PLACEHOLDER_MATCHER
+            .captures_iter(template)
+            .all(|groups| BIRTHDAY_MESSAGE_PLACEHOLDERS.contains(&&groups[1]))
+}
+
+/// Fills in a birthday message's placeholders. `{age}` is left empty if the age isn't known.
+pub fn render_birthday_message(
+    template: &str, mention: &str, name: &str, age: Option<u32>,
+) -> String {
+    PLACEHOLDER_MATCHER
+        .replace_all(template, |groups: &Captures| match &groups[1] {
+            "mention" => mention.to_owned(),
+            "name" => name.to_owned(),
+            "age" => age.map(|age| age.to_string()).unwrap_or_default(),
+            _ => groups[0].to_owned(),
+        })
+        .into_owned()
+}
+
+/// The English and Spanish messages the guild has set, or both default ones if it set neither.
+fn birthday_message_templates(config: &GuildConfig) -> Vec<&str> {
+    let templates = [&config.birthday_message, &config.birthday_message_spanish]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+
+    if templates.is_empty() {
+        vec![DEFAULT_ENGLISH_BIRTHDAY_MESSAGE, DEFAULT_SPANISH_BIRTHDAY_MESSAGE]
+    } else {
+        templates
+    }
+}
+
+/// Posts the birthday announcement in the guild's birthday channel, if it has one, the birthday
+/// is public and the birthday ending at `ends_at` hasn't been announced there yet. Only the
+/// birthday person gets pinged, whatever the message says.
+pub async fn announce_birthday(
+    http: &impl DiscordHttp, db: &Database, config: &GuildConfig, guild_id: GuildId,
+    user_id: UserId, ends_at: DateTime<Utc>,
+) -> SerenitySQLiteResult<()> {
+    let Some(channel_id) = config.birthday_channel else {
+        return Ok(());
+    };
+
+    let Some((birthday, details)) = db.birthdays().get(user_id.get()).await? else {
+        return Ok(());
+    };
+
+    // An announcement shows everyone the date.
+    if details.visibility != BirthdayVisibility::Public {
+        return Ok(());
+    }
+
+    // Claimed before posting, so a restart can at worst miss an announcement, not repeat it.
+    if !db.birthdays().claim_announcement(user_id.get(), guild_id.get(), ends_at).await? {
+        return Ok(());
+    }
+
+    let mention = user_id.mention().to_string();
+    let name = match http.get_member(guild_id, user_id).await {
+        Ok(member) => member.display_name().to_owned(),
+        Err(_) => mention.clone(),
+    };
+    // A second before the end is still within the birthday being announced.
+    let year = birthday.year_at(ends_at - Duration::seconds(1), config.leap_day_policy);
+    let age = details.age_in(year);
+    let content = birthday_message_templates(config)
+        .into_iter()
+        .map(|template| render_birthday_message(template, &mention, &name, age))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let message = CreateMessage::new()
+        .content(content)
+        .allowed_mentions(CreateAllowedMentions::new().users([user_id]));
+
+    http.send_message(channel_id, message).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_in_placeholders() {
+        let template = "{mention} ({name}) is {age} today! {unknown}";
+
+        assert_eq!(
+            render_birthday_message(template, "<@1>", "Burd", Some(25)),
+            "<@1> (Burd) is 25 today! {unknown}"
+        );
+        assert_eq!(render_birthday_message("{age}", "<@1>", "Burd", None), "");
+    }
+
+    #[test]
+    fn validates_placeholders() {
+        assert!(is_valid_birthday_message("¡Feliz cumpleaños, {name}!"));
+        assert!(is_valid_birthday_message("No placeholders"));
+        assert!(!is_valid_birthday_message("Happy birthday, {nickname}!"));
+        assert!(!is_valid_birthday_message("  "));
+        assert!(!is_valid_birthday_message(&"a".repeat(MAX_BIRTHDAY_MESSAGE_LENGTH + 1)));
+    }
+
+    #[test]
+    fn defaults_to_both_languages() {
+        let mut config = GuildConfig::default();
+
+        assert_eq!(
+            birthday_message_templates(&config),
+            [DEFAULT_ENGLISH_BIRTHDAY_MESSAGE, DEFAULT_SPANISH_BIRTHDAY_MESSAGE]
+        );
+
+        config.birthday_message_spanish = Some("¡Feliz cumple!".to_owned());
+
+        assert_eq!(birthday_message_templates(&config), ["¡Feliz cumple!"]);
+    }
+}
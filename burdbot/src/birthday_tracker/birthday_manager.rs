@@ -1,236 +1,229 @@
-use chrono::Datelike;
-use chrono::Duration;
-use chrono::NaiveDate;
-use chrono::TimeZone;
-use chrono::Timelike;
 use chrono::Utc;
 use log::warn;
-use rusqlite::Connection;
-use rusqlite::OptionalExtension;
-use rusqlite::Transaction;
-use rusqlite::params;
 use serenity::all::CreateEmbed;
 use serenity::all::CreateEmbedFooter;
-use serenity::all::CreateMessage;
 use serenity::all::GuildId;
 use serenity::all::RoleId;
 use serenity::all::UserId;
 use serenity::client::Context;
-use serenity::model::id::ChannelId;
 
-use crate::BURDBOT_DB;
-use crate::commands::BirthdayInfoConfirmation;
+use crate::database::{self, Database, PendingBirthday};
+use crate::discord::DiscordHttp;
 use crate::error::SerenitySQLiteError;
 use crate::error::SerenitySQLiteResult;
-use crate::util;
+use crate::guild_config::{self, GuildConfig};
 
 use super::ADD_BDAY_ROLE_REASON;
-use super::BirthdayDateTime;
 use super::RM_BDAY_ROLE_REASON;
+use super::{BirthdayVisibility, announce_birthday};
 
-fn get_server_role(transaction: &Transaction, guild_id: u64) -> rusqlite::Result<Option<u64>> {
-    let role_select_statement = "
-    SELECT role_id FROM bday_role_list
-    WHERE guild_id = ?";
-
-    transaction.query_row(role_select_statement, [guild_id], |row| row.get::<_, u64>(0)).optional()
-}
-
+/// Saves a confirmed birthday and returns the reply for the user. If it's ongoing, the role is
+/// given right away in `guild_id`, and in the user's other guilds on the next update.
+/// `guild_config` is used for the leap day policy and the birthday announcement.
 pub async fn add_birthday_to_db(
-    ctx: &Context, guild_id: GuildId, channel_id: ChannelId, bday_info: &BirthdayInfoConfirmation,
-) -> SerenitySQLiteResult<()> {
-    let connection = Connection::open(BURDBOT_DB)?;
-    let ins_stmt_str = if bday_info.is_privileged {
-        "
-        INSERT OR REPLACE INTO bday
-        VALUES (?, ?, ?);
-        "
-    } else {
-        "
-            INSERT OR IGNORE INTO bday
-            VALUES (?, ?, ?);
-        "
-    };
-
-    let user_id = bday_info.user_id;
-    let bday_date_naive_local = NaiveDate::from_ymd_opt(2021, bday_info.month, bday_info.day)
-        .unwrap()
-        .and_hms_opt(0, 0, 0)
-        .unwrap();
-    let bday_date_naive_utc = bday_date_naive_local - Duration::hours(bday_info.time_zone);
-    let bday_date_time = BirthdayDateTime::new(
-        bday_date_naive_utc.month(),
-        bday_date_naive_utc.day(),
-        bday_date_naive_utc.hour(),
-    );
-    let rows_changed =
-        connection.execute(ins_stmt_str, params!(user_id, guild_id.get(), bday_date_time))?;
-
-    if rows_changed == 0 {
+    http: &impl DiscordHttp, db: &Database, guild_id: GuildId, pending: &PendingBirthday,
+    guild_config: &GuildConfig,
+) -> SerenitySQLiteResult<String> {
+    let user_id = pending.user_id;
+    let birthday = pending.birthday;
+    let was_saved =
+        db.birthdays().set(user_id, birthday, pending.details, pending.is_privileged).await?;
+
+    if !was_saved {
         // Must be an unprivileged person trying to override their own birthday.
-        let unprivileged_str =
-            "You already set your birthday. Please ask a mod to override it if you need to.";
-
-        util::send_message(ctx, channel_id, unprivileged_str, "add_birthday_to_db").await;
-
-        return Ok(());
+        return Ok(
+            "You already set your birthday. Please ask a mod to override it if you need to."
+                .to_owned(),
+        );
     }
 
-    let message;
-    let role_id_option;
+    let role_id_option = db.birthdays().get_role(guild_id.get()).await?;
+    let message = if role_id_option.is_some() {
+        format!("{user_id}'s birthday has been saved.")
+    } else {
+        format!(
+            "{user_id}'s birthday has been saved, but this server doesn't have a birthday role. \
+            Please ask a staff member to set one."
+        )
+    };
 
+    // Check if the birthday is ongoing
+    if let Some(role_id) = role_id_option
+        && let Some(window) = birthday.window_at(Utc::now(), guild_config.leap_day_policy)
+        && db.birthdays().add_ongoing(user_id, guild_id.get(), window.end).await?
     {
-        let mut role_check_query = connection.prepare(
-            "
-                SELECT role_id 
-                FROM bday_role_list 
-                WHERE guild_id = ?;
-            ",
-        )?;
-
-        match role_check_query
-            .query_row([guild_id.get()], |row| Ok(row.get::<_, u64>(0)))
-            .optional()?
+        let user_id = UserId::new(user_id);
+
+        if let Err(error) = http
+            .add_member_role(guild_id, user_id, RoleId::new(role_id), ADD_BDAY_ROLE_REASON)
+            .await
         {
-            Some(role_id_result) => {
-                role_id_option = Some(role_id_result?);
-                message = format!("{user_id}'s birthday has been saved.");
-            },
-            None => {
-                role_id_option = None;
-                message = format!(
-                    "{user_id}'s birthday has been saved, but this server doesn't have a birthday role. \
-                    Please ask a staff member to set one."
-                );
-            },
-        };
-    }
+            warn!(
+                "Error while trying to add role to user while adding bday to db. Likely not a concern \
+                 considering this most likely occurred because the role was removed while \
+                 the code was executing or insufficient permission: {error:?}"
+            );
+        }
 
-    if let Some(role_id) = role_id_option {
-        let now = Utc::now().naive_utc();
-        let bday_over = bday_date_naive_utc + Duration::days(1);
-
-        // Check if the birthday is ongoing
-        if now < bday_over && now > bday_date_naive_utc {
-            let bday_date_time =
-                BirthdayDateTime::new(bday_over.month(), bday_over.day(), bday_over.hour());
-            let insertion_statement = "
-                INSERT OR IGNORE INTO bday_user_list
-                    VALUES (?, ?);
-            ";
-
-            connection.execute(insertion_statement, params!(user_id, bday_date_time))?;
-
-            if let Err(error) = ctx
-                .http
-                .add_member_role(
-                    guild_id,
-                    UserId::new(user_id),
-                    RoleId::new(role_id),
-                    ADD_BDAY_ROLE_REASON,
-                )
-                .await
-            {
-                warn!(
-                    "Error while trying to add role to user while adding bday to db. Likely not a concern \
-                     considering this most likely occurred because the role was removed while \
-                     the code was executing or insufficient permission: {error:?}"
-                );
-            }
+        if let Err(error) =
+            announce_birthday(http, db, guild_config, guild_id, user_id, window.end).await
+        {
+            warn!("Error while announcing a birthday that was just added: {error:?}");
         }
     }
 
-    util::send_message(ctx, channel_id, message, "add_birthday_to_db").await;
-
-    Ok(())
+    Ok(message)
 }
 
+/// Feb 29 birthdays start according to `guild_id`'s leap day policy. Staff-only birthdays are
+/// only shown if `for_staff` is set, and hidden ones never are.
 pub async fn get_birthday(
-    ctx: &Context, channel_id: ChannelId, user_id: u64,
-) -> SerenitySQLiteResult<()> {
-    let connection = Connection::open(BURDBOT_DB)?;
-    let bday_select_str = "
-            SELECT bday_date
-            FROM bday
-            WHERE user_id = ?";
-    let bday_option = connection
-        .query_row(bday_select_str, [user_id], |row| row.get::<_, BirthdayDateTime>(0))
-        .optional()?;
-
-    if let Some(bday) = bday_option {
-        let now = Utc::now();
-        let mut time_stamp =
-            Utc.with_ymd_and_hms(now.year(), bday.month, bday.day, bday.hour, 0, 0).unwrap();
-
-        if time_stamp < now {
-            time_stamp = time_stamp.with_year(time_stamp.year() + 1).unwrap();
-        }
+    ctx: &Context, guild_id: GuildId, user_id: u64, for_staff: bool,
+) -> SerenitySQLiteResult<CreateEmbed> {
+    let Some((birthday, details)) = database::get(ctx).await.birthdays().get(user_id).await? else {
+        let msg = format!("No birthday found from the user {user_id}");
 
-        let footer = CreateEmbedFooter::new(format!("{user_id}'s next birthday will start at "));
-        let embed = CreateEmbed::new().timestamp(time_stamp).footer(footer);
+        return Ok(CreateEmbed::new().description(msg));
+    };
 
-        channel_id.send_message(&ctx.http, CreateMessage::new().embed(embed)).await?;
-    } else {
-        let msg = format!("No birthday found from the user {user_id}");
-        let embed = CreateEmbed::new().description(msg);
+    if !details.visibility.is_shown(for_staff) {
+        let msg = match details.visibility {
+            BirthdayVisibility::Hidden => format!("{user_id}'s birthday is hidden."),
+            _ => format!("{user_id}'s birthday is only shown to staff."),
+        };
 
-        channel_id.send_message(&ctx.http, CreateMessage::new().embed(embed)).await?;
+        return Ok(CreateEmbed::new().description(msg));
     }
 
-    Ok(())
+    let leap_day_policy = guild_config::get(ctx, guild_id).await.leap_day_policy;
+    let time_stamp = birthday.next_start(Utc::now(), leap_day_policy);
+    let footer = CreateEmbedFooter::new(format!("{user_id}'s next birthday will start at "));
+    let embed = CreateEmbed::new().timestamp(time_stamp).footer(footer);
+
+    Ok(match details.age_in(birthday.year_at(time_stamp, leap_day_policy)) {
+        Some(age) => embed.description(format!("They'll be turning {age}.")),
+        None => embed,
+    })
 }
 
+/// Removes the birthday everywhere, taking the birthday role away in every guild where
+/// it was ongoing.
 pub async fn remove_birthday(
-    ctx: &Context, channel_id: ChannelId, guild_id: GuildId, user_id: UserId,
-) -> Result<(), SerenitySQLiteError> {
-    let mut role_id = None;
-    let rows_changed;
-
-    {
-        let mut connection = Connection::open(BURDBOT_DB)?;
-        let transaction = connection.transaction()?;
-
-        // Foreign key constraint will take care of people in the ongoing birthday table.
-        transaction.execute(
-            "
-            DELETE FROM bday
-            WHERE user_id = ?
-        ",
-            [user_id.get()],
-        )?;
-
-        rows_changed = transaction
-            .query_row("SELECT total_changes();", [], |row| row.get::<_, usize>(0))
-            .unwrap();
-
-        // If more than 1 row changed then we deleted a foreign key too from bday_user_list
-        if rows_changed > 1 {
-            role_id = get_server_role(&transaction, guild_id.get())?;
-        }
-
-        transaction.commit()?;
-    }
-
-    if let Some(id) = role_id
-        && let Err(error) = ctx
-            .http
-            .remove_member_role(guild_id, user_id, RoleId::new(id), RM_BDAY_ROLE_REASON)
+    http: &impl DiscordHttp, db: &Database, user_id: UserId,
+) -> Result<String, SerenitySQLiteError> {
+    let removed = db.birthdays().remove(user_id.get()).await?;
+    let ongoing_roles = removed.as_ref().map_or(&[][..], |removed| &removed.ongoing_roles);
+
+    for &(guild_id, role_id) in ongoing_roles {
+        if let Err(error) = http
+            .remove_member_role(
+                GuildId::new(guild_id),
+                user_id,
+                RoleId::new(role_id),
+                RM_BDAY_ROLE_REASON,
+            )
             .await
-    {
-        warn!(
-            "Error while trying to remove birthday from database. Likely not a concern \
-                    considering this most likely occurred because the role was removed while \
-                    the code was executing or insufficient permission: {error:?}"
-        );
+        {
+            warn!(
+                "Error while trying to remove birthday from database. Likely not a concern \
+                        considering this most likely occurred because the role was removed while \
+                        the code was executing or insufficient permission: {error:?}"
+            );
+        }
     }
     // Give this message only if their bday was actually found
-    let message = if rows_changed > 0 {
+    Ok(if removed.is_some() {
         format!("{user_id}'s birthday was removed.")
     } else {
         format!("No birthday was found for {user_id}.")
-    };
+    })
+}
+
+/// Stops the user's birthday from counting in `guild_id`, taking the role away if it's ongoing.
+/// The role is taken away first, so nothing is saved if that fails.
+pub async fn opt_out_of_guild(
+    http: &impl DiscordHttp, db: &Database, guild_id: GuildId, user_id: UserId,
+) -> SerenitySQLiteResult<&'static str> {
+    if let Some(role_id) = db.birthdays().ongoing_role(user_id.get(), guild_id.get()).await? {
+        http.remove_member_role(guild_id, user_id, RoleId::new(role_id), RM_BDAY_ROLE_REASON)
+            .await?;
+    }
+
+    db.birthdays().opt_out(user_id.get(), guild_id.get()).await?;
+
+    Ok("You won't get this server's birthday role anymore. Your birthday still counts in \
+        other servers.")
+}
+
+pub async fn opt_in_to_guild(
+    ctx: &Context, guild_id: GuildId, user_id: UserId,
+) -> SerenitySQLiteResult<&'static str> {
+    let was_opted_out =
+        database::get(ctx).await.birthdays().opt_in(user_id.get(), guild_id.get()).await?;
+
+    Ok(if was_opted_out {
+        "You'll get this server's birthday role on your birthday again."
+    } else {
+        "You're already getting this server's birthday role on your birthday."
+    })
+}
+
+pub async fn set_birthday_visibility(
+    ctx: &Context, user_id: UserId, visibility: BirthdayVisibility,
+) -> rusqlite::Result<&'static str> {
+    let has_birthday =
+        database::get(ctx).await.birthdays().set_visibility(user_id.get(), visibility).await?;
+
+    if !has_birthday {
+        return Ok("You haven't set your birthday yet.");
+    }
+
+    Ok(match visibility {
+        BirthdayVisibility::Public => {
+            "Your birthday will be shown in announcements and birthday lists."
+        },
+        BirthdayVisibility::Staff => "Your birthday will only be shown to staff.",
+        BirthdayVisibility::Hidden => {
+            "Your birthday won't be shown to anyone. You'll still get the birthday role."
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::FixedOffset;
+
+    use super::*;
+    use crate::birthday_tracker::{Birthday, BirthdayTimeZone};
+    use crate::database::TempDatabase;
+    use crate::discord::recording::RecordingHttp;
 
-    util::send_message(ctx, channel_id, message, "remove_birthday").await;
+    const GUILD_ID: u64 = 10;
+    const ROLE_ID: u64 = 20;
+    const USER_ID: u64 = 30;
 
-    Ok(())
+    #[tokio::test]
+    async fn opting_out_is_not_saved_if_the_role_is_kept() {
+        let db = TempDatabase::new();
+        let utc = BirthdayTimeZone::Offset(FixedOffset::east_opt(0).unwrap());
+        let birthday = Birthday::new(6, 15, utc).unwrap();
+        let ends_at = Utc::now() + chrono::Duration::days(1);
+        let (guild_id, user_id) = (GuildId::new(GUILD_ID), UserId::new(USER_ID));
+
+        db.birthdays().set_role(GUILD_ID, ROLE_ID).await.unwrap();
+        db.birthdays().set(USER_ID, birthday, None, false).await.unwrap();
+        db.birthdays().add_ongoing(USER_ID, GUILD_ID, ends_at).await.unwrap();
+
+        let failing_http = RecordingHttp::new().fail_when(|_| true);
+
+        assert!(opt_out_of_guild(&failing_http, &db, guild_id, user_id).await.is_err());
+        assert_eq!(db.birthdays().ongoing_role(USER_ID, GUILD_ID).await.unwrap(), Some(ROLE_ID));
+
+        let http = RecordingHttp::new();
+
+        opt_out_of_guild(&http, &db, guild_id, user_id).await.unwrap();
+
+        assert_eq!(http.calls().len(), 1);
+        assert_eq!(db.birthdays().ongoing_role(USER_ID, GUILD_ID).await.unwrap(), None);
+    }
 }
@@ -0,0 +1,149 @@
+//! iCalendar (RFC 5545) export of a guild's birthdays.
+
+use chrono::{DateTime, Utc};
+use serenity::all::{CreateAttachment, GuildId, UserId};
+use serenity::client::Context;
+
+use super::listing::guild_birthdays;
+use super::{Birthday, LeapDayPolicy, REFERENCE_LEAP_YEAR};
+
+/// Lines longer than this many bytes have to be folded.
+const MAX_LINE_LENGTH: usize = 75;
+
+/// A birthday in the calendar, with the name to show for it.
+struct CalendarBirthday {
+    user_id: u64,
+    name: String,
+    birthday: Birthday,
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+/// Writes a content line, folding it so no line is longer than [`MAX_LINE_LENGTH`] bytes.
+fn write_line(out: &mut String, line: &str) {
+    let mut line_length = 0;
+
+    for c in line.chars() {
+        if line_length + c.len_utf8() > MAX_LINE_LENGTH {
+            out.push_str("\r\n ");
+            line_length = 1;
+        }
+
+        out.push(c);
+        line_length += c.len_utf8();
+    }
+
+    out.push_str("\r\n");
+}
+
+/// How the birthday repeats. Feb 29 birthdays are on the last day of February, or on the
+/// 60th day of the year which is Mar 1 in years without Feb 29.
+fn recurrence_rule(birthday: &Birthday, leap_day_policy: LeapDayPolicy) -> &'static str {
+    match (birthday.is_leap_day(), leap_day_policy) {
+        (false, _) => "RRULE:FREQ=YEARLY",
+        (true, LeapDayPolicy::Feb28) => "RRULE:FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=-1",
+        (true, LeapDayPolicy::Mar1) => "RRULE:FREQ=YEARLY;BYYEARDAY=60",
+    }
+}
+
+/// Makes a calendar with a yearly all-day event for every birthday. Events start in a leap
+/// year so that Feb 29 birthdays start on their actual day.
+fn birthday_calendar(
+    guild_id: GuildId, birthdays: &[CalendarBirthday], leap_day_policy: LeapDayPolicy,
+    now: DateTime<Utc>,
+) -> String {
+    let mut out = String::new();
+    let time_stamp = now.format("%Y%m%dT%H%M%SZ");
+
+    write_line(&mut out, "BEGIN:VCALENDAR");
+    write_line(&mut out, "VERSION:2.0");
+    write_line(&mut out, "PRODID:-//BurdBot//Birthdays//EN");
+    write_line(&mut out, "CALSCALE:GREGORIAN");
+
+    for CalendarBirthday { user_id, name, birthday } in birthdays {
+        let date = birthday.date_in(REFERENCE_LEAP_YEAR, leap_day_policy);
+        let description = format!("Starts at midnight {}.", birthday.time_zone());
+
+        write_line(&mut out, "BEGIN:VEVENT");
+        write_line(&mut out, &format!("UID:birthday-{user_id}-{guild_id}@burdbot"));
+        write_line(&mut out, &format!("DTSTAMP:{time_stamp}"));
+        write_line(&mut out, &format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")));
+        write_line(&mut out, recurrence_rule(birthday, leap_day_policy));
+        write_line(&mut out, &format!("SUMMARY:{}", escape_text(&format!("{name}'s birthday"))));
+        write_line(&mut out, &format!("DESCRIPTION:{}", escape_text(&description)));
+        write_line(&mut out, "TRANSP:TRANSPARENT");
+        write_line(&mut out, "END:VEVENT");
+    }
+
+    write_line(&mut out, "END:VCALENDAR");
+
+    out
+}
+
+/// Makes an `.ics` file with the public birthdays of `guild_id`'s members, except for the ones
+/// who opted out of the guild.
+pub async fn birthday_calendar_attachment(
+    ctx: &Context, guild_id: GuildId,
+) -> rusqlite::Result<CreateAttachment> {
+    let (birthdays, leap_day_policy) = guild_birthdays(ctx, guild_id, false).await?;
+    let birthdays = birthdays
+        .into_iter()
+        .map(|(user_id, birthday)| {
+            let name = ctx
+                .cache
+                .member(guild_id, UserId::new(user_id))
+                .map_or_else(|| user_id.to_string(), |member| member.display_name().to_owned());
+
+            CalendarBirthday { user_id, name, birthday }
+        })
+        .collect::<Vec<_>>();
+    let calendar = birthday_calendar(guild_id, &birthdays, leap_day_policy, Utc::now());
+
+    Ok(CreateAttachment::bytes(calendar.into_bytes(), "birthdays.ics"))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn calendar_birthday(user_id: u64, name: &str, month: u32, day: u32) -> CalendarBirthday {
+        let birthday = Birthday::new(month, day, "America/New_York".parse().unwrap()).unwrap();
+
+        CalendarBirthday { user_id, name: name.to_owned(), birthday }
+    }
+
+    #[test]
+    fn makes_yearly_events() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 16, 12, 30, 0).unwrap();
+        let birthdays =
+            [calendar_birthday(1, "Burd, the bot", 10, 6), calendar_birthday(2, "Leap", 2, 29)];
+        let calendar = birthday_calendar(GuildId::new(10), &birthdays, LeapDayPolicy::Mar1, now);
+        let lines = calendar.split("\r\n").collect::<Vec<_>>();
+
+        assert_eq!(lines.first(), Some(&"BEGIN:VCALENDAR"));
+        assert_eq!(lines[lines.len() - 2..], ["END:VCALENDAR", ""]);
+        assert!(lines.contains(&"UID:birthday-1-10@burdbot"));
+        assert!(lines.contains(&"DTSTAMP:20261016T123000Z"));
+        assert!(lines.contains(&"DTSTART;VALUE=DATE:20241006"));
+        assert!(lines.contains(&"SUMMARY:Burd\\, the bot's birthday"));
+        assert!(lines.contains(&"DESCRIPTION:Starts at midnight America/New_York."));
+        assert!(lines.contains(&"DTSTART;VALUE=DATE:20240229"));
+        assert!(lines.contains(&"RRULE:FREQ=YEARLY;BYYEARDAY=60"));
+        assert_eq!(lines.iter().filter(|&&line| line == "BEGIN:VEVENT").count(), 2);
+    }
+
+    #[test]
+    fn folds_long_lines() {
+        let mut out = String::new();
+        let line = format!("SUMMARY:{}", "ñ".repeat(100));
+
+        write_line(&mut out, &line);
+
+        assert!(out.split("\r\n").all(|line| line.len() <= MAX_LINE_LENGTH));
+        assert_eq!(out.replace("\r\n ", ""), format!("{line}\r\n"));
+    }
+}
@@ -0,0 +1,146 @@
+//! Confirm and cancel buttons for setting birthdays. Pending birthdays are kept in the
+//! database, so the buttons keep working across restarts until they expire.
+
+use std::time::Duration;
+
+use chrono::{TimeDelta, Utc};
+use log::{error, warn};
+use serenity::all::{
+    ButtonStyle, ChannelId, ComponentInteraction, CreateActionRow, CreateButton,
+    CreateInteractionResponse, CreateInteractionResponseMessage, EditMessage, GuildId, MessageId,
+};
+use serenity::client::Context;
+use tokio::time;
+
+use super::add_birthday_to_db;
+use crate::commands::error_util;
+use crate::database::{self, PendingBirthday};
+use crate::error::SerenitySQLiteError;
+use crate::guild_config;
+use crate::util;
+
+const CONFIRM_BUTTON_ID: &str = "birthday_confirm";
+const CANCEL_BUTTON_ID: &str = "birthday_cancel";
+
+/// How long the author has to press a button.
+pub const BIRTHDAY_CONFIRMATION_TIMEOUT: TimeDelta = TimeDelta::seconds(30);
+
+/// How often expired confirmations are looked for, so they expire a few seconds late at most.
+const EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+pub fn birthday_confirmation_buttons() -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(CONFIRM_BUTTON_ID).label("Confirm").style(ButtonStyle::Success),
+        CreateButton::new(CANCEL_BUTTON_ID).label("Cancel").style(ButtonStyle::Secondary),
+    ])]
+}
+
+/// Handles presses of the confirm and cancel buttons. Other components are ignored.
+pub async fn on_component_interaction(ctx: &Context, interaction: &ComponentInteraction) {
+    let is_confirmation = match interaction.data.custom_id.as_str() {
+        CONFIRM_BUTTON_ID => true,
+        CANCEL_BUTTON_ID => false,
+        _ => return,
+    };
+
+    let response = match handle_button(ctx, interaction, is_confirmation).await {
+        Ok(response) => response,
+        Err(error) => {
+            match error {
+                SerenitySQLiteError::SerenityError(errors) => error!(
+                    "Serenity error while adding birthday to db: {}",
+                    errors.serenity_errors[0]
+                ),
+                SerenitySQLiteError::SQLiteError(error) => {
+                    error!("SQLite error while adding birthday to db: {error}")
+                },
+            }
+
+            ephemeral_response(error_util::GENERIC_FAIL)
+        },
+    };
+
+    if let Err(error) = interaction.create_response(ctx, response).await {
+        warn!("Couldn't respond to a birthday confirmation button: {error:?}");
+    }
+}
+
+fn ephemeral_response(content: &str) -> CreateInteractionResponse {
+    let message = CreateInteractionResponseMessage::new().content(content).ephemeral(true);
+
+    CreateInteractionResponse::Message(message)
+}
+
+async fn handle_button(
+    ctx: &Context, interaction: &ComponentInteraction, is_confirmation: bool,
+) -> Result<CreateInteractionResponse, SerenitySQLiteError> {
+    let db = database::get(ctx).await;
+    let message_id = interaction.message.id.get();
+    let pending = match db.birthdays().get_pending(message_id).await? {
+        Some(pending) if pending.expires_at > Utc::now() => pending,
+        _ => return Ok(ephemeral_response("This birthday request expired.")),
+    };
+
+    if pending.author_id != interaction.user.id.get() {
+        return Ok(ephemeral_response("Only the person who set this birthday can confirm it."));
+    }
+
+    // Both buttons could be pressed at once, and only the first press counts.
+    if !db.birthdays().remove_pending(message_id).await? {
+        return Ok(ephemeral_response("This birthday request expired."));
+    }
+
+    let reply = if is_confirmation {
+        let guild_id = GuildId::new(pending.guild_id);
+        let guild_config = guild_config::get(ctx, guild_id).await;
+
+        add_birthday_to_db(&*ctx.http, &db, guild_id, &pending, &guild_config).await?
+    } else {
+        "Add birthday request cancelled.".to_owned()
+    };
+    let message = CreateInteractionResponseMessage::new()
+        .embed(util::message_embed(reply))
+        .components(Vec::new());
+
+    Ok(CreateInteractionResponse::UpdateMessage(message))
+}
+
+/// Keeps a birthday until its author presses a button on `message_id` or it expires.
+pub async fn add_pending_birthday(ctx: &Context, pending: PendingBirthday) -> rusqlite::Result<()> {
+    database::get(ctx).await.birthdays().add_pending(pending).await
+}
+
+/// Expires pending birthdays on one shared timer, removing the buttons from their messages.
+/// Birthdays that expired while the bot was down are expired on the first check.
+pub fn setup_birthday_confirmation_expiry(ctx: Context) {
+    tokio::spawn(async move {
+        let db = database::get(&ctx).await;
+        let mut interval = time::interval(EXPIRY_CHECK_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let expired = match db.birthdays().take_expired_pending(Utc::now()).await {
+                Ok(expired) => expired,
+                Err(error) => {
+                    error!("Couldn't expire pending birthdays: {error:?}");
+
+                    continue;
+                },
+            };
+
+            for pending in expired {
+                let channel_id = ChannelId::new(pending.channel_id);
+                let message = EditMessage::new()
+                    .embed(util::message_embed("Add birthday request expired."))
+                    .components(Vec::new());
+
+                if let Err(error) =
+                    channel_id.edit_message(&ctx, MessageId::new(pending.message_id), message).await
+                {
+                    warn!("Couldn't expire a birthday confirmation message: {error:?}");
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,71 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+
+/// When a guild celebrates Feb 29 birthdays in years without Feb 29.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LeapDayPolicy {
+    #[default]
+    Feb28,
+    Mar1,
+}
+
+impl LeapDayPolicy {
+    /// The date Feb 29 birthdays get in `year`, which has no Feb 29.
+    pub fn date_in(self, year: i32) -> NaiveDate {
+        match self {
+            LeapDayPolicy::Feb28 => NaiveDate::from_ymd_opt(year, 2, 28).unwrap(),
+            LeapDayPolicy::Mar1 => NaiveDate::from_ymd_opt(year, 3, 1).unwrap(),
+        }
+    }
+
+    /// The format the policy is stored as in the database.
+    pub fn to_db_string(self) -> &'static str {
+        match self {
+            LeapDayPolicy::Feb28 => "feb28",
+            LeapDayPolicy::Mar1 => "mar1",
+        }
+    }
+}
+
+impl FromStr for LeapDayPolicy {
+    type Err = ();
+
+    /// Accepts things like `feb28`, `Feb 28`, `february_28` and `02-28`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        match normalized.as_str() {
+            "feb28" | "february28" | "0228" => Ok(LeapDayPolicy::Feb28),
+            "mar1" | "march1" | "0301" => Ok(LeapDayPolicy::Mar1),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for LeapDayPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LeapDayPolicy::Feb28 => f.write_str("February 28"),
+            LeapDayPolicy::Mar1 => f.write_str("March 1"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_policies() {
+        assert_eq!("Feb 28".parse(), Ok(LeapDayPolicy::Feb28));
+        assert_eq!("march_1".parse(), Ok(LeapDayPolicy::Mar1));
+        assert_eq!("03-01".parse(), Ok(LeapDayPolicy::Mar1));
+        assert_eq!("feb29".parse::<LeapDayPolicy>(), Err(()));
+    }
+}
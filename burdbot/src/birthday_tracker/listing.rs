@@ -0,0 +1,228 @@
+use chrono::{DateTime, Datelike, Duration, Month, Utc};
+use serenity::all::{
+    Color, CreateEmbed, CreateEmbedFooter, EMBED_MAX_COUNT, EMBED_MAX_LENGTH, GuildId, Mentionable,
+    UserId,
+};
+use serenity::client::Context;
+
+use crate::database;
+use crate::guild_config;
+
+use super::{Birthday, BirthdayDetails, LeapDayPolicy, is_cached_member};
+
+const BIRTHDAYS_PER_PAGE: usize = 20;
+
+/// Birthdays that are ongoing at `now` or start within `days` days of it, as
+/// `(user_id, start)`, soonest first.
+fn upcoming_birthdays(
+    birthdays: &[(u64, Birthday)], now: DateTime<Utc>, days: i64, leap_day_policy: LeapDayPolicy,
+) -> Vec<(u64, DateTime<Utc>)> {
+    let until = now + Duration::days(days);
+    let mut upcoming = birthdays
+        .iter()
+        .filter_map(|(user_id, birthday)| {
+            let start = match birthday.window_at(now, leap_day_policy) {
+                Some(window) => window.start,
+                None => birthday.next_start(now, leap_day_policy),
+            };
+
+            (start < until).then_some((*user_id, start))
+        })
+        .collect::<Vec<_>>();
+
+    upcoming.sort_unstable_by_key(|&(user_id, start)| (start, user_id));
+
+    upcoming
+}
+
+/// Birthdays on a day of `month` in `year` where they're celebrated, as `(user_id, start)`,
+/// soonest first. Feb 29 birthdays can be in March in years without it.
+fn birthdays_in_month(
+    birthdays: &[(u64, Birthday)], year: i32, month: u32, leap_day_policy: LeapDayPolicy,
+) -> Vec<(u64, DateTime<Utc>)> {
+    let mut in_month = birthdays
+        .iter()
+        .filter(|(_, birthday)| birthday.date_in(year, leap_day_policy).month() == month)
+        .map(|(user_id, birthday)| (*user_id, birthday.window_in(year, leap_day_policy).start))
+        .collect::<Vec<_>>();
+
+    in_month.sort_unstable_by_key(|&(user_id, start)| (start, user_id));
+
+    in_month
+}
+
+/// Pages of birthdays, grouped so that each group fits in a message, both in how many embeds it
+/// has and in their combined length.
+fn birthday_list_embeds(title: &str, birthdays: &[(u64, DateTime<Utc>)]) -> Vec<Vec<CreateEmbed>> {
+    if birthdays.is_empty() {
+        let embed = CreateEmbed::new().color(Color::RED).title(title).description("No birthdays.");
+
+        return vec![vec![embed]];
+    }
+
+    let page_count = birthdays.len().div_ceil(BIRTHDAYS_PER_PAGE);
+    let mut messages = Vec::<Vec<CreateEmbed>>::new();
+    let mut message_length = 0;
+
+    for (idx, page) in birthdays.chunks(BIRTHDAYS_PER_PAGE).enumerate() {
+        let lines = page
+            .iter()
+            .map(|(user_id, start)| {
+                let timestamp = start.timestamp();
+
+                format!(
+                    "{}: <t:{timestamp}:f> (<t:{timestamp}:R>)",
+                    UserId::new(*user_id).mention()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let footer = format!("Page {} of {page_count}", idx + 1);
+        let length = title.chars().count() + lines.chars().count() + footer.chars().count();
+        let embed = CreateEmbed::new()
+            .color(Color::GOLD)
+            .title(title)
+            .description(lines)
+            .footer(CreateEmbedFooter::new(footer));
+
+        match messages.last_mut() {
+            Some(message)
+                if message.len() < EMBED_MAX_COUNT
+                    && message_length + length <= EMBED_MAX_LENGTH =>
+            {
+                message.push(embed);
+                message_length += length;
+            },
+            _ => {
+                messages.push(vec![embed]);
+                message_length = length;
+            },
+        }
+    }
+
+    messages
+}
+
+/// The birthdays of `guild_id`'s members that can be shown, along with their details.
+/// Staff-only birthdays are included if `for_staff` is set, and hidden ones never are.
+pub(super) async fn guild_birthday_details(
+    ctx: &Context, guild_id: GuildId, for_staff: bool,
+) -> rusqlite::Result<Vec<(u64, Birthday, BirthdayDetails)>> {
+    let birthdays = database::get(ctx).await.birthdays().get_all_in_guild(guild_id.get()).await?;
+
+    Ok(birthdays
+        .into_iter()
+        .filter(|(_, _, details)| details.visibility.is_shown(for_staff))
+        .filter(|&(user_id, _, _)| is_cached_member(&ctx.cache, guild_id.get(), user_id))
+        .collect())
+}
+
+/// The birthdays of `guild_id`'s members that can be shown, along with its leap day policy.
+/// Staff-only birthdays are included if `for_staff` is set, and hidden ones never are.
+pub(super) async fn guild_birthdays(
+    ctx: &Context, guild_id: GuildId, for_staff: bool,
+) -> rusqlite::Result<(Vec<(u64, Birthday)>, LeapDayPolicy)> {
+    let birthdays = guild_birthday_details(ctx, guild_id, for_staff).await?;
+    let leap_day_policy = guild_config::get(ctx, guild_id).await.leap_day_policy;
+    let birthdays =
+        birthdays.into_iter().map(|(user_id, birthday, _)| (user_id, birthday)).collect();
+
+    Ok((birthdays, leap_day_policy))
+}
+
+/// Lists the birthdays in `guild_id` that are ongoing or start within `days` days.
+pub async fn upcoming_birthday_embeds(
+    ctx: &Context, guild_id: GuildId, days: i64,
+) -> rusqlite::Result<Vec<Vec<CreateEmbed>>> {
+    let (birthdays, leap_day_policy) = guild_birthdays(ctx, guild_id, false).await?;
+    let upcoming = upcoming_birthdays(&birthdays, Utc::now(), days, leap_day_policy);
+    let title = format!("Birthdays in the next {days} day{}", if days == 1 { "" } else { "s" });
+
+    Ok(birthday_list_embeds(&title, &upcoming))
+}
+
+/// Lists the birthdays in `guild_id` in the next `month` to come, which may be this one.
+pub async fn month_birthday_embeds(
+    ctx: &Context, guild_id: GuildId, month: u32,
+) -> rusqlite::Result<Vec<Vec<CreateEmbed>>> {
+    let (birthdays, leap_day_policy) = guild_birthdays(ctx, guild_id, false).await?;
+    let now = Utc::now();
+    let year = if month >= now.month() { now.year() } else { now.year() + 1 };
+    let in_month = birthdays_in_month(&birthdays, year, month, leap_day_policy);
+    let month_name = Month::try_from(month as u8).expect("Months are checked by the commands.");
+    let title = format!("Birthdays in {} {year}", month_name.name());
+
+    Ok(birthday_list_embeds(&title, &in_month))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, TimeZone};
+
+    use super::*;
+    use crate::birthday_tracker::BirthdayTimeZone;
+
+    fn birthday(month: u32, day: u32, offset_hours: i32) -> Birthday {
+        let offset = FixedOffset::east_opt(offset_hours * 60 * 60).unwrap();
+
+        Birthday::new(month, day, BirthdayTimeZone::Offset(offset)).unwrap()
+    }
+
+    #[test]
+    fn upcoming_birthdays_are_soonest_first() {
+        let now = Utc.with_ymd_and_hms(2026, 12, 30, 8, 0, 0).unwrap();
+        let birthdays = [
+            (1, birthday(1, 2, 0)),
+            // Still ongoing in UTC-10
+            (2, birthday(12, 29, -10)),
+            (3, birthday(12, 31, 0)),
+            (4, birthday(1, 10, 0)),
+        ];
+        let upcoming = upcoming_birthdays(&birthdays, now, 7, LeapDayPolicy::default());
+        let user_ids = upcoming.iter().map(|&(user_id, _)| user_id).collect::<Vec<_>>();
+
+        assert_eq!(user_ids, [2, 3, 1]);
+        assert_eq!(upcoming[2].1, Utc.with_ymd_and_hms(2027, 1, 2, 0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn month_listing_follows_the_leap_day_policy() {
+        let birthdays = [(1, birthday(2, 29, 0)), (2, birthday(3, 15, 0)), (3, birthday(2, 1, 0))];
+        let in_march = |year, policy| {
+            birthdays_in_month(&birthdays, year, 3, policy)
+                .into_iter()
+                .map(|(user_id, _)| user_id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(in_march(2027, LeapDayPolicy::Mar1), [1, 2]);
+        assert_eq!(in_march(2027, LeapDayPolicy::Feb28), [2]);
+        assert_eq!(in_march(2028, LeapDayPolicy::Mar1), [2]);
+    }
+
+    #[test]
+    fn long_lists_are_split_into_pages() {
+        let start = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let birthdays = (0..BIRTHDAYS_PER_PAGE as u64 * 11 + 1)
+            .map(|user_id| (367538590520967181 + user_id, start))
+            .collect::<Vec<_>>();
+        let messages = birthday_list_embeds("Birthdays", &birthdays);
+        let embed_length = |embed: &serde_json::Value| {
+            ["title", "description"]
+                .map(|key| embed[key].as_str().unwrap().chars().count())
+                .into_iter()
+                .sum::<usize>()
+                + embed["footer"]["text"].as_str().unwrap().chars().count()
+        };
+
+        // With real user IDs, 10 pages are too long to share a message.
+        assert!(messages.len() > 2);
+        assert_eq!(messages.iter().map(Vec::len).sum::<usize>(), 12);
+
+        for message in messages {
+            let embeds = message.into_iter().map(|embed| serde_json::to_value(embed).unwrap());
+
+            assert!(embeds.map(|embed| embed_length(&embed)).sum::<usize>() <= EMBED_MAX_LENGTH);
+        }
+    }
+}
@@ -1,78 +1,252 @@
+mod announcement;
 mod birthday_manager;
 mod birthday_server_role_manager;
+mod calendar;
+mod confirmation;
+mod leap_day;
+mod listing;
 mod role_updater;
+mod time_zone;
+mod transfer;
+mod visibility;
 
+pub use announcement::*;
 pub use birthday_manager::*;
 pub use birthday_server_role_manager::*;
+pub use calendar::*;
+pub use confirmation::*;
+pub use leap_day::*;
+pub use listing::*;
 pub use role_updater::*;
+pub use time_zone::*;
+pub use transfer::*;
+pub use visibility::*;
 
-use crate::commands;
+use std::ops::Range;
 
-use chrono::{DateTime, Datelike, Timelike, Utc};
-use lazy_static::lazy_static;
-use regex::Regex;
-use rusqlite::ToSql;
-use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
 
-#[derive(Clone, Copy)]
-struct BirthdayDateTime {
+/// How often birthday roles get updated, in seconds. Updates happen on multiples of this
+/// from midnight UTC so that they line up with the start of birthdays.
+pub(crate) const UPDATE_INTERVAL: u32 = OFFSET_STEP_MINUTES as u32 * 60;
+
+/// A leap year, so that Feb 29 counts as a valid birthday.
+const REFERENCE_LEAP_YEAR: i32 = 2024;
+
+/// A birthday as it was given: a day of the year in the birthday person's time zone.
+/// When it starts and ends in UTC gets worked out for each year, since that moves with
+/// daylight saving time and, for Feb 29, with the guild's leap day policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Birthday {
     month: u32,
     day: u32,
-    hour: u32,
+    time_zone: BirthdayTimeZone,
 }
 
-impl BirthdayDateTime {
-    fn new(month: u32, day: u32, hour: u32) -> BirthdayDateTime {
-        BirthdayDateTime { month, day, hour }
+impl Birthday {
+    /// Returns `None` if the month doesn't have that day. Feb 29 is allowed.
+    pub fn new(month: u32, day: u32, time_zone: BirthdayTimeZone) -> Option<Birthday> {
+        NaiveDate::from_ymd_opt(REFERENCE_LEAP_YEAR, month, day)?;
+
+        Some(Birthday { month, day, time_zone })
     }
 
-    fn one_day_ahead(&self) -> BirthdayDateTime {
-        let mut day = (self.day + 1) % (commands::MONTH_TO_DAYS[self.month as usize] as u32 + 1);
-        let mut month = self.month;
+    pub fn month(&self) -> u32 {
+        self.month
+    }
 
-        if day == 0 {
-            month = (self.month + 1) % 13;
+    pub fn day(&self) -> u32 {
+        self.day
+    }
 
-            if month == 0 {
-                month = 1;
-            }
+    pub fn time_zone(&self) -> BirthdayTimeZone {
+        self.time_zone
+    }
 
-            day = 1;
-        }
+    pub fn is_leap_day(&self) -> bool {
+        self.month == 2 && self.day == 29
+    }
 
-        BirthdayDateTime::new(month, day, self.hour)
+    /// The local date of the birthday in `year`. Feb 29 gets moved by `leap_day_policy`
+    /// in years without it.
+    pub fn date_in(&self, year: i32, leap_day_policy: LeapDayPolicy) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, self.month, self.day)
+            .unwrap_or_else(|| leap_day_policy.date_in(year))
     }
-}
 
-impl From<DateTime<Utc>> for BirthdayDateTime {
-    fn from(date_time: DateTime<Utc>) -> Self {
-        BirthdayDateTime { month: date_time.month(), day: date_time.day(), hour: date_time.hour() }
+    /// When the birthday starts and ends in `year`. It lasts from the start of its local day
+    /// to the start of the next one, so it's 23 or 25 hours long around daylight saving time
+    /// changes.
+    pub fn window_in(&self, year: i32, leap_day_policy: LeapDayPolicy) -> Range<DateTime<Utc>> {
+        let date = self.date_in(year, leap_day_policy);
+        let start = self.time_zone.start_of(date).and_utc();
+        let end = self.time_zone.start_of(date + Duration::days(1)).and_utc();
+
+        start..end
     }
-}
 
-impl ToSql for BirthdayDateTime {
-    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
-        let date_time_str = format!("{:02}-{:02}-{:02}", self.month, self.day, self.hour);
+    /// The birthday ongoing at `date_time`, if there is one.
+    pub fn window_at(
+        &self, date_time: DateTime<Utc>, leap_day_policy: LeapDayPolicy,
+    ) -> Option<Range<DateTime<Utc>>> {
+        // Around new year's, the ongoing birthday can be in the year before or after in UTC.
+        (date_time.year() - 1..=date_time.year() + 1)
+            .map(|year| self.window_in(year, leap_day_policy))
+            .find(|window| window.contains(&date_time))
+    }
 
-        Ok(ToSqlOutput::from(date_time_str))
+    /// The year of the birthday that's ongoing at `date_time` or starts next after it, which
+    /// is the year that gives someone's age on it.
+    pub fn year_at(&self, date_time: DateTime<Utc>, leap_day_policy: LeapDayPolicy) -> i32 {
+        (date_time.year() - 1..)
+            .find(|&year| self.window_in(year, leap_day_policy).end > date_time)
+            .unwrap()
+    }
+
+    /// When the birthday next starts after `date_time`.
+    pub fn next_start(
+        &self, date_time: DateTime<Utc>, leap_day_policy: LeapDayPolicy,
+    ) -> DateTime<Utc> {
+        (date_time.year() - 1..)
+            .map(|year| self.window_in(year, leap_day_policy).start)
+            .find(|start| *start > date_time)
+            .unwrap()
     }
 }
 
-impl FromSql for BirthdayDateTime {
-    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
-        lazy_static! {
-            static ref BIRTHDAY_TIME_MATCHER: Regex = Regex::new(r"^(\d+)-(\d+)-(\d+)$").unwrap();
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, TimeZone};
+    use chrono_tz::Tz;
+    use rand_09::{Rng, SeedableRng};
+
+    use super::*;
+
+    /// Time zones with whole, fractional and negative offsets, and daylight saving time that
+    /// changes at night, at midnight (Santiago) and by half an hour (Lord Howe).
+    fn time_zones() -> Vec<BirthdayTimeZone> {
+        let offsets = [0, -7 * 60, 5 * 60 + 45, -(3 * 60 + 30), 14 * 60, -12 * 60];
+        let names = [
+            Tz::America__New_York,
+            Tz::Europe__London,
+            Tz::America__Santiago,
+            Tz::Australia__Lord_Howe,
+            Tz::Pacific__Chatham,
+            Tz::Asia__Kolkata,
+        ];
+
+        offsets
+            .into_iter()
+            .map(|minutes| BirthdayTimeZone::Offset(FixedOffset::east_opt(minutes * 60).unwrap()))
+            .chain(names.into_iter().map(BirthdayTimeZone::Named))
+            .collect()
+    }
+
+    fn local_date(time_zone: BirthdayTimeZone, date_time: DateTime<Utc>) -> NaiveDate {
+        match time_zone {
+            BirthdayTimeZone::Offset(offset) => offset.from_utc_datetime(&date_time.naive_utc()),
+            BirthdayTimeZone::Named(tz) => {
+                tz.from_utc_datetime(&date_time.naive_utc()).fixed_offset()
+            },
+        }
+        .date_naive()
+    }
+
+    fn every_day_of(year: i32) -> impl Iterator<Item = NaiveDate> {
+        NaiveDate::from_ymd_opt(year, 1, 1)
+            .unwrap()
+            .iter_days()
+            .take_while(move |date| date.year() == year)
+    }
+
+    #[test]
+    fn every_day_covers_exactly_its_local_day() {
+        let second = Duration::seconds(1);
+
+        for time_zone in time_zones() {
+            for year in [2024, 2026] {
+                for date in every_day_of(year) {
+                    let birthday = Birthday::new(date.month(), date.day(), time_zone).unwrap();
+                    let window = birthday.window_in(year, LeapDayPolicy::default());
+                    let length = window.end - window.start;
+
+                    assert_eq!(local_date(time_zone, window.start), date, "{time_zone} {date}");
+                    assert_eq!(local_date(time_zone, window.end - second), date);
+                    assert_ne!(local_date(time_zone, window.start - second), date);
+                    assert!(length >= Duration::hours(23) && length <= Duration::hours(25));
+                    assert_eq!(
+                        birthday.window_at(window.start, LeapDayPolicy::default()),
+                        Some(window.clone())
+                    );
+                    assert_eq!(birthday.window_at(window.end, LeapDayPolicy::default()), None);
+                }
+            }
         }
+    }
 
-        value.as_str().and_then(|sql_str| match BIRTHDAY_TIME_MATCHER.captures(sql_str) {
-            Some(groups) => {
-                let month = groups.get(1).unwrap().as_str().parse::<u32>().unwrap();
-                let day = groups.get(2).unwrap().as_str().parse::<u32>().unwrap();
-                let hour = groups.get(3).unwrap().as_str().parse::<u32>().unwrap();
+    #[test]
+    fn consecutive_days_follow_each_other() {
+        for time_zone in time_zones() {
+            let days = every_day_of(2024).chain(every_day_of(2025)).collect::<Vec<_>>();
 
-                Ok(BirthdayDateTime { month, day, hour })
-            },
-            None => Err(FromSqlError::InvalidType),
-        })
+            for dates in days.windows(2) {
+                let window = |date: NaiveDate| {
+                    Birthday::new(date.month(), date.day(), time_zone)
+                        .unwrap()
+                        .window_in(date.year(), LeapDayPolicy::default())
+                };
+
+                assert_eq!(window(dates[0]).end, window(dates[1]).start, "{time_zone} {dates:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn leap_day_birthdays_follow_the_policy() {
+        let utc = BirthdayTimeZone::Offset(FixedOffset::east_opt(0).unwrap());
+        let leap_day = Birthday::new(2, 29, utc).unwrap();
+        let feb_28 = Birthday::new(2, 28, utc).unwrap();
+        let mar_1 = Birthday::new(3, 1, utc).unwrap();
+
+        for policy in [LeapDayPolicy::Feb28, LeapDayPolicy::Mar1] {
+            let moved_to = if policy == LeapDayPolicy::Feb28 { feb_28 } else { mar_1 };
+
+            for year in 2020..2030 {
+                if year % 4 == 0 {
+                    assert_eq!(leap_day.date_in(year, policy).day(), 29);
+                } else {
+                    assert_eq!(leap_day.window_in(year, policy), moved_to.window_in(year, policy));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn birthdays_are_ongoing_exactly_on_their_local_day() {
+        let mut rng = rand_pcg::Pcg64Mcg::seed_from_u64(20261016);
+        let time_zones = time_zones();
+        let policies = [LeapDayPolicy::Feb28, LeapDayPolicy::Mar1];
+        let first = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap().timestamp();
+        let last = Utc.with_ymd_and_hms(2031, 1, 1, 0, 0, 0).unwrap().timestamp();
+
+        for _ in 0..20000 {
+            let time_zone = time_zones[rng.random_range(0..time_zones.len())];
+            let policy = policies[rng.random_range(0..policies.len())];
+            let date =
+                NaiveDate::from_yo_opt(REFERENCE_LEAP_YEAR, rng.random_range(1..=366)).unwrap();
+            let birthday = Birthday::new(date.month(), date.day(), time_zone).unwrap();
+            let date_time = DateTime::from_timestamp(rng.random_range(first..last), 0).unwrap();
+            let today = local_date(time_zone, date_time);
+            let is_birthday = birthday.date_in(today.year(), policy) == today;
+            let window = birthday.window_at(date_time, policy);
+
+            assert_eq!(window.is_some(), is_birthday, "{time_zone} {date} {date_time}");
+
+            let next_start = birthday.next_start(date_time, policy);
+
+            assert!(next_start > date_time);
+            assert!(birthday.window_at(next_start, policy).is_some());
+            assert!(window.is_none_or(|window| window.end <= next_start));
+        }
     }
 }
@@ -1,49 +1,91 @@
-use chrono::{DateTime, Datelike, Duration, Utc};
-use rusqlite::{Connection, Transaction, params};
-use serenity::http::Http;
+use std::collections::HashSet;
 
-use super::BirthdayDateTime;
-use crate::BURDBOT_DB;
+use chrono::{DateTime, Duration, Utc};
+use log::info;
+use serenity::all::{Cache, GuildId, RoleId, UserId};
+use serenity::client::Context;
+
+use super::announce_birthday;
+use crate::database::Database;
+use crate::discord::DiscordHttp;
 use crate::error::{SerenitySQLiteError, SerenitySQLiteResult};
+use crate::{guild_config, metrics};
 
 pub(crate) const RM_BDAY_ROLE_REASON: Option<&str> = Some("It's no longer their birthday");
 pub(crate) const ADD_BDAY_ROLE_REASON: Option<&str> = Some("It's their birthday");
 
-struct DatabaseRoleInfo {
-    removal_list: Vec<(u64, u64, u64)>,
-    addition_list: Vec<(u64, u64, u64)>,
+/// Updates birthday roles in every guild with a birthday role, only giving them to users who
+/// are in the guild.
+pub async fn update_birthday_roles(ctx: &Context, db: &Database) -> SerenitySQLiteResult<()> {
+    let cache = ctx.cache.clone();
+    let is_member = move |guild_id, user_id| is_cached_member(&cache, guild_id, user_id);
+
+    update_birthday_roles_at(&*ctx.http, db, get_date_time_to_use(), is_member).await
+}
+
+/// Guild members are chunked on startup, so the cache knows everyone in a guild. Guilds that
+/// aren't cached yet are assumed to have the user, and Discord rejects the role if they don't.
+pub fn is_cached_member(cache: &Cache, guild_id: u64, user_id: u64) -> bool {
+    cache
+        .guild(GuildId::new(guild_id))
+        .is_none_or(|guild| guild.members.contains_key(&UserId::new(user_id)))
 }
 
-pub async fn update_birthday_roles<T: AsRef<Http>>(http: T) -> SerenitySQLiteResult<()> {
-    let user_role_info = update_bday_db_roles()?;
+async fn update_birthday_roles_at(
+    http: &impl DiscordHttp, db: &Database, date_time: DateTime<Utc>,
+    is_member: impl Fn(u64, u64) -> bool + Send + 'static,
+) -> SerenitySQLiteResult<()> {
+    let guild_configs = guild_config::get_all_guild_configs(db).await?;
+    let leap_day_policies = guild_configs
+        .iter()
+        .map(|(guild_id, config)| (guild_id.get(), config.leap_day_policy))
+        .collect();
+    let user_role_info =
+        db.birthdays().update_ongoing(date_time, leap_day_policies, is_member).await?;
     let mut error_vector_option = None;
-    let http = http.as_ref();
 
-    for (user_id, guild_id, role_id) in user_role_info.removal_list {
-        if let Err(error) = http
+    for (user_id, guild_id, role_id) in user_role_info.removals {
+        let result = http
             .remove_member_role(
-                guild_id.into(),
-                user_id.into(),
-                role_id.into(),
+                GuildId::new(guild_id),
+                UserId::new(user_id),
+                RoleId::new(role_id),
                 RM_BDAY_ROLE_REASON,
             )
-            .await
-        {
+            .await;
+
+        record_role_update("remove", &result);
+
+        if let Err(error) = result {
             let removal_errors = error_vector_option.get_or_insert(Vec::new());
 
             removal_errors.push(error);
         }
     }
 
-    for (user_id, guild_id, role_id) in user_role_info.addition_list {
-        if let Err(error) = http
-            .add_member_role(guild_id.into(), user_id.into(), role_id.into(), ADD_BDAY_ROLE_REASON)
-            .await
-        {
+    for started in user_role_info.additions {
+        let (guild_id, user_id) = (GuildId::new(started.guild_id), UserId::new(started.user_id));
+        let result = http
+            .add_member_role(guild_id, user_id, RoleId::new(started.role_id), ADD_BDAY_ROLE_REASON)
+            .await;
+
+        record_role_update("add", &result);
+
+        if let Err(error) = result {
             let addition_errors = error_vector_option.get_or_insert(Vec::new());
 
             addition_errors.push(error);
         }
+
+        let config = guild_configs.get(&guild_id).cloned().unwrap_or_default();
+
+        match announce_birthday(http, db, &config, guild_id, user_id, started.ends_at).await {
+            Ok(()) => {},
+            Err(SerenitySQLiteError::SerenityError(errors)) => {
+                error_vector_option.get_or_insert(Vec::new()).extend(errors.serenity_errors);
+            },
+            Err(error @ SerenitySQLiteError::SQLiteError(_)) => return Err(error),
+        }
     }
 
     match error_vector_option {
@@ -52,126 +94,365 @@ pub async fn update_birthday_roles<T: AsRef<Http>>(http: T) -> SerenitySQLiteRes
     }
 }
 
-fn update_bday_db_roles() -> rusqlite::Result<DatabaseRoleInfo> {
-    let mut connection = Connection::open(BURDBOT_DB)?;
-    let transaction = connection.transaction()?;
-    let date_time = get_date_time_to_use();
-    let bdays_to_remove = get_and_delete_old_bdays(&transaction, date_time)?;
-    let bdays_to_add = add_new_bdays(&transaction, date_time)?;
+/// Role changes made to match the ongoing birthdays.
+#[derive(Debug, Default)]
+pub struct RoleCorrections {
+    pub added: usize,
+    pub removed: usize,
+    pub errors: Vec<serenity::Error>,
+}
+
+/// Makes the birthday roles in cached guilds match the ongoing birthdays, in case updates were
+/// missed while the bot was down or failed, then ends and starts birthdays as usual.
+pub async fn reconcile_birthday_roles(ctx: &Context, db: &Database) -> SerenitySQLiteResult<()> {
+    let role_holders = |guild_id, role_id| cached_role_holders(&ctx.cache, guild_id, role_id);
+    let is_member = |guild_id, user_id| is_cached_member(&ctx.cache, guild_id, user_id);
+    let corrections =
+        reconcile_birthday_roles_with(&*ctx.http, db, role_holders, is_member).await?;
+
+    info!(
+        "Reconciled birthday roles: added {} and removed {}, with {} failures.",
+        corrections.added,
+        corrections.removed,
+        corrections.errors.len()
+    );
 
-    transaction.commit()?;
+    let mut errors = corrections.errors;
+
+    match update_birthday_roles(ctx, db).await {
+        Ok(()) => {},
+        Err(SerenitySQLiteError::SerenityError(update_errors)) => {
+            errors.extend(update_errors.serenity_errors);
+        },
+        Err(error @ SerenitySQLiteError::SQLiteError(_)) => return Err(error),
+    }
 
-    Ok(DatabaseRoleInfo { removal_list: bdays_to_remove, addition_list: bdays_to_add })
+    if errors.is_empty() { Ok(()) } else { Err(SerenitySQLiteError::from(errors)) }
 }
 
-fn get_date_time_to_use() -> DateTime<Utc> {
-    Utc::now() + Duration::hours(1)
+/// The members of `guild_id` with `role_id`, or `None` if the guild isn't cached.
+fn cached_role_holders(cache: &Cache, guild_id: u64, role_id: u64) -> Option<HashSet<u64>> {
+    let guild = cache.guild(GuildId::new(guild_id))?;
+    let role_id = RoleId::new(role_id);
+
+    Some(
+        guild
+            .members
+            .values()
+            .filter(|member| member.roles.contains(&role_id))
+            .map(|member| member.user.id.get())
+            .collect(),
+    )
 }
 
-fn get_and_delete_old_bdays(
-    transaction: &Transaction, date_time: DateTime<Utc>,
-) -> rusqlite::Result<Vec<(u64, u64, u64)>> {
-    // get bdays from bday table that are 24 hrs in the past and set the end date to be 24 hrs from the start date.
-    let mut query_info = Vec::new();
+/// Birthdays that are over but haven't been ended yet count as ongoing here, so that the
+/// update afterwards removes their roles without them being counted twice.
+async fn reconcile_birthday_roles_with(
+    http: &impl DiscordHttp, db: &Database,
+    role_holders: impl Fn(u64, u64) -> Option<HashSet<u64>>, is_member: impl Fn(u64, u64) -> bool,
+) -> rusqlite::Result<RoleCorrections> {
+    let mut corrections = RoleCorrections::default();
+
+    for ongoing_role in db.birthdays().get_ongoing_roles().await? {
+        let (guild_id, role_id) = (ongoing_role.guild_id, ongoing_role.role_id);
+        let Some(holders) = role_holders(guild_id, role_id) else {
+            continue;
+        };
+        let ongoing = ongoing_role.user_ids.into_iter().collect::<HashSet<_>>();
+        let missing = ongoing
+            .difference(&holders)
+            .copied()
+            .filter(|&user_id| is_member(guild_id, user_id))
+            .collect::<Vec<_>>();
+        let extra = holders.difference(&ongoing).copied().collect::<Vec<_>>();
+        let (guild_id, role_id) = (GuildId::new(guild_id), RoleId::new(role_id));
 
-    let mut user_selection_statement = transaction.prepare(
-        "
-            SELECT 
-                bday_user_list.user_id, 
-                bday.guild_id,
-                bday_role_list.role_id
-            FROM bday_user_list
-                INNER JOIN bday ON bday_user_list.user_id = bday.user_id
-                INNER JOIN bday_role_list ON bday.guild_id = bday_role_list.guild_id
-            WHERE bday_user_list.bday_over_date < ?;
-    ",
-    )?;
+        for user_id in missing {
+            let result = http
+                .add_member_role(guild_id, UserId::new(user_id), role_id, ADD_BDAY_ROLE_REASON)
+                .await;
+
+            record_role_update("add", &result);
+
+            match result {
+                Ok(()) => corrections.added += 1,
+                Err(error) => corrections.errors.push(error),
+            }
+        }
 
-    let date_time_fmt = BirthdayDateTime::from(date_time);
+        for user_id in extra {
+            let result = http
+                .remove_member_role(guild_id, UserId::new(user_id), role_id, RM_BDAY_ROLE_REASON)
+                .await;
 
-    let rows = user_selection_statement.query_map([date_time_fmt], |row| {
-        Ok((row.get::<_, u64>(0)?, row.get::<_, u64>(1)?, row.get::<_, u64>(2)?))
-    })?;
+            record_role_update("remove", &result);
 
-    for row in rows {
-        query_info.push(row.unwrap());
+            match result {
+                Ok(()) => corrections.removed += 1,
+                Err(error) => corrections.errors.push(error),
+            }
+        }
     }
 
-    let user_deletion_statement = "
-        DELETE FROM bday_user_list
-        WHERE bday_over_date < ?;
-    ";
+    Ok(corrections)
+}
 
-    transaction.execute(user_deletion_statement, [date_time_fmt])?;
+fn record_role_update(action: &str, result: &serenity::Result<()>) {
+    let result = if result.is_ok() { "success" } else { "failure" };
 
-    Ok(query_info)
+    metrics::BIRTHDAY_ROLE_UPDATES.inc(&[action, result]);
 }
 
-fn add_new_bdays(
-    transaction: &Transaction, curr_date_time: DateTime<Utc>,
-) -> rusqlite::Result<Vec<(u64, u64, u64)>> {
-    let mut query_info = Vec::new();
-    let mut user_selection_statement = if curr_date_time.month() != 1 || curr_date_time.day() != 1 {
-        // If not Jan. 1
-        transaction.prepare(
-            "
-                SELECT 
-                    bday.user_id, 
-                    bday.guild_id,
-                    bday_role_list.role_id,
-                    bday_date
-                FROM bday
-                    INNER JOIN bday_role_list ON bday.guild_id = bday_role_list.guild_id
-                WHERE bday_date < ? AND bday_date > ?;
-            ",
-        )?
-    } else {
-        // If Jan. 1, we must ensure wrapping around is okay.
-        transaction.prepare(
-            "
-                    SELECT 
-                        bday.user_id, 
-                        bday.guild_id,
-                        bday_role_list.role_id,
-                        bday_date
-                    FROM bday
-                        INNER JOIN bday_role_list ON bday.guild_id = bday_role_list.guild_id
-                    WHERE bday_date < ? OR bday_date > ?;
-                ",
-        )?
-    }; // 6-15-4 < 6-18-12 AND 6-15-4 > 6-17-11
-
-    let earliest_date_time = curr_date_time - Duration::hours(25); // Checks 23 hrs or less away
-    let curr_date_time_fmt = BirthdayDateTime::from(curr_date_time);
-    let earliest_date_time_fmt = BirthdayDateTime::from(earliest_date_time);
-
-    let rows = user_selection_statement.query_map(
-        [curr_date_time_fmt, earliest_date_time_fmt],
-        |row| {
-            Ok((
-                row.get::<_, u64>(0)?,
-                row.get::<_, u64>(1)?,
-                row.get::<_, u64>(2)?,
-                row.get::<_, BirthdayDateTime>(3)?,
-            ))
-        },
-    )?;
+/// A minute ahead, since birthdays have to start before the time that's used to get the role.
+fn get_date_time_to_use() -> DateTime<Utc> {
+    Utc::now() + Duration::minutes(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, TimeZone};
+    use serenity::all::ChannelId;
 
-    let mut insertion_statement = transaction.prepare(
-        "
-        INSERT OR IGNORE INTO bday_user_list
-            VALUES (?, ?);
-    ",
-    )?;
+    use super::*;
+    use crate::birthday_tracker::{
+        Birthday, BirthdayDetails, BirthdayTimeZone, BirthdayVisibility,
+    };
+    use crate::database::TempDatabase;
+    use crate::discord::recording::{DiscordCall, RecordingHttp, test_member};
 
-    for (user_id, guild_id, bday_role_id, bday_date) in rows.flatten() {
-        let bday_date_time = bday_date.one_day_ahead();
-        let rows_changed = insertion_statement.execute(params![user_id, bday_date_time])?;
+    const GUILD_ID: u64 = 10;
+    const ROLE_ID: u64 = 20;
+    const USER_ID: u64 = 30;
 
-        if rows_changed != 0 {
-            query_info.push((user_id, guild_id, bday_role_id));
+    fn utc() -> BirthdayTimeZone {
+        BirthdayTimeZone::Offset(FixedOffset::east_opt(0).unwrap())
+    }
+
+    async fn database_with_birthday() -> TempDatabase {
+        let db = TempDatabase::new();
+        let birthday = Birthday::new(6, 15, utc()).unwrap();
+
+        db.birthdays().set_role(GUILD_ID, ROLE_ID).await.unwrap();
+        db.birthdays().set(USER_ID, birthday, None, false).await.unwrap();
+
+        db
+    }
+
+    fn everyone(_guild_id: u64, _user_id: u64) -> bool {
+        true
+    }
+
+    fn role_change(add: bool) -> DiscordCall {
+        role_change_in(GUILD_ID, ROLE_ID, add)
+    }
+
+    fn role_change_in(guild_id: u64, role_id: u64, add: bool) -> DiscordCall {
+        let (guild_id, user_id, role_id) =
+            (GuildId::new(guild_id), UserId::new(USER_ID), RoleId::new(role_id));
+
+        if add {
+            DiscordCall::AddMemberRole { guild_id, user_id, role_id }
+        } else {
+            DiscordCall::RemoveMemberRole { guild_id, user_id, role_id }
         }
     }
 
-    Ok(query_info)
+    #[tokio::test]
+    async fn birthday_role_is_added_then_removed() {
+        let db = database_with_birthday().await;
+        let http = RecordingHttp::new();
+        let during_birthday = Utc.with_ymd_and_hms(2026, 6, 15, 5, 0, 0).unwrap();
+        let after_birthday = Utc.with_ymd_and_hms(2026, 6, 16, 5, 0, 0).unwrap();
+
+        update_birthday_roles_at(&http, &db, during_birthday, everyone).await.unwrap();
+
+        assert_eq!(http.calls(), vec![role_change(true)]);
+
+        update_birthday_roles_at(&http, &db, after_birthday, everyone).await.unwrap();
+
+        assert_eq!(http.calls(), vec![role_change(true), role_change(false)]);
+    }
+
+    #[tokio::test]
+    async fn failed_role_changes_are_returned() {
+        let db = database_with_birthday().await;
+        let http = RecordingHttp::new().fail_when(|_| true);
+        let during_birthday = Utc.with_ymd_and_hms(2026, 6, 15, 5, 0, 0).unwrap();
+        let result = update_birthday_roles_at(&http, &db, during_birthday, everyone).await;
+
+        assert!(matches!(result, Err(SerenitySQLiteError::SerenityError(errors))
+            if errors.serenity_errors.len() == 1));
+    }
+
+    #[tokio::test]
+    async fn leap_day_birthdays_follow_the_guild_policy() {
+        let db = TempDatabase::new();
+        let http = RecordingHttp::new();
+        let feb_28 = Utc.with_ymd_and_hms(2026, 2, 28, 5, 0, 0).unwrap();
+        let mar_1 = Utc.with_ymd_and_hms(2026, 3, 1, 5, 0, 0).unwrap();
+        let birthday = Birthday::new(2, 29, utc()).unwrap();
+
+        db.birthdays().set_role(GUILD_ID, ROLE_ID).await.unwrap();
+        db.birthdays().set(USER_ID, birthday, None, false).await.unwrap();
+        db.guild_configs()
+            .set(GUILD_ID, "leap_day_policy".to_owned(), "mar1".to_owned())
+            .await
+            .unwrap();
+
+        update_birthday_roles_at(&http, &db, feb_28, everyone).await.unwrap();
+
+        assert!(http.calls().is_empty());
+
+        update_birthday_roles_at(&http, &db, mar_1, everyone).await.unwrap();
+        update_birthday_roles_at(&http, &db, mar_1, everyone).await.unwrap();
+
+        // Only added once, even though the second update is still within the birthday
+        assert_eq!(http.calls(), vec![role_change(true)]);
+    }
+
+    #[tokio::test]
+    async fn birthdays_fan_out_to_guilds_the_user_is_in() {
+        const OTHER_GUILD_ID: u64 = 11;
+        const OTHER_ROLE_ID: u64 = 21;
+        const LEFT_GUILD_ID: u64 = 12;
+        const OPTED_OUT_GUILD_ID: u64 = 13;
+
+        let db = database_with_birthday().await;
+        let http = RecordingHttp::new();
+        let during_birthday = Utc.with_ymd_and_hms(2026, 6, 15, 5, 0, 0).unwrap();
+        let after_birthday = Utc.with_ymd_and_hms(2026, 6, 16, 5, 0, 0).unwrap();
+
+        db.birthdays().set_role(OTHER_GUILD_ID, OTHER_ROLE_ID).await.unwrap();
+        db.birthdays().set_role(LEFT_GUILD_ID, 22).await.unwrap();
+        db.birthdays().set_role(OPTED_OUT_GUILD_ID, 23).await.unwrap();
+        db.birthdays().opt_out(USER_ID, OPTED_OUT_GUILD_ID).await.unwrap();
+
+        let is_member = |guild_id, _user_id| guild_id != LEFT_GUILD_ID;
+
+        update_birthday_roles_at(&http, &db, during_birthday, is_member).await.unwrap();
+
+        let calls = http.calls();
+
+        assert_eq!(calls.len(), 2);
+        assert!(calls.contains(&role_change(true)));
+        assert!(calls.contains(&role_change_in(OTHER_GUILD_ID, OTHER_ROLE_ID, true)));
+
+        let removed_role = db.birthdays().opt_out(USER_ID, OTHER_GUILD_ID).await.unwrap();
+
+        assert_eq!(removed_role, Some(OTHER_ROLE_ID));
+
+        update_birthday_roles_at(&http, &db, after_birthday, is_member).await.unwrap();
+
+        // Opting out ended the birthday there, so only the first guild's role is left to remove
+        assert_eq!(http.calls()[2..], [role_change(false)]);
+    }
+
+    #[tokio::test]
+    async fn birthdays_are_announced_once() {
+        let db = database_with_birthday().await;
+        let http = RecordingHttp::new().with_member(test_member(GUILD_ID, USER_ID, &[]));
+        let channel_id = ChannelId::new(720900352018219040);
+        let during_birthday = Utc.with_ymd_and_hms(2026, 6, 15, 5, 0, 0).unwrap();
+
+        db.guild_configs()
+            .set(GUILD_ID, "birthday_channel".to_owned(), channel_id.to_string())
+            .await
+            .unwrap();
+        db.guild_configs()
+            .set(GUILD_ID, "birthday_message".to_owned(), "Happy birthday, {name}!".to_owned())
+            .await
+            .unwrap();
+
+        update_birthday_roles_at(&http, &db, during_birthday, everyone).await.unwrap();
+
+        // Ending the birthday early and starting it again gives the role back, but the
+        // announcement was already posted.
+        db.birthdays().opt_out(USER_ID, GUILD_ID).await.unwrap();
+        db.birthdays().opt_in(USER_ID, GUILD_ID).await.unwrap();
+        update_birthday_roles_at(&http, &db, during_birthday, everyone).await.unwrap();
+
+        let announcements = http.sent_to(channel_id);
+
+        assert_eq!(announcements.len(), 1);
+        assert_eq!(announcements[0]["content"], "Happy birthday, user30!");
+        assert_eq!(http.calls().iter().filter(|&call| *call == role_change(true)).count(), 2);
+    }
+
+    #[tokio::test]
+    async fn reconciliation_matches_roles_to_ongoing_birthdays() {
+        const UNCACHED_GUILD_ID: u64 = 11;
+        const OTHER_USER_ID: u64 = 31;
+
+        let db = database_with_birthday().await;
+        let http = RecordingHttp::new();
+        let ends_at = Utc.with_ymd_and_hms(2026, 6, 16, 0, 0, 0).unwrap();
+
+        db.birthdays().set_role(UNCACHED_GUILD_ID, 21).await.unwrap();
+        db.birthdays().add_ongoing(USER_ID, GUILD_ID, ends_at).await.unwrap();
+        db.birthdays().add_ongoing(USER_ID, UNCACHED_GUILD_ID, ends_at).await.unwrap();
+
+        // The bot was down when the birthday started, and someone kept the role from before.
+        let role_holders =
+            |guild_id, _role_id| (guild_id == GUILD_ID).then(|| HashSet::from([OTHER_USER_ID]));
+        let corrections =
+            reconcile_birthday_roles_with(&http, &db, role_holders, everyone).await.unwrap();
+        let removal = DiscordCall::RemoveMemberRole {
+            guild_id: GuildId::new(GUILD_ID),
+            user_id: UserId::new(OTHER_USER_ID),
+            role_id: RoleId::new(ROLE_ID),
+        };
+
+        assert_eq!((corrections.added, corrections.removed), (1, 1));
+        assert_eq!(http.calls(), vec![role_change(true), removal]);
+
+        let role_holders = |_guild_id, _role_id| Some(HashSet::from([USER_ID]));
+
+        reconcile_birthday_roles_with(&http, &db, role_holders, everyone).await.unwrap();
+
+        assert_eq!(http.calls().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn announcements_show_ages_of_public_birthdays_only() {
+        const HIDDEN_USER_ID: u64 = 31;
+
+        let db = TempDatabase::new();
+        let http = RecordingHttp::new().with_member(test_member(GUILD_ID, USER_ID, &[]));
+        let channel_id = ChannelId::new(720900352018219040);
+        let during_birthday = Utc.with_ymd_and_hms(2026, 6, 15, 5, 0, 0).unwrap();
+        let birthday = Birthday::new(6, 15, utc()).unwrap();
+        let details = |birth_year, visibility| BirthdayDetails { birth_year, visibility };
+
+        db.birthdays().set_role(GUILD_ID, ROLE_ID).await.unwrap();
+        db.birthdays()
+            .set(USER_ID, birthday, Some(details(Some(2000), BirthdayVisibility::Public)), false)
+            .await
+            .unwrap();
+        db.birthdays()
+            .set(HIDDEN_USER_ID, birthday, Some(details(None, BirthdayVisibility::Hidden)), false)
+            .await
+            .unwrap();
+        db.guild_configs()
+            .set(GUILD_ID, "birthday_channel".to_owned(), channel_id.to_string())
+            .await
+            .unwrap();
+        db.guild_configs()
+            .set(GUILD_ID, "birthday_message".to_owned(), "{name} is {age}!".to_owned())
+            .await
+            .unwrap();
+
+        update_birthday_roles_at(&http, &db, during_birthday, everyone).await.unwrap();
+
+        let announcements = http.sent_to(channel_id);
+        let role_additions = http
+            .calls()
+            .into_iter()
+            .filter(|call| matches!(call, DiscordCall::AddMemberRole { .. }))
+            .count();
+
+        // Hidden birthdays still get the role.
+        assert_eq!(role_additions, 2);
+        assert_eq!(announcements.len(), 1);
+        assert_eq!(announcements[0]["content"], "user30 is 26!");
+    }
 }
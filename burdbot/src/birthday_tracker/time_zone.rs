@@ -0,0 +1,212 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use chrono::{FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone};
+use chrono_tz::Tz;
+use lazy_static::lazy_static;
+use regex::Regex;
+use rusqlite::ToSql;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
+use thiserror::Error;
+
+/// Birthday roles are updated this often, so offsets have to be a multiple of it.
+pub const OFFSET_STEP_MINUTES: i32 = 15;
+const MIN_OFFSET_MINUTES: i32 = -12 * 60;
+const MAX_OFFSET_MINUTES: i32 = 14 * 60;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TimeZoneParseError {
+    #[error(
+        "``{0}`` isn't a UTC offset like ``-7`` or ``+5:30`` or a time zone name like \
+        ``America/Argentina/Buenos_Aires``."
+    )]
+    Unknown(String),
+    #[error("UTC offsets have to be between -12:00 and +14:00.")]
+    OutOfRange,
+    #[error("UTC offsets have to be in steps of {OFFSET_STEP_MINUTES} minutes, like ``+5:45``.")]
+    UnevenMinutes,
+}
+
+/// The time zone a birthday was given in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BirthdayTimeZone {
+    Offset(FixedOffset),
+    /// An IANA time zone, whose offset depends on the date because of daylight saving time.
+    Named(Tz),
+}
+
+impl BirthdayTimeZone {
+    /// The UTC offset at the start of `date`.
+    pub fn offset_on(&self, date: NaiveDate) -> FixedOffset {
+        match self {
+            BirthdayTimeZone::Offset(offset) => *offset,
+            BirthdayTimeZone::Named(tz) => {
+                let midnight = date.and_time(NaiveTime::MIN);
+
+                // Midnight doesn't exist on days where the clocks skip over it,
+                // so the offset from before the skip is used instead.
+                match tz.offset_from_local_datetime(&midnight).earliest() {
+                    Some(offset) => offset.fix(),
+                    None => tz.offset_from_utc_datetime(&midnight).fix(),
+                }
+            },
+        }
+    }
+
+    /// When `date` starts in this time zone, in UTC.
+    pub fn start_of(&self, date: NaiveDate) -> NaiveDateTime {
+        let midnight = date.and_time(NaiveTime::MIN);
+
+        midnight - self.offset_on(date)
+    }
+
+    /// Describes the time zone for a message about `date`, like `UTC+5:30`
+    /// or `America/New_York time (UTC-4)`.
+    pub fn describe_on(&self, date: NaiveDate) -> String {
+        match self {
+            BirthdayTimeZone::Offset(offset) => format_offset(*offset),
+            BirthdayTimeZone::Named(tz) => {
+                format!("{} time ({})", tz.name(), format_offset(self.offset_on(date)))
+            },
+        }
+    }
+}
+
+fn format_offset(offset: FixedOffset) -> String {
+    let minutes = offset.local_minus_utc() / 60;
+    let sign = if minutes < 0 { '-' } else { '+' };
+    let (hours, minutes) = (minutes.abs() / 60, minutes.abs() % 60);
+
+    match (hours, minutes) {
+        (0, 0) => "UTC".to_owned(),
+        (hours, 0) => format!("UTC{sign}{hours}"),
+        (hours, minutes) => format!("UTC{sign}{hours}:{minutes:02}"),
+    }
+}
+
+fn parse_offset(
+    sign: &str, hours: &str, minutes: Option<&str>,
+) -> Result<FixedOffset, TimeZoneParseError> {
+    let hours = hours.parse::<i32>().unwrap();
+    let minutes = minutes.map_or(0, |minutes| minutes.parse::<i32>().unwrap());
+
+    if minutes >= 60 || minutes % OFFSET_STEP_MINUTES != 0 {
+        return Err(TimeZoneParseError::UnevenMinutes);
+    }
+
+    let total_minutes = if sign == "-" { -(hours * 60 + minutes) } else { hours * 60 + minutes };
+
+    if !(MIN_OFFSET_MINUTES..=MAX_OFFSET_MINUTES).contains(&total_minutes) {
+        return Err(TimeZoneParseError::OutOfRange);
+    }
+
+    Ok(FixedOffset::east_opt(total_minutes * 60).expect("Offsets are checked to be in range."))
+}
+
+impl FromStr for BirthdayTimeZone {
+    type Err = TimeZoneParseError;
+
+    /// Parses offsets like `-7`, `+5:30` and `UTC+5:45`, or IANA names like `Asia/Kolkata`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        lazy_static! {
+            static ref OFFSET_MATCHER: Regex =
+                Regex::new(r"(?i)^(?:UTC|GMT)?([+-]?)(\d{1,2})(?::(\d{2}))?$").unwrap();
+        }
+
+        let s = s.trim();
+
+        // Without this, they'd parse as the named time zones, which are the same thing.
+        if s.eq_ignore_ascii_case("UTC") || s.eq_ignore_ascii_case("GMT") {
+            return Ok(BirthdayTimeZone::Offset(FixedOffset::east_opt(0).unwrap()));
+        }
+
+        if let Some(groups) = OFFSET_MATCHER.captures(s) {
+            let sign = groups.get(1).unwrap().as_str();
+            let hours = groups.get(2).unwrap().as_str();
+            let minutes = groups.get(3).map(|minutes| minutes.as_str());
+
+            return parse_offset(sign, hours, minutes).map(BirthdayTimeZone::Offset);
+        }
+
+        s.parse::<Tz>()
+            .map(BirthdayTimeZone::Named)
+            .map_err(|_| TimeZoneParseError::Unknown(s.to_owned()))
+    }
+}
+
+impl Display for BirthdayTimeZone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BirthdayTimeZone::Offset(offset) => f.write_str(&format_offset(*offset)),
+            BirthdayTimeZone::Named(tz) => f.write_str(tz.name()),
+        }
+    }
+}
+
+/// Stored the way it's displayed, which parses back to the same time zone.
+impl ToSql for BirthdayTimeZone {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_string()))
+    }
+}
+
+impl FromSql for BirthdayTimeZone {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str()?.parse().map_err(|error| FromSqlError::Other(Box::new(error)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offset(minutes: i32) -> BirthdayTimeZone {
+        BirthdayTimeZone::Offset(FixedOffset::east_opt(minutes * 60).unwrap())
+    }
+
+    #[test]
+    fn parses_offsets() {
+        assert_eq!("-7".parse(), Ok(offset(-7 * 60)));
+        assert_eq!("14".parse(), Ok(offset(14 * 60)));
+        assert_eq!("+5:30".parse(), Ok(offset(5 * 60 + 30)));
+        assert_eq!("UTC+5:45".parse(), Ok(offset(5 * 60 + 45)));
+        assert_eq!("-3:30".parse(), Ok(offset(-(3 * 60 + 30))));
+        assert_eq!("+15".parse::<BirthdayTimeZone>(), Err(TimeZoneParseError::OutOfRange));
+        assert_eq!("+5:20".parse::<BirthdayTimeZone>(), Err(TimeZoneParseError::UnevenMinutes));
+        assert_eq!("UTC".parse(), Ok(offset(0)));
+    }
+
+    #[test]
+    fn parses_names() {
+        let buenos_aires = "America/Argentina/Buenos_Aires".parse::<BirthdayTimeZone>().unwrap();
+
+        assert_eq!(buenos_aires, BirthdayTimeZone::Named(Tz::America__Argentina__Buenos_Aires));
+        assert!(matches!(
+            "Mars/Olympus_Mons".parse::<BirthdayTimeZone>(),
+            Err(TimeZoneParseError::Unknown(_))
+        ));
+    }
+
+    #[test]
+    fn named_zones_follow_daylight_saving_time() {
+        let new_york = BirthdayTimeZone::Named(Tz::America__New_York);
+        let winter = NaiveDate::from_ymd_opt(2026, 1, 15).unwrap();
+        let summer = NaiveDate::from_ymd_opt(2026, 7, 15).unwrap();
+
+        assert_eq!(new_york.start_of(winter), winter.and_hms_opt(5, 0, 0).unwrap());
+        assert_eq!(new_york.start_of(summer), summer.and_hms_opt(4, 0, 0).unwrap());
+        assert_eq!(new_york.describe_on(summer), "America/New_York time (UTC-4)");
+    }
+
+    #[test]
+    fn fractional_offsets_start_at_local_midnight() {
+        let india = "+5:30".parse::<BirthdayTimeZone>().unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+
+        assert_eq!(
+            india.start_of(date),
+            NaiveDate::from_ymd_opt(2026, 2, 28).unwrap().and_hms_opt(18, 30, 0).unwrap()
+        );
+        assert_eq!(india.to_string(), "UTC+5:30");
+    }
+}
@@ -0,0 +1,367 @@
+//! Importing and exporting a guild's birthdays as CSV or JSON, for moving from other bots.
+//!
+//! Both formats have the same fields: `user_id`, `month`, `day`, `time_zone`, `birth_year` and
+//! `visibility`. The last two can be left empty, or out entirely, for an unknown birth year and a
+//! public birthday. User IDs are strings in JSON so that they don't lose precision in JavaScript,
+//! but numbers are accepted too.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use chrono::{Datelike, Utc};
+use serde_json::{Value, json};
+use serenity::all::{Attachment, CreateAttachment, GuildId, UserId};
+use serenity::client::Context;
+use strum_macros::{Display, EnumIter, EnumString};
+
+use super::listing::guild_birthday_details;
+use super::{Birthday, BirthdayDetails, BirthdayVisibility, MIN_BIRTH_YEAR};
+use crate::database;
+use crate::error::SerenitySQLiteResult;
+
+const CSV_HEADER: &str = "user_id,month,day,time_zone,birth_year,visibility";
+
+/// The fields every row needs, which come first.
+const REQUIRED_FIELDS: [&str; 4] = ["user_id", "month", "day", "time_zone"];
+
+const OPTIONAL_FIELDS: [&str; 2] = ["birth_year", "visibility"];
+
+/// The most row errors listed in a reply, so that it fits in a message.
+const MAX_LISTED_ERRORS: usize = 15;
+
+const MAX_IMPORT_FILE_SIZE: u32 = 1024 * 1024;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Display, EnumString, EnumIter)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum BirthdayFileFormat {
+    Csv,
+    Json,
+}
+
+impl BirthdayFileFormat {
+    /// Gets the format from a file name's extension.
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        file_name.rsplit_once('.')?.1.parse().ok()
+    }
+}
+
+/// A user's birthday, as it's imported and exported.
+type BirthdayRecord = (u64, Birthday, BirthdayDetails);
+
+/// A row of an imported file, numbered from 1. For CSV files, that's the line number.
+type Row = (usize, Result<BirthdayRecord, String>);
+
+/// Why a row, or the whole file if the row is 0, couldn't be imported.
+type RowError = (usize, String);
+
+/// Parses a row's fields, in the order of [`CSV_HEADER`]. The optional ones can be empty.
+fn parse_row(fields: &[&str; 6]) -> Result<BirthdayRecord, String> {
+    let [user_id, month, day, time_zone, birth_year, visibility] = *fields;
+    let user_id = user_id.parse::<u64>().map_err(|_| format!("``{user_id}`` isn't a user ID."))?;
+    let (month, day) = match (month.parse::<u32>(), day.parse::<u32>()) {
+        (Ok(month), Ok(day)) => (month, day),
+        _ => return Err(format!("``{month}/{day}`` isn't a month and day.")),
+    };
+    let time_zone = time_zone.parse().map_err(|error| format!("{error}"))?;
+    let birthday = Birthday::new(month, day, time_zone)
+        .ok_or_else(|| format!("``{month}/{day}`` isn't a month and day."))?;
+    let max_year = Utc::now().year();
+    let birth_year = match birth_year {
+        "" => None,
+        _ => match birth_year.parse::<i32>() {
+            Ok(year) if (MIN_BIRTH_YEAR..=max_year).contains(&year) => Some(year),
+            _ => {
+                return Err(format!(
+                    "``{birth_year}`` isn't a birth year between {MIN_BIRTH_YEAR} and {max_year}."
+                ));
+            },
+        },
+    };
+    let visibility = match visibility {
+        "" => BirthdayVisibility::default(),
+        _ => visibility.parse().map_err(|_| {
+            format!("``{visibility}`` isn't a visibility. It can be public, staff or hidden.")
+        })?,
+    };
+
+    Ok((user_id, birthday, BirthdayDetails { birth_year, visibility }))
+}
+
+/// Whether a CSV line is the header, with or without the optional fields.
+fn is_csv_header(line: &str) -> bool {
+    let line = line.trim();
+
+    [CSV_HEADER, &REQUIRED_FIELDS.join(",")].iter().any(|header| line.eq_ignore_ascii_case(header))
+}
+
+fn parse_csv(text: &str) -> Vec<Row> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter(|(idx, line)| *idx != 0 || !is_csv_header(line))
+        .map(|(idx, line)| {
+            let fields =
+                line.split(',').map(|field| field.trim().trim_matches('"')).collect::<Vec<_>>();
+            let row = match fields[..] {
+                [user_id, month, day, time_zone, ref optional @ ..] if optional.len() <= 2 => {
+                    let birth_year = optional.first().copied().unwrap_or_default();
+                    let visibility = optional.get(1).copied().unwrap_or_default();
+
+                    parse_row(&[user_id, month, day, time_zone, birth_year, visibility])
+                },
+                _ => Err(format!("Expected the columns ``{CSV_HEADER}``, or the first 4 of them.")),
+            };
+
+            (idx + 1, row)
+        })
+        .collect()
+}
+
+/// Gets a field as a string, whether it's a string or a number.
+fn json_field(record: &Value, name: &str) -> Result<String, String> {
+    match &record[name] {
+        Value::String(value) => Ok(value.clone()),
+        Value::Number(value) => Ok(value.to_string()),
+        _ => Err(format!("Missing ``{name}``.")),
+    }
+}
+
+/// Gets a field that can be left out or null as a string, which is empty if it is.
+fn optional_json_field(record: &Value, name: &str) -> Result<String, String> {
+    match &record[name] {
+        Value::Null => Ok(String::new()),
+        _ => json_field(record, name),
+    }
+}
+
+fn parse_json(text: &str) -> Result<Vec<Row>, String> {
+    let records = match serde_json::from_str::<Value>(text) {
+        Ok(Value::Array(records)) => records,
+        Ok(_) => return Err("The file should have a list of birthdays.".to_owned()),
+        Err(error) => return Err(format!("The file isn't valid JSON: {error}")),
+    };
+
+    Ok(records
+        .iter()
+        .enumerate()
+        .map(|(idx, record)| {
+            let required = REQUIRED_FIELDS.map(|name| json_field(record, name));
+            let optional = OPTIONAL_FIELDS.map(|name| optional_json_field(record, name));
+            let row = required.into_iter().chain(optional).collect::<Result<Vec<_>, _>>().and_then(
+                |fields| {
+                    let fields = std::array::from_fn(|idx| fields[idx].as_str());
+
+                    parse_row(&fields)
+                },
+            );
+
+            (idx + 1, row)
+        })
+        .collect())
+}
+
+/// Parses a file and checks that every user is a member and only appears once.
+/// Returns the birthdays, or the errors for each bad row.
+fn validate_birthday_file(
+    format: BirthdayFileFormat, text: &str, is_member: impl Fn(u64) -> bool,
+) -> Result<Vec<BirthdayRecord>, Vec<RowError>> {
+    let rows = match format {
+        BirthdayFileFormat::Csv => parse_csv(text),
+        BirthdayFileFormat::Json => parse_json(text).map_err(|error| vec![(0, error)])?,
+    };
+    let mut first_rows = HashMap::new();
+    let mut birthdays = Vec::new();
+    let mut errors = Vec::new();
+
+    for (row_number, row) in rows {
+        let checked = row.and_then(|(user_id, birthday, details)| {
+            if !is_member(user_id) {
+                return Err(format!("{user_id} isn't in this server."));
+            }
+
+            if let Some(first_row) = first_rows.insert(user_id, row_number) {
+                return Err(format!("{user_id} is already in row {first_row}."));
+            }
+
+            Ok((user_id, birthday, details))
+        });
+
+        match checked {
+            Ok(birthday) => birthdays.push(birthday),
+            Err(error) => errors.push((row_number, error)),
+        }
+    }
+
+    if errors.is_empty() { Ok(birthdays) } else { Err(errors) }
+}
+
+fn format_errors(errors: &[RowError]) -> String {
+    let mut reply = "Nothing was imported because of these problems:".to_owned();
+
+    for (row_number, error) in errors.iter().take(MAX_LISTED_ERRORS) {
+        if *row_number == 0 {
+            let _ = write!(reply, "\n{error}");
+        } else {
+            let _ = write!(reply, "\nRow {row_number}: {error}");
+        }
+    }
+
+    if errors.len() > MAX_LISTED_ERRORS {
+        let _ = write!(reply, "\n...and {} more.", errors.len() - MAX_LISTED_ERRORS);
+    }
+
+    reply
+}
+
+/// Imports the birthdays in an attached file if every row is valid, and returns the reply.
+/// Users who already have a birthday keep it.
+pub async fn import_birthdays(
+    ctx: &Context, guild_id: GuildId, attachment: &Attachment,
+) -> SerenitySQLiteResult<String> {
+    let Some(format) = BirthdayFileFormat::from_file_name(&attachment.filename) else {
+        return Ok("The file has to be a ``.csv`` or ``.json`` file.".to_owned());
+    };
+
+    if attachment.size > MAX_IMPORT_FILE_SIZE {
+        return Ok(format!("The file can't be bigger than {} KiB.", MAX_IMPORT_FILE_SIZE / 1024));
+    }
+
+    let contents = attachment.download().await?;
+    let Ok(text) = String::from_utf8(contents) else {
+        return Ok("The file has to be UTF-8 text.".to_owned());
+    };
+
+    let is_member = |user_id| ctx.cache.member(guild_id, UserId::new(user_id)).is_some();
+    let birthdays = match validate_birthday_file(format, &text, is_member) {
+        Ok(birthdays) => birthdays,
+        Err(errors) => return Ok(format_errors(&errors)),
+    };
+
+    let total = birthdays.len();
+    let saved = database::get(ctx).await.birthdays().set_many(birthdays).await?;
+
+    Ok(format!(
+        "Imported {saved} birthdays. {} users already had a birthday, which was kept.",
+        total - saved
+    ))
+}
+
+fn export_file(format: BirthdayFileFormat, birthdays: &[BirthdayRecord]) -> String {
+    match format {
+        BirthdayFileFormat::Csv => {
+            let mut csv = format!("{CSV_HEADER}\n");
+
+            for (user_id, birthday, details) in birthdays {
+                let (month, day, time_zone) =
+                    (birthday.month(), birthday.day(), birthday.time_zone());
+                let birth_year =
+                    details.birth_year.map(|year| year.to_string()).unwrap_or_default();
+                let visibility = details.visibility;
+                let _ =
+                    writeln!(csv, "{user_id},{month},{day},{time_zone},{birth_year},{visibility}");
+            }
+
+            csv
+        },
+        BirthdayFileFormat::Json => {
+            let records = birthdays
+                .iter()
+                .map(|(user_id, birthday, details)| {
+                    json!({
+                        "user_id": user_id.to_string(),
+                        "month": birthday.month(),
+                        "day": birthday.day(),
+                        "time_zone": birthday.time_zone().to_string(),
+                        "birth_year": details.birth_year,
+                        "visibility": details.visibility.to_string(),
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            serde_json::to_string_pretty(&records).expect("JSON values should serialize.")
+        },
+    }
+}
+
+/// Exports the birthdays of `guild_id`'s members in a file that can be imported again. Hidden
+/// birthdays are left out, and staff-only ones keep their visibility.
+pub async fn export_birthdays(
+    ctx: &Context, guild_id: GuildId, format: BirthdayFileFormat,
+) -> rusqlite::Result<CreateAttachment> {
+    let mut birthdays = guild_birthday_details(ctx, guild_id, true).await?;
+
+    birthdays.sort_unstable_by_key(|&(user_id, ..)| user_id);
+
+    let file_name = format!("birthdays.{format}");
+
+    Ok(CreateAttachment::bytes(export_file(format, &birthdays).into_bytes(), file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn everyone(_: u64) -> bool {
+        true
+    }
+
+    #[test]
+    fn exports_round_trip() {
+        let staff_only =
+            BirthdayDetails { birth_year: Some(1990), visibility: BirthdayVisibility::Staff };
+        let birthdays = vec![
+            (
+                367538590520967181,
+                Birthday::new(10, 6, "-7".parse().unwrap()).unwrap(),
+                BirthdayDetails::default(),
+            ),
+            (2, Birthday::new(2, 29, "America/New_York".parse().unwrap()).unwrap(), staff_only),
+        ];
+
+        for format in [BirthdayFileFormat::Csv, BirthdayFileFormat::Json] {
+            let file = export_file(format, &birthdays);
+
+            assert_eq!(validate_birthday_file(format, &file, everyone), Ok(birthdays.clone()));
+        }
+    }
+
+    #[test]
+    fn reports_every_bad_row() {
+        let csv = "user_id,month,day,time_zone\n\
+            1,10,6,-7\n\
+            2,2,30,UTC\n\
+            3,1,1,Mars/Olympus_Mons\n\
+            1,3,3,+1\n\
+            4,3,3\n\
+            5,3,3,UTC\n\
+            6,3,3,UTC,1800\n\
+            7,3,3,UTC,,secret";
+        let is_member = |user_id| user_id != 5;
+        let errors = validate_birthday_file(BirthdayFileFormat::Csv, csv, is_member).unwrap_err();
+        let rows = errors.iter().map(|(row, _)| *row).collect::<Vec<_>>();
+
+        assert_eq!(rows, [3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(errors[2].1, "1 is already in row 2.");
+    }
+
+    #[test]
+    fn accepts_numeric_json_ids() {
+        let json = r#"[{"user_id": 1, "month": 10, "day": 6, "time_zone": "-7"}]"#;
+        let birthdays = validate_birthday_file(BirthdayFileFormat::Json, json, everyone).unwrap();
+
+        // Without a birth year or visibility, like files from other bots
+        assert_eq!(birthdays[0].0, 1);
+        assert_eq!(birthdays[0].2, BirthdayDetails::default());
+        assert!(validate_birthday_file(BirthdayFileFormat::Json, "{}", everyone).is_err());
+    }
+
+    #[test]
+    fn gets_format_from_file_name() {
+        assert_eq!(BirthdayFileFormat::from_file_name("bdays.CSV"), Some(BirthdayFileFormat::Csv));
+        assert_eq!(
+            BirthdayFileFormat::from_file_name("bdays.json"),
+            Some(BirthdayFileFormat::Json)
+        );
+        assert_eq!(BirthdayFileFormat::from_file_name("bdays.txt"), None);
+    }
+}
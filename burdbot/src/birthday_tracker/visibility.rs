@@ -0,0 +1,71 @@
+use rusqlite::ToSql;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
+use strum_macros::{Display, EnumIter, EnumString};
+
+/// The earliest birth year that can be given.
+pub const MIN_BIRTH_YEAR: i32 = 1900;
+
+/// Who can see a birthday's date and age. Every birthday gets the role, whatever this is.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Display, EnumString, EnumIter)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum BirthdayVisibility {
+    /// Shown in announcements, birthday lists and calendars.
+    #[default]
+    Public,
+    /// Only shown to staff, like in getuserbirthday and exports.
+    Staff,
+    /// Never shown to anyone.
+    Hidden,
+}
+
+impl BirthdayVisibility {
+    /// Whether the date can be shown to everyone, or only to staff if `to_staff` is set.
+    pub fn is_shown(self, to_staff: bool) -> bool {
+        match self {
+            BirthdayVisibility::Public => true,
+            BirthdayVisibility::Staff => to_staff,
+            BirthdayVisibility::Hidden => false,
+        }
+    }
+}
+
+impl ToSql for BirthdayVisibility {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_string()))
+    }
+}
+
+impl FromSql for BirthdayVisibility {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str()?.parse().map_err(|error| FromSqlError::Other(Box::new(error)))
+    }
+}
+
+/// What a member can choose about their birthday besides its date.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct BirthdayDetails {
+    pub birth_year: Option<i32>,
+    pub visibility: BirthdayVisibility,
+}
+
+impl BirthdayDetails {
+    /// The age turned on the birthday in `year`, if the birth year is known.
+    pub fn age_in(&self, year: i32) -> Option<u32> {
+        self.birth_year.and_then(|birth_year| u32::try_from(year - birth_year).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ages_need_a_birth_year() {
+        let details = BirthdayDetails { birth_year: Some(2000), ..Default::default() };
+
+        assert_eq!(details.age_in(2026), Some(26));
+        assert_eq!(details.age_in(1999), None);
+        assert_eq!(BirthdayDetails::default().age_in(2026), None);
+        assert_eq!("Staff".parse(), Ok(BirthdayVisibility::Staff));
+    }
+}
@@ -1,33 +1,46 @@
 mod administrative;
 mod birthday;
 mod easter_egg;
-mod error_util;
 mod language;
+mod server_config;
 
 pub mod custom;
+pub mod error_util;
 pub mod vocaroo;
 
 pub use administrative::ADMINISTRATIVE_GROUP;
 pub use birthday::BIRTHDAY_GROUP;
-pub use birthday::BirthdayInfoConfirmation;
-pub use birthday::MONTH_TO_DAYS;
 // pub use birthday::MONTH_TO_NAME;
 pub use custom::CUSTOM_GROUP;
 pub use easter_egg::EASTEREGG_GROUP;
 pub use language::LANGUAGE_GROUP;
+pub use server_config::SERVERCONFIG_GROUP;
 pub use vocaroo::VOCAROO_GROUP;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
 
+use chrono::Utc;
+use lazy_static::lazy_static;
+use log::error;
 use serenity::client::Context;
 use serenity::framework::standard::Args;
+use serenity::framework::standard::CommandError;
 use serenity::framework::standard::CommandGroup;
 use serenity::framework::standard::CommandResult;
 use serenity::framework::standard::HelpOptions;
 use serenity::framework::standard::help_commands;
 use serenity::framework::standard::macros::help;
 use serenity::model::channel::Message;
-use serenity::model::id::UserId;
+use serenity::model::id::{ChannelId, GuildId, UserId};
+
+use crate::config::{self, BucketConfig};
+use crate::database::{self, NewCommandUsage};
+use crate::guild_config;
+
+pub type SlashContext<'a> = poise::Context<'a, (), CommandError>;
+pub type SlashCommand = poise::Command<(), CommandError>;
 
 #[help]
 #[strikethrough_commands_tip_in_dm("")]
@@ -41,7 +54,115 @@ async fn help(
     context: &Context, msg: &Message, args: Args, help_options: &'static HelpOptions,
     groups: &[&'static CommandGroup], owners: HashSet<UserId>,
 ) -> CommandResult {
-    help_commands::with_embeds(context, msg, args, help_options, groups, owners).await?;
+    let prefix = guild_config::get_prefix(context, msg.guild_id).await;
+    let help_options = HelpOptions {
+        individual_command_tip: individual_command_tip(&prefix),
+        ..help_options.clone()
+    };
+
+    help_commands::with_embeds(context, msg, args, &help_options, groups, owners).await?;
 
     Ok(())
 }
+
+/// The help tip for a prefix. [`HelpOptions`] only takes static strings,
+/// so the tip for each prefix gets leaked the first time it's needed.
+fn individual_command_tip(prefix: &str) -> &'static str {
+    lazy_static! {
+        static ref TIPS: Mutex<HashMap<String, &'static str>> = Mutex::new(HashMap::new());
+    }
+
+    let mut tips = TIPS.lock().unwrap();
+
+    tips.entry(prefix.to_owned()).or_insert_with(|| {
+        let tip = format!(
+            "The prefix here is ``{prefix}``. To get help with an individual \
+            command, pass its name as an argument to this command, like ``{prefix}help pronounce``."
+        );
+
+        Box::leak(tip.into_boxed_str())
+    })
+}
+
+/// Saves a use of a prefix or slash command for the stats command.
+/// `error` is what the command failed with, if it did.
+pub async fn record_command_usage(
+    ctx: &Context, command: &str, guild_id: Option<GuildId>, channel_id: ChannelId,
+    user_id: UserId, error: Option<&CommandError>, duration: Duration,
+) {
+    let usage = NewCommandUsage {
+        command: command.to_owned(),
+        guild_id: guild_id.map(GuildId::get),
+        channel_id: channel_id.get(),
+        user_id: user_id.get(),
+        error_kind: error.map(error_util::error_kind),
+        duration_ms: duration.as_millis() as u64,
+        used_at: Utc::now(),
+    };
+
+    if let Err(err) = database::get(ctx).await.command_usage().record(usage).await {
+        error!("Couldn't record a use of {command}: {err:?}");
+    }
+}
+
+/// Gives a slash command and its subcommands the delay of a prefix command bucket as a per-user
+/// cooldown. Discord only invokes the subcommands of a command that has them, so those are the
+/// ones that need it.
+fn with_bucket(mut command: SlashCommand, bucket: BucketConfig) -> SlashCommand {
+    let user = Some(Duration::from_secs(bucket.delay));
+
+    *command.cooldown_config.write().unwrap() =
+        poise::CooldownConfig { user, ..Default::default() };
+    command.subcommands =
+        command.subcommands.into_iter().map(|command| with_bucket(command, bucket)).collect();
+
+    command
+}
+
+/// All slash commands. The easter eggs aren't here: their names and bodies are only decrypted at
+/// build time, with a key that isn't in the repository, and they're written against the `Message`
+/// of a prefix command. They stay prefix only until someone with the key rewrites them.
+pub fn slash_commands() -> Vec<SlashCommand> {
+    let buckets = &config::get().buckets;
+
+    vec![
+        with_bucket(birthday::setmybirthday_slash(), buckets.db_operations),
+        with_bucket(birthday::setuserbirthday_slash(), buckets.db_operations),
+        with_bucket(birthday::removeuserbirthday_slash(), buckets.db_operations),
+        with_bucket(birthday::getmybirthday_slash(), buckets.db_operations),
+        with_bucket(birthday::getuserbirthday_slash(), buckets.db_operations),
+        with_bucket(birthday::birthdayoptout_slash(), buckets.db_operations),
+        with_bucket(birthday::birthdayoptin_slash(), buckets.db_operations),
+        with_bucket(birthday::birthdayvisibility_slash(), buckets.db_operations),
+        with_bucket(birthday::upcomingbirthdays_slash(), buckets.db_operations),
+        with_bucket(birthday::birthdays_slash(), buckets.db_operations),
+        with_bucket(birthday::birthdaycalendar_slash(), buckets.intense),
+        with_bucket(birthday::importbirthdays_slash(), buckets.very_intense),
+        with_bucket(birthday::exportbirthdays_slash(), buckets.intense),
+        with_bucket(birthday::setserverbirthdayrole_slash(), buckets.very_intense),
+        with_bucket(birthday::getserverbirthdayrole_slash(), buckets.db_operations),
+        with_bucket(birthday::removeserverbirthdayrole_slash(), buckets.db_operations),
+        with_bucket(administrative::stafflog_slash(), buckets.db_operations),
+        administrative::addstafflog_slash(),
+        administrative::editstafflog_slash(),
+        administrative::removestafflog_slash(),
+        administrative::restorestafflog_slash(),
+        with_bucket(administrative::searchstafflogs_slash(), buckets.db_operations),
+        with_bucket(administrative::stats_slash(), buckets.db_operations),
+        custom::banfrommemes_slash(),
+        custom::unbanfrommemes_slash(),
+        custom::banfromlectura_slash(),
+        custom::unbanfromlectura_slash(),
+        custom::banimage_slash(),
+        custom::unbanimage_slash(),
+        custom::bannedimages_slash(),
+        with_bucket(vocaroo::enablevocarootomp3_slash(), buckets.db_operations),
+        with_bucket(vocaroo::disablevocarootomp3_slash(), buckets.db_operations),
+        vocaroo::isvocarootomp3enabled_slash(),
+        with_bucket(language::pronounce_slash(), buckets.intense),
+        with_bucket(server_config::guildconfig_slash(), buckets.db_operations),
+        with_bucket(server_config::setguildconfig_slash(), buckets.db_operations),
+        with_bucket(server_config::unsetguildconfig_slash(), buckets.db_operations),
+        with_bucket(server_config::setprefix_slash(), buckets.db_operations),
+    ]
+}
@@ -1,7 +1,8 @@
-use lazy_static::lazy_static;
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
 use log::error;
-use regex::Regex;
-use rusqlite::{Connection, params};
+use poise::CreateReply;
 use serenity::all::{CreateEmbedAuthor, CreateEmbedFooter};
 use serenity::builder::{CreateEmbed, CreateMessage};
 use serenity::client::Context;
@@ -10,64 +11,44 @@ use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::Color;
 use serenity::model::channel::Message;
 use serenity::model::guild::Member;
-use serenity::model::id::MessageId;
+use serenity::model::id::{ChannelId, GuildId};
 use serenity::model::prelude::User;
+use strum::IntoEnumIterator;
 
-use crate::{BURDBOT_DB, argument_parser};
+use crate::argument_parser;
+use crate::commands::SlashContext;
+use crate::database::{
+    self, Database, StaffLog, StaffLogCategory, StaffLogDeletion, StaffLogRevision, StaffLogSearch,
+    StaffLogSeverity,
+};
+use crate::discord::DiscordHttp;
 
 use crate::argument_parser::{
     ArgumentConversionError, ArgumentInfo, ArgumentParseError, BoundedArgumentInfo, ConversionType,
 };
 
 const GONE_WRONG: &str = "Something's gone wrong. <@367538590520967181> has been notified.";
-
-fn get_message_id_from_link(link: &str) -> u64 {
-    lazy_static! {
-        static ref MESSAGE_ID_REGEX: Regex = Regex::new(r"\d+/*$").expect("Bad message ID regex.");
-    }
-
-    let mat = MESSAGE_ID_REGEX.find(link).expect("MESSAGE_ID_REGEX couldn't match link.");
-
-    let mut unsanitized_message_id = mat.as_str();
-
-    if let Some(slash_pos) = unsanitized_message_id.find('/') {
-        unsanitized_message_id = &unsanitized_message_id[0..slash_pos];
-    }
-
-    unsanitized_message_id.parse().expect(
-        "Message ID could not be parsed in link. \
-    This should never happen.",
-    )
-}
-
-struct Log {
-    entry_id: i64,
-    original_link: String,
-    last_edited_link: Option<String>,
-    reason: String,
-}
-
-impl Log {
-    fn new(
-        entry_id: i64, original_link: String, last_edited_link: Option<String>, reason: String,
-    ) -> Log {
-        Log { entry_id, original_link, last_edited_link, reason }
-    }
-
-    fn get_original_time(&self) -> i64 {
-        let message_id = get_message_id_from_link(self.original_link.as_str());
-
-        MessageId::from(message_id).created_at().timestamp()
-    }
-
-    fn get_edited_time(&self) -> Option<i64> {
-        self.last_edited_link.as_ref().map(|last_edited_link| {
-            let message_id = get_message_id_from_link(last_edited_link.as_str());
-
-            MessageId::from(message_id).created_at().timestamp()
-        })
-    }
-}
+const OWN_STAFF_LOG: &str = "You cannot read or modify your own staff log.";
+const NO_REASON: &str = "You must specify a reason for the log.";
+const LOG_NOT_FOUND: &str =
+    "Could not find the given log entry. Please verify that this log entry exists.";
+const REMOVED_LOG_NOT_FOUND: &str =
+    "Could not find the given removed log entry. Please verify that this log entry was removed.";
+/// The most revisions shown at once, since that's how many fields an embed can have.
+const MAX_SHOWN_REVISIONS: usize = 25;
+const EMBED_FIELD_MAX_LENGTH: usize = 1024;
+/// How long all the shown revisions can be together, leaving room in the embed's 6000 characters
+/// for its title, author, footer and description.
+const HISTORY_FIELDS_MAX_LENGTH: usize = 5500;
+const DEFAULT_STATS_DAYS: i64 = 7;
+const MAX_STATS_DAYS: i64 = 30;
+const CATEGORY_CHOICES_REPLY: &str = "The category has to be ``note``, ``warning``, ``mute``, \
+    ``kick``, ``ban`` or ``channel-ban``.";
+const SEVERITY_CHOICES_REPLY: &str = "The severity has to be ``low``, ``medium`` or ``high``.";
+const SEARCH_RESULTS_PER_PAGE: usize = 10;
+/// Reasons in search results are cut short so that a whole page fits in an embed.
+const SEARCH_REASON_MAX_LENGTH: usize = 300;
+const TOP_COMMAND_COUNT: usize = 10;
 
 async fn parse_staff_log_member(
     ctx: &Context, msg: &Message, args: &mut Args, arg_pos: usize, args_needed: usize,
@@ -83,9 +64,7 @@ async fn parse_staff_log_member(
 
         args.advance();
 
-        let reply = "You cannot read or modify your own staff log.";
-
-        msg.channel_id.send_message(ctx, CreateMessage::new().content(reply)).await?;
+        msg.channel_id.send_message(ctx, CreateMessage::new().content(OWN_STAFF_LOG)).await?;
 
         Err(Box::new(ArgumentParseError::ArgumentConversionError(ArgumentConversionError::new(
             arg_pos,
@@ -97,35 +76,6 @@ async fn parse_staff_log_member(
     }
 }
 
-fn get_staff_logs(id: u64) -> rusqlite::Result<Vec<Log>> {
-    let connection = Connection::open(BURDBOT_DB)?;
-    let query = "
-        SELECT original_link, last_edited_link, reason
-        FROM staff_logs
-        WHERE user_id = ?
-        ORDER BY entry_id;
-    ";
-    let mut statement = connection.prepare(query)?;
-    let rows = statement
-        .query_map([id], |row| {
-            let original_link = row.get("original_link")?;
-            let edited_link = row.get("last_edited_link")?;
-
-            Ok(Log::new(0, original_link, edited_link, row.get("reason")?))
-        })?
-        .enumerate()
-        .map(|(index, row_result)| {
-            let mut row = row_result.expect("Unwrapping this row should always be ok.");
-
-            row.entry_id = index as i64 + 1;
-
-            row
-        })
-        .collect();
-
-    Ok(rows)
-}
-
 fn id_to_color(id: u64) -> Color {
     let id_bytes = id.to_le_bytes();
     let red = id_bytes[0] ^ id_bytes[7] ^ id_bytes[4];
@@ -135,7 +85,12 @@ fn id_to_color(id: u64) -> Color {
     Color::from_rgb(red, green, blue)
 }
 
-fn format_field(log: &Log, is_first: bool) -> String {
+/// Like "Log #2 ⚠️ Warning", so that the kind of entry stands out.
+fn log_heading(log: &StaffLog) -> String {
+    format!("Log #{} {} {}", log.entry_id, log.category.badge(), log.category.name())
+}
+
+fn format_field(log: &StaffLog, is_first: bool) -> String {
     let edited_time = log.get_edited_time();
     let last_edited_text = match edited_time {
         Some(last_edited_time) => format!("**Last edited on**: <t:{last_edited_time}:f>\n"),
@@ -147,153 +102,553 @@ fn format_field(log: &Log, is_first: bool) -> String {
         None => String::new(),
     };
 
-    if is_first {
-        format!(
-            "**Logged on**: <t:{}:f>\n{}**Reason**: {}\n[See original log]({}){}",
-            log.get_original_time(),
-            last_edited_text,
-            log.reason,
-            log.original_link,
-            last_edited_link
-        )
-    } else {
-        format!(
-            "**Log #{}**:\n**Logged on**: <t:{}:f>\n{}**Reason**: {}\n[See original log]({}){}",
-            log.entry_id,
-            log.get_original_time(),
-            last_edited_text,
-            log.reason,
-            log.original_link,
-            last_edited_link
-        )
-    }
+    let removed_text = match log.deletion {
+        Some(deletion) => format!(
+            "**Removed by**: <@{}> on <t:{}:f>\n",
+            deletion.deleted_by,
+            deletion.deleted_at.timestamp()
+        ),
+        None => String::new(),
+    };
+
+    let heading = if is_first { String::new() } else { format!("**{}**:\n", log_heading(log)) };
+
+    format!(
+        "{}**Logged on**: <t:{}:f>\n{}{}**Severity**: {}\n**Reason**: {}\n[See original log]({}){}",
+        heading,
+        log.get_original_time(),
+        last_edited_text,
+        removed_text,
+        log.severity,
+        log.reason,
+        log.original_link,
+        last_edited_link
+    )
 }
 
-fn make_staff_log_embed<F>(invoker: &User, member: &Member, func: F) -> (Option<i64>, CreateMessage)
-where
-    F: FnOnce(CreateEmbed, i64) -> CreateEmbed,
-{
-    let id = member.user.id.get();
-    let mut message = CreateMessage::new();
-
-    match get_staff_logs(id) {
-        Ok(logs) => {
-            let log_count = logs.len() as i64;
-            let username = member.user.tag();
-            let nickname = member.display_name();
-            let avatar =
-                member.user.avatar_url().unwrap_or_else(|| member.user.default_avatar_url());
-            let author =
-                CreateEmbedAuthor::new(format!("{username} ({nickname})\n{id}")).icon_url(avatar);
-            let embed_footer = CreateEmbedFooter::new(format!("Requested by: {}", invoker.tag()))
-                .icon_url(invoker.avatar_url().unwrap_or_else(|| invoker.default_avatar_url()));
-            let mut embed = CreateEmbed::new()
-                .title("Staff Log")
-                .color(id_to_color(id))
-                .author(author)
-                .footer(embed_footer);
-
-            if logs.is_empty() {
-                embed = embed.description("This user has no logs.");
-            } else {
-                embed = embed.field("⁣Log #1:", format_field(&logs[0], true), false);
+/// How many entries of each category there are, like "⚠️ Warning: 2 | 🔇 Mute: 1".
+fn category_counts(logs: &[StaffLog]) -> String {
+    StaffLogCategory::iter()
+        .filter_map(|category| {
+            let count = logs.iter().filter(|log| log.category == category).count();
 
-                for log in logs.iter().skip(1) {
-                    embed = embed.field("⁣", format_field(log, false), false);
-                }
-            }
+            (count > 0).then(|| format!("{} {}: {count}", category.badge(), category.name()))
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
 
-            embed = func(embed, log_count);
-            (Some(log_count), message.embed(embed))
-        },
+/// A staff log embed about `member` without any logs in it.
+fn staff_log_embed_base(title: &str, invoker: &User, member: &Member) -> CreateEmbed {
+    let id = member.user.id.get();
+    let username = member.user.tag();
+    let nickname = member.display_name();
+    let avatar = member.user.avatar_url().unwrap_or_else(|| member.user.default_avatar_url());
+    let author = CreateEmbedAuthor::new(format!("{username} ({nickname})\n{id}")).icon_url(avatar);
+    let embed_footer = CreateEmbedFooter::new(format!("Requested by: {}", invoker.tag()))
+        .icon_url(invoker.avatar_url().unwrap_or_else(|| invoker.default_avatar_url()));
+
+    CreateEmbed::new().title(title).color(id_to_color(id)).author(author).footer(embed_footer)
+}
+
+/// Makes the embed with all of a member's logs in their guild, or logs and returns `None` if that
+/// fails. Removed logs are only shown if `include_deleted` is set, and only logs of `category`
+/// and `severity` are shown if they're given. The counts per category are of every log that
+/// could be shown.
+async fn make_staff_log_embed(
+    db: &Database, invoker: &User, member: &Member, include_deleted: bool,
+    category: Option<StaffLogCategory>, severity: Option<StaffLogSeverity>,
+) -> Option<CreateEmbed> {
+    let id = member.user.id.get();
+    let logs = match db.staff_logs().get(member.guild_id.get(), id, include_deleted).await {
+        Ok(logs) => logs,
         Err(error) => {
             error!("Error while making staff log embed: {error:?}");
 
-            message =
-                message.content("Something's gone wrong. <@367538590520967181> has been notified.");
+            return None;
+        },
+    };
+
+    let mut embed = staff_log_embed_base("Staff Log", invoker, member);
+
+    if logs.is_empty() {
+        return Some(embed.description("This user has no logs."));
+    }
+
+    let shown = logs
+        .iter()
+        .filter(|log| category.is_none_or(|category| log.category == category))
+        .filter(|log| severity.is_none_or(|severity| log.severity == severity))
+        .collect::<Vec<_>>();
+    let mut description = category_counts(&logs);
+
+    match (category.is_some() || severity.is_some(), shown.first()) {
+        (true, None) => {
+            let severity = severity.map(|severity| format!("{severity}-severity"));
+            let category = category.map(|category| category.name().to_lowercase());
+            let kind = severity.into_iter().chain(category).collect::<Vec<_>>().join(" ");
+
+            description.push_str(&format!("\nThis user has no {kind} logs."));
+        },
+        (_, Some(first)) => {
+            let first_name = format!("⁣{}:", log_heading(first));
+
+            embed = embed.field(first_name, format_field(first, true), false);
 
-            (None, message)
+            for log in shown.iter().skip(1) {
+                embed = embed.field("⁣", format_field(log, false), false);
+            }
         },
+        (false, None) => {},
     }
+
+    Some(embed.description(description))
 }
 
-fn add_log(user_id: u64, entry_id: i64, original_link: &str, reason: &str) -> rusqlite::Result<()> {
-    let connection = Connection::open(BURDBOT_DB)?;
-    let insert_query = "
-            INSERT INTO staff_logs
-                VALUES(?, ?, ?, ?, ?);
-        ";
+/// A staff log command's response, so that the prefix and slash commands can share it.
+/// Staff logs belong to the guild they're written in, which is the guild of the target `Member`
+/// given to the functions making these.
+struct StaffLogReply {
+    content: Option<&'static str>,
+    embed: Option<CreateEmbed>,
+}
 
-    connection
-        .execute(insert_query, params![user_id, entry_id, original_link, None::<u8>, reason])?;
+impl StaffLogReply {
+    fn content(content: &'static str) -> Self {
+        StaffLogReply { content: Some(content), embed: None }
+    }
 
-    Ok(())
+    /// The log embed with `content`, or an error if making the embed failed.
+    fn embed(embed: Option<CreateEmbed>, content: Option<&'static str>) -> Self {
+        match embed {
+            Some(embed) => StaffLogReply { content, embed: Some(embed) },
+            None => StaffLogReply::content(GONE_WRONG),
+        }
+    }
+
+    fn into_message(self) -> CreateMessage {
+        let mut message = CreateMessage::new();
+
+        if let Some(content) = self.content {
+            message = message.content(content);
+        }
+
+        if let Some(embed) = self.embed {
+            message = message.embed(embed);
+        }
+
+        message
+    }
+
+    async fn send(self, http: &impl DiscordHttp, channel_id: ChannelId) -> serenity::Result<()> {
+        http.send_message(channel_id, self.into_message()).await?;
+
+        Ok(())
+    }
+
+    fn into_reply(self) -> CreateReply {
+        // Always set so that editing a reply with this clears the old content.
+        let mut reply = CreateReply::default().content(self.content.unwrap_or_default());
+
+        if let Some(embed) = self.embed {
+            reply = reply.embed(embed);
+        }
+
+        reply
+    }
+}
+
+async fn view_staff_log(
+    db: &Database, invoker: &User, target: &Member, include_deleted: bool,
+    category: Option<StaffLogCategory>, severity: Option<StaffLogSeverity>,
+) -> StaffLogReply {
+    let embed =
+        make_staff_log_embed(db, invoker, target, include_deleted, category, severity).await;
+
+    StaffLogReply::embed(embed, None)
+}
+
+fn new_revision(invoker: &User, link: String, reason: &str) -> StaffLogRevision {
+    StaffLogRevision {
+        author_id: Some(invoker.id.get()),
+        link,
+        reason: reason.to_owned(),
+        revised_at: Utc::now(),
+    }
+}
+
+/// Adds a log for `target`. `link` should lead to the message that the log was made with.
+async fn add_staff_log(
+    db: &Database, invoker: &User, target: &Member, link: String, reason: &str,
+    category: StaffLogCategory, severity: StaffLogSeverity,
+) -> StaffLogReply {
+    let (guild_id, target_id) = (target.guild_id.get(), target.user.id.get());
+    let revision = new_revision(invoker, link, reason);
+
+    if let Err(err) = db.staff_logs().add(guild_id, target_id, category, severity, revision).await {
+        error!("Error while adding staff log: {err:?}");
+
+        return StaffLogReply::content(GONE_WRONG);
+    }
+
+    StaffLogReply::embed(make_staff_log_embed(db, invoker, target, false, None, None).await, None)
+}
+
+/// Edits a log of `target`. `link` should lead to the message that the edit was made with.
+async fn edit_staff_log(
+    db: &Database, invoker: &User, target: &Member, entry_id: i64, link: String, reason: &str,
+) -> CommandResult<StaffLogReply> {
+    let (guild_id, target_id) = (target.guild_id.get(), target.user.id.get());
+    let revision = new_revision(invoker, link, reason);
+
+    if !db.staff_logs().edit(guild_id, target_id, entry_id, revision).await? {
+        return Ok(StaffLogReply::content(LOG_NOT_FOUND));
+    }
+
+    let embed = make_staff_log_embed(db, invoker, target, false, None, None).await;
+
+    Ok(StaffLogReply::embed(embed, Some("Edited staff log.")))
+}
+
+/// Removes a log of `target`, which keeps its number and can be restored.
+async fn remove_staff_log(
+    db: &Database, invoker: &User, target: &Member, entry_id: i64,
+) -> CommandResult<StaffLogReply> {
+    let (guild_id, target_id) = (target.guild_id.get(), target.user.id.get());
+    let deletion = StaffLogDeletion { deleted_by: invoker.id.get(), deleted_at: Utc::now() };
+
+    if !db.staff_logs().remove(guild_id, target_id, entry_id, deletion).await? {
+        return Ok(StaffLogReply::content(LOG_NOT_FOUND));
+    }
+
+    let embed = make_staff_log_embed(db, invoker, target, false, None, None).await;
+    let content = "Successfully removed entry from staff log. It can be brought back with \
+        restorestafflog.";
+
+    Ok(StaffLogReply::embed(embed, Some(content)))
+}
+
+async fn restore_staff_log(
+    db: &Database, invoker: &User, target: &Member, entry_id: i64,
+) -> CommandResult<StaffLogReply> {
+    if !db.staff_logs().restore(target.guild_id.get(), target.user.id.get(), entry_id).await? {
+        return Ok(StaffLogReply::content(REMOVED_LOG_NOT_FOUND));
+    }
+
+    let embed = make_staff_log_embed(db, invoker, target, false, None, None).await;
+
+    Ok(StaffLogReply::embed(embed, Some("Restored staff log entry.")))
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum WordChange {
+    Kept,
+    Removed,
+    Added,
+}
+
+/// Shows the words removed from `old` crossed out and the ones added in `new` in bold.
+fn word_diff(old: &str, new: &str) -> String {
+    let old = old.split_whitespace().collect::<Vec<_>>();
+    let new = new.split_whitespace().collect::<Vec<_>>();
+    // How many words old[i..] and new[j..] have in common, in order.
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut changes = Vec::new();
+
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            changes.push((WordChange::Kept, old[i]));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+            changes.push((WordChange::Removed, old[i]));
+            i += 1;
+        } else {
+            changes.push((WordChange::Added, new[j]));
+            j += 1;
+        }
+    }
+
+    changes
+        .chunk_by(|(a, _), (b, _)| a == b)
+        .map(|run| {
+            let words = run.iter().map(|(_, word)| *word).collect::<Vec<_>>().join(" ");
+
+            match run[0].0 {
+                WordChange::Kept => words,
+                WordChange::Removed => format!("~~{words}~~"),
+                WordChange::Added => format!("**{words}**"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn truncate(value: String, max_length: usize) -> String {
+    if value.chars().count() <= max_length {
+        return value;
+    }
+
+    let mut truncated = value.chars().take(max_length - 1).collect::<String>();
+
+    truncated.push('…');
+
+    truncated
+}
+
+/// Makes the embed with every revision of a log entry, where each edit shows what it changed.
+/// The oldest revisions are left out if they don't all fit.
+fn make_history_embed(
+    invoker: &User, member: &Member, entry_id: i64, revisions: &[StaffLogRevision],
+) -> CreateEmbed {
+    let title = format!("Staff Log #{entry_id} History");
+    let embed = staff_log_embed_base(&title, invoker, member);
+    let first_shown = revisions.len().saturating_sub(MAX_SHOWN_REVISIONS);
+    let mut fields = Vec::new();
+    let mut length = 0;
+
+    for idx in (first_shown..revisions.len()).rev() {
+        let revision = &revisions[idx];
+        let author = match revision.author_id {
+            Some(author_id) => format!("<@{author_id}>"),
+            None => "Unknown".to_owned(),
+        };
+        let (action, reason) = match idx.checked_sub(1) {
+            Some(previous) => (
+                "Edited",
+                format!(
+                    "**Changes**: {}",
+                    word_diff(&revisions[previous].reason, &revision.reason)
+                ),
+            ),
+            None => ("Logged", format!("**Reason**: {}", revision.reason)),
+        };
+        let name = format!("⁣Revision #{}:", idx + 1);
+        let header =
+            format!("**{action} by**: {author} on <t:{}:f>", revision.revised_at.timestamp());
+        let link = format!("[See message]({})", revision.link);
+        // Only the reason gets cut short, so that the link still works.
+        let reason_length =
+            EMBED_FIELD_MAX_LENGTH - header.chars().count() - link.chars().count() - 2;
+        let value = format!("{header}\n{}\n{link}", truncate(reason, reason_length));
+
+        length += name.chars().count() + value.chars().count();
+
+        if length > HISTORY_FIELDS_MAX_LENGTH {
+            break;
+        }
+
+        fields.push((name, value, false));
+    }
+
+    fields.reverse();
+
+    let embed = if fields.len() < revisions.len() {
+        embed.description(format!(
+            "Showing the last {} of {} revisions.",
+            fields.len(),
+            revisions.len()
+        ))
+    } else {
+        embed
+    };
+
+    embed.fields(fields)
+}
+
+async fn staff_log_history(
+    db: &Database, invoker: &User, target: &Member, entry_id: i64,
+) -> CommandResult<StaffLogReply> {
+    let (guild_id, target_id) = (target.guild_id.get(), target.user.id.get());
+    let revisions = db.staff_logs().history(guild_id, target_id, entry_id).await?;
+
+    if revisions.is_empty() {
+        return Ok(StaffLogReply::content(LOG_NOT_FOUND));
+    }
+
+    let embed = make_history_embed(invoker, target, entry_id, &revisions);
+
+    Ok(StaffLogReply { content: None, embed: Some(embed) })
+}
+
+/// Parses the category to filter by, if one was given.
+async fn parse_optional_category(
+    ctx: &Context, msg: &Message, args: &mut Args, arg_pos: usize,
+) -> argument_parser::Result<Option<StaffLogCategory>> {
+    if args.is_empty() {
+        return Ok(None);
+    }
+
+    let arg_info = ArgumentInfo::new(args, arg_pos, arg_pos);
+
+    argument_parser::parse_choices(ctx, msg, arg_info, StaffLogCategory::iter()).await.map(Some)
+}
+
+/// Parses the `category:` and `severity:` filters of the logs to show. A category can still be
+/// given on its own after them. Returns `None` if they're invalid, after saying why.
+async fn parse_log_filters(
+    ctx: &Context, msg: &Message, args: &mut Args,
+) -> CommandResult<Option<(Option<StaffLogCategory>, Option<StaffLogSeverity>)>> {
+    let (category, severity) = match parse_log_kind_options(args) {
+        Ok(filters) => filters,
+        Err(reply) => {
+            msg.channel_id.say(ctx, reply).await?;
+
+            return Ok(None);
+        },
+    };
+    let category = match category {
+        Some(category) => Some(category),
+        None => parse_optional_category(ctx, msg, args, 2).await?,
+    };
+
+    Ok(Some((category, severity)))
 }
 
 #[command]
 #[description(
-    "Displays the staff log of someone. Staff logs can only be seen by \
-    administrators as long as it is not their own log."
+    "Displays the staff log of someone in this server, optionally only the entries of one \
+    category: note, warning, mute, kick, ban or channel-ban, and of one severity: low, medium \
+    or high. Staff logs can only be seen by administrators as long as it is not their own log."
 )]
-#[usage("<USER>")]
+#[usage("<USER> [category:CATEGORY] [severity:SEVERITY]")]
 #[example("367538590520967181")]
-#[example("DELIBURD#7741")]
+#[example("DELIBURD#7741 warning")]
+#[example("DELIBURD#7741 category:ban severity:high")]
 #[aliases("slog", "sl")]
+#[sub_commands(stafflog_all, stafflog_history)]
 #[bucket("db_operations")]
 async fn stafflog(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
     let target = parse_staff_log_member(ctx, msg, &mut args, 1, 1).await?;
+    let Some((category, severity)) = parse_log_filters(ctx, msg, &mut args).await? else {
+        return Ok(());
+    };
+    let db = database::get(ctx).await;
+    let reply = view_staff_log(&db, &msg.author, &target, false, category, severity).await;
 
-    msg.channel_id
-        .send_message(&ctx, make_staff_log_embed(&msg.author, &target, |e, _| e).1)
-        .await?;
+    reply.send(&*ctx.http, msg.channel_id).await?;
 
     Ok(())
 }
 
+#[command("all")]
+#[description(
+    "Displays the staff log of someone in this server, including removed entries and who \
+    removed them. Like stafflog, it can be filtered by category and severity. Staff logs can only \
+    be seen by administrators as long as it is not their own log."
+)]
+#[usage("<USER> [category:CATEGORY] [severity:SEVERITY]")]
+#[example("367538590520967181")]
+#[example("DELIBURD#7741 ban")]
+#[bucket("db_operations")]
+async fn stafflog_all(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target = parse_staff_log_member(ctx, msg, &mut args, 1, 1).await?;
+    let Some((category, severity)) = parse_log_filters(ctx, msg, &mut args).await? else {
+        return Ok(());
+    };
+    let db = database::get(ctx).await;
+    let reply = view_staff_log(&db, &msg.author, &target, true, category, severity).await;
+
+    reply.send(&*ctx.http, msg.channel_id).await?;
+
+    Ok(())
+}
+
+#[command("history")]
+#[description(
+    "Shows every version of a staff log entry, with what each edit changed. Staff logs can only \
+    be seen by administrators as long as it is not their own log."
+)]
+#[usage("<USER> <ENTRY NUMBER>")]
+#[example("367538590520967181 1")]
+#[example("DELIBURD#7741 1")]
+#[bucket("db_operations")]
+async fn stafflog_history(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target = parse_staff_log_member(ctx, msg, &mut args, 1, 2).await?;
+    let entry_id = argument_parser::parse_bounded_arg(
+        ctx,
+        msg,
+        BoundedArgumentInfo::new(&mut args, 2, 2, 1, i64::MAX),
+    )
+    .await?;
+
+    let db = database::get(ctx).await;
+    let reply = staff_log_history(&db, &msg.author, &target, entry_id).await?;
+
+    reply.send(&*ctx.http, msg.channel_id).await?;
+
+    Ok(())
+}
+
+/// Parses the `category:` and `severity:` options before a log's reason, in either order, and
+/// moves past them. The reason starts at the first argument that isn't one of them.
+fn parse_log_kind_options(
+    args: &mut Args,
+) -> Result<(Option<StaffLogCategory>, Option<StaffLogSeverity>), &'static str> {
+    let (mut category, mut severity) = (None, None);
+
+    while let Some((option, value)) = args.current().and_then(|arg| arg.split_once(':')) {
+        match option.to_lowercase().as_str() {
+            "category" => category = Some(value.parse().map_err(|_| CATEGORY_CHOICES_REPLY)?),
+            "severity" => severity = Some(value.parse().map_err(|_| SEVERITY_CHOICES_REPLY)?),
+            _ => break,
+        }
+
+        args.advance();
+    }
+
+    Ok((category, severity))
+}
+
 #[command]
 #[description(
-    "Adds a staff log entry. Staff logs can only be added by \
-    administrators as long as it is not their own log."
+    "Adds a staff log entry. The category can be given with ``category:`` as note, warning, mute, \
+    kick, ban or channel-ban and is a note if not given. The severity can be given with \
+    ``severity:`` as low, medium or high and defaults to the category's usual severity. Staff logs can only be added by administrators as long as it is \
+    not their own log."
 )]
-#[usage("<USER> <ENTRY>")]
+#[usage("<USER> [category:CATEGORY] [severity:SEVERITY] <ENTRY>")]
 #[example("367538590520967181 For being a bad burd")]
-#[example("DELIBURD#7741 For being a bad burd")]
+#[example("DELIBURD#7741 category:warning severity:high For being a bad burd")]
 #[aliases("addslog", "addsl", "asl")]
 async fn addstafflog(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
     let target = parse_staff_log_member(ctx, msg, &mut args, 1, 2).await?;
-    let target_id = target.user.id.get();
+    let (category, severity) = match parse_log_kind_options(&mut args) {
+        Ok(options) => options,
+        Err(reply) => {
+            msg.channel_id.say(ctx, reply).await?;
+
+            return Ok(());
+        },
+    };
+    let category = category.unwrap_or(StaffLogCategory::Note);
+    let severity = severity.unwrap_or_else(|| category.default_severity());
     let reason = match args.remains() {
         Some(reason) => reason,
         None => {
-            msg.channel_id.say(ctx, "You must specify a reason for the log.").await?;
+            msg.channel_id.say(ctx, NO_REASON).await?;
 
             return Ok(());
         },
     };
 
-    let msg_link = msg.link();
-    let (log_ct, mut msg_content) =
-        make_staff_log_embed(&msg.author, &target, |embed, log_count| {
-            let log = &Log::new(log_count + 1, msg_link.clone(), None, reason.to_string());
-
-            if log_count == 0 {
-                embed.field("⁣Log #1:", format_field(log, true), false)
-            } else {
-                embed.field("⁣", format_field(log, false), false)
-            }
-        });
-
-    if let Some(ct) = log_ct {
-        let entry_id = ct + 1;
-
-        if let Err(err) = add_log(target_id, entry_id, msg_link.as_str(), reason) {
-            error!("Error while making staff log embed: {err:?}");
-
-            msg_content = msg_content.content(GONE_WRONG)
-        }
-    }
+    let db = database::get(ctx).await;
+    let link = msg.link();
+    let reply = add_staff_log(&db, &msg.author, &target, link, reason, category, severity).await;
 
-    msg.channel_id.send_message(ctx, msg_content).await?;
+    reply.send(&*ctx.http, msg.channel_id).await?;
 
     Ok(())
 }
@@ -315,60 +670,60 @@ async fn editstafflog(ctx: &Context, msg: &Message, mut args: Args) -> CommandRe
         BoundedArgumentInfo::new(&mut args, 1, 3, 1, i64::MAX),
     )
     .await?;
-    let target_id = target.user.id.get();
     let reason = match args.remains() {
         Some(reason) => reason,
         None => {
-            msg.channel_id.say(ctx, "You must specify a reason for the log.").await?;
+            msg.channel_id.say(ctx, NO_REASON).await?;
 
             return Ok(());
         },
     };
 
-    let rows_changed;
-
-    {
-        let connection = Connection::open(BURDBOT_DB)?;
-        let update_query = "
-            UPDATE staff_logs
-                SET(last_edited_link, reason) = (?, ?)
-                WHERE user_id = ? AND entry_id = ?;
-        ";
+    let db = database::get(ctx).await;
+    let reply = edit_staff_log(&db, &msg.author, &target, entry_id, msg.link(), reason).await?;
 
-        rows_changed =
-            connection.execute(update_query, params![msg.link(), reason, target_id, entry_id])?;
-    }
+    reply.send(&*ctx.http, msg.channel_id).await?;
 
-    let msg_content = if rows_changed > 0 {
-        let (log_ct, mut m) = make_staff_log_embed(&msg.author, &target, |c, _| c);
+    Ok(())
+}
 
-        if log_ct.is_some() {
-            // If successful, then set msg content
-            m = m.content("Edited staff log.")
-        }
+#[command]
+#[description(
+    "Removes a staff log entry. It keeps its number, still shows up in ``stafflog all`` and can \
+    be brought back with restorestafflog. Staff logs can only be edited by \
+    administrators as long as it is not their own log."
+)]
+#[usage("<USER> <ENTRY NUMBER>")]
+#[example("367538590520967181 1")]
+#[example("DELIBURD#7741 1")]
+#[aliases("removeslog", "removesl", "rmsl")]
+async fn removestafflog(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let target = parse_staff_log_member(ctx, msg, &mut args, 1, 2).await?;
+    let entry_id = argument_parser::parse_bounded_arg(
+        ctx,
+        msg,
+        BoundedArgumentInfo::new(&mut args, 2, 2, 1, i64::MAX),
+    )
+    .await?;
 
-        m
-    } else {
-        CreateMessage::new().content(
-            "Could not find the given log entry. Please verify that this log entry exists.",
-        )
-    };
+    let db = database::get(ctx).await;
+    let reply = remove_staff_log(&db, &msg.author, &target, entry_id).await?;
 
-    msg.channel_id.send_message(ctx, msg_content).await?;
+    reply.send(&*ctx.http, msg.channel_id).await?;
 
     Ok(())
 }
 
 #[command]
 #[description(
-    "Removes a staff log entry. Staff logs can only be edited by \
+    "Brings back a removed staff log entry. Staff logs can only be edited by \
     administrators as long as it is not their own log."
 )]
 #[usage("<USER> <ENTRY NUMBER>")]
 #[example("367538590520967181 1")]
 #[example("DELIBURD#7741 1")]
-#[aliases("removeslog", "removesl", "rmsl")]
-async fn removestafflog(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+#[aliases("restoreslog", "restoresl", "rssl")]
+async fn restorestafflog(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
     let target = parse_staff_log_member(ctx, msg, &mut args, 1, 2).await?;
     let entry_id = argument_parser::parse_bounded_arg(
         ctx,
@@ -376,56 +731,978 @@ async fn removestafflog(ctx: &Context, msg: &Message, mut args: Args) -> Command
         BoundedArgumentInfo::new(&mut args, 2, 2, 1, i64::MAX),
     )
     .await?;
-    let target_id = target.user.id.get();
 
-    let rows_changed;
+    let db = database::get(ctx).await;
+    let reply = restore_staff_log(&db, &msg.author, &target, entry_id).await?;
+
+    reply.send(&*ctx.http, msg.channel_id).await?;
+
+    Ok(())
+}
 
-    {
-        let mut connection = Connection::open(BURDBOT_DB)?;
-        let transaction = connection.transaction()?;
-        let delete_query = "
-            DELETE FROM staff_logs
-            WHERE user_id = ? AND entry_id = ?;
-        ";
+fn bad_date(date: &str) -> String {
+    format!("``{date}`` isn't a date like 2026-09-01.")
+}
 
-        rows_changed = transaction.execute(delete_query, params![target_id, entry_id])?;
+/// Parses a `YYYY-MM-DD` date as the start of that day in UTC, or as the start of the next day
+/// if `is_end` is set so that searches include the whole day.
+fn parse_search_date(date: &str, is_end: bool) -> Result<DateTime<Utc>, String> {
+    let parsed = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| bad_date(date))?;
+    let day = if is_end { parsed.succ_opt().ok_or_else(|| bad_date(date))? } else { parsed };
 
-        // Update the other entries after this entry id to decrement their ids.
-        if rows_changed != 0 {
-            let decrement_entry_ids = "
-                UPDATE staff_logs
-                    SET entry_id = entry_id - 1
-                    WHERE user_id = ? AND entry_id > ?;
-            ";
+    Ok(day.and_time(NaiveTime::MIN).and_utc())
+}
 
-            transaction.execute(decrement_entry_ids, params![target_id, entry_id])?;
+/// Parses searchstafflogs' arguments, where filters like ``category:warning`` can be anywhere
+/// and every other word is a keyword. Returns the search and the page to show, or what's wrong.
+fn parse_search_terms(terms: &str) -> Result<(StaffLogSearch, usize), String> {
+    let mut search = StaffLogSearch::default();
+    let mut page = 1;
+    let mut keywords = Vec::new();
+
+    for term in terms.split_whitespace() {
+        let Some((filter, value)) = term.split_once(':') else {
+            keywords.push(term);
+
+            continue;
+        };
+
+        match filter.to_lowercase().as_str() {
+            "by" => {
+                let author_id = argument_parser::parse_user_mention(value)
+                    .or_else(|| value.parse().ok())
+                    .ok_or_else(|| format!("``{value}`` isn't a user mention or ID."))?;
+
+                search.author_id = Some(author_id);
+            },
+            "category" => {
+                let category = value.parse().map_err(|_| CATEGORY_CHOICES_REPLY.to_owned())?;
+
+                search.category = Some(category);
+            },
+            "from" => search.logged_after = Some(parse_search_date(value, false)?),
+            "to" => search.logged_before = Some(parse_search_date(value, true)?),
+            "page" => {
+                page = value
+                    .parse()
+                    .ok()
+                    .filter(|&page| page >= 1)
+                    .ok_or_else(|| format!("``{value}`` isn't a page number."))?;
+            },
+            _ => keywords.push(term),
         }
+    }
 
-        transaction.commit()?;
+    if !keywords.is_empty() {
+        search.keywords = Some(keywords.join(" "));
     }
 
-    let msg_content = if rows_changed > 0 {
-        let (log_ct, mut m) = make_staff_log_embed(&msg.author, &target, |c, _| c);
+    Ok((search, page))
+}
+
+fn format_search_result(user_id: u64, log: &StaffLog) -> String {
+    let reason = truncate(log.reason.clone(), SEARCH_REASON_MAX_LENGTH);
 
-        if log_ct.is_some() {
-            // If successful, then set msg content
-            m = m.content("Successfully removed entry from staff log.")
-        }
+    format!(
+        "**User**: <@{user_id}>\n**Logged on**: <t:{}:f>\n**Severity**: {}\n**Reason**: {}\n\
+        [See original log]({})",
+        log.get_original_time(),
+        log.severity,
+        reason,
+        log.original_link
+    )
+}
+
+/// Makes the embed with one page of the guild's entries that match `search`, counting pages
+/// from 1. The invoker's own entries are never shown.
+async fn make_search_embed(
+    db: &Database, invoker: &User, guild_id: GuildId, mut search: StaffLogSearch, page: usize,
+) -> rusqlite::Result<CreateEmbed> {
+    search.excluded_user_id = Some(invoker.id.get());
+
+    let offset = page.saturating_sub(1).saturating_mul(SEARCH_RESULTS_PER_PAGE);
+    let results =
+        db.staff_logs().search(guild_id.get(), search, SEARCH_RESULTS_PER_PAGE, offset).await?;
+    let page_count = results.total.div_ceil(SEARCH_RESULTS_PER_PAGE).max(1);
+    let footer = CreateEmbedFooter::new(format!(
+        "Page {page} of {page_count} | Requested by: {}",
+        invoker.tag()
+    ))
+    .icon_url(invoker.avatar_url().unwrap_or_else(|| invoker.default_avatar_url()));
+    let mut embed = CreateEmbed::new().title("Staff Log Search").color(Color::BLUE).footer(footer);
+
+    if results.logs.is_empty() {
+        let description = if results.total == 0 {
+            "No logs matched.".to_owned()
+        } else {
+            format!("There's no page {page}. The last one is {page_count}.")
+        };
+
+        return Ok(embed.description(description));
+    }
+
+    let total = results.total;
+    let description = format!("**{total}** log{} matched.", if total == 1 { "" } else { "s" });
+
+    embed = embed.description(description);
+
+    for (user_id, log) in &results.logs {
+        embed = embed.field(
+            format!("⁣{}:", log_heading(log)),
+            format_search_result(*user_id, log),
+            false,
+        );
+    }
+
+    Ok(embed)
+}
+
+async fn search_staff_logs(
+    db: &Database, invoker: &User, guild_id: GuildId, search: StaffLogSearch, page: usize,
+) -> StaffLogReply {
+    match make_search_embed(db, invoker, guild_id, search, page).await {
+        Ok(embed) => StaffLogReply { content: None, embed: Some(embed) },
+        Err(err) => {
+            error!("Error while searching staff logs: {err:?}");
 
-        m
+            StaffLogReply::content(GONE_WRONG)
+        },
+    }
+}
+
+#[command]
+#[description(
+    "Searches every staff log entry in this server, newest first. Each keyword has to be in the \
+    reason, and these filters can go anywhere: ``by:<USER ID>`` for who logged the entry, \
+    ``category:<CATEGORY>``, ``from:<YYYY-MM-DD>`` and ``to:<YYYY-MM-DD>`` for when it was \
+    logged, and ``page:<NUMBER>``. Your own logs are never shown."
+)]
+#[usage("[KEYWORDS] [FILTERS]")]
+#[example("spam category:warning from:2026-09-01 to:2026-09-30")]
+#[example("by:367538590520967181 page:2")]
+#[aliases("searchslogs", "searchsl", "ssl")]
+#[bucket("db_operations")]
+async fn searchstafflogs(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let (search, page) = match parse_search_terms(args.rest()) {
+        Ok(terms) => terms,
+        Err(reply) => {
+            msg.channel_id.say(ctx, reply).await?;
+
+            return Ok(());
+        },
+    };
+
+    let guild_id = msg.guild_id.expect("Staff logs should only be available in guilds.");
+    let db = database::get(ctx).await;
+    let reply = search_staff_logs(&db, &msg.author, guild_id, search, page).await;
+
+    reply.send(&*ctx.http, msg.channel_id).await?;
+
+    Ok(())
+}
+
+/// Makes the stats embed for the `days` days up to and including the day of `now`.
+async fn make_stats_embed(
+    db: &Database, guild_id: GuildId, days: i64, now: DateTime<Utc>,
+) -> rusqlite::Result<CreateEmbed> {
+    let first_day = now.date_naive() - Duration::days(days - 1);
+    let since = first_day.and_time(NaiveTime::MIN).and_utc();
+    let command_stats = db.command_usage().command_stats(guild_id.get(), since).await?;
+    let daily_uses = db.command_usage().daily_uses(guild_id.get(), since).await?;
+    let (uses, errors) = command_stats
+        .iter()
+        .fold((0, 0), |(uses, errors), stats| (uses + stats.uses, errors + stats.errors));
+    let error_rate = |uses: u32, errors: u32| f64::from(errors) * 100.0 / f64::from(uses);
+    let title = format!("Command usage in the last {days} day{}", if days == 1 { "" } else { "s" });
+    let embed = CreateEmbed::new().title(title).color(Color::BLUE);
+
+    if uses == 0 {
+        return Ok(embed.description("No commands were used."));
+    }
+
+    let top_commands = command_stats
+        .iter()
+        .take(TOP_COMMAND_COUNT)
+        .map(|stats| {
+            let rate = error_rate(stats.uses, stats.errors);
+
+            format!("``{}``: {} uses, {rate:.1}% errors", stats.command, stats.uses)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let per_day = first_day
+        .iter_days()
+        .take(days as usize)
+        .map(|day| {
+            let day_uses = daily_uses.iter().find(|(used_on, _)| *used_on == day);
+
+            format!("{}: {}", day.format("%Y-%m-%d"), day_uses.map_or(0, |(_, uses)| *uses))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok(embed
+        .description(format!(
+            "**{uses}** commands used, **{errors}** failed ({:.1}%).",
+            error_rate(uses, errors)
+        ))
+        .field("Top commands", top_commands, false)
+        .field("Uses per day (UTC)", per_day, false))
+}
+
+async fn stats_reply(db: &Database, guild_id: GuildId, days: i64) -> CreateEmbed {
+    match make_stats_embed(db, guild_id, days, Utc::now()).await {
+        Ok(embed) => embed,
+        Err(err) => {
+            error!("Error while making stats embed: {err:?}");
+
+            CreateEmbed::new().color(Color::RED).description(GONE_WRONG)
+        },
+    }
+}
+
+#[command]
+#[description(
+    "Shows the most used commands in this server, how often they fail and how many \
+    commands were used each day. Looks at the last 7 days unless given a number of days."
+)]
+#[usage("[DAYS]")]
+#[example("")]
+#[example("30")]
+#[bucket("db_operations")]
+async fn stats(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let days = if args.is_empty() {
+        DEFAULT_STATS_DAYS
     } else {
-        CreateMessage::new().content(
-            "Could not find the given log entry. Please verify that this log entry exists.",
+        argument_parser::parse_bounded_arg(
+            ctx,
+            msg,
+            BoundedArgumentInfo::new(&mut args, 1, 1, 1, MAX_STATS_DAYS),
         )
+        .await?
     };
 
-    msg.channel_id.send_message(ctx, msg_content).await?;
+    let guild_id = msg.guild_id.expect("Stats should only be available in guilds.");
+    let db = database::get(ctx).await;
+    let embed = stats_reply(&db, guild_id, days).await;
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(embed)).await?;
 
     Ok(())
 }
 
 #[group]
 #[only_in("guilds")]
-#[commands(stafflog, addstafflog, editstafflog, removestafflog)]
+#[commands(
+    stafflog, addstafflog, editstafflog, removestafflog, restorestafflog, searchstafflogs, stats
+)]
 #[required_permissions("Administrator")]
 struct Administrative;
+
+/// Replies and returns false if `target` is the one using the command.
+async fn check_staff_log_target(ctx: SlashContext<'_>, target: &Member) -> CommandResult<bool> {
+    if target.user.id == ctx.author().id {
+        ctx.say(OWN_STAFF_LOG).await?;
+
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+/// Looks at the staff log of someone other than you.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "stafflog",
+    subcommands("stafflog_view_slash", "stafflog_history_slash"),
+    required_permissions = "ADMINISTRATOR",
+    default_member_permissions = "ADMINISTRATOR"
+)]
+pub async fn stafflog_slash(_ctx: SlashContext<'_>) -> CommandResult {
+    // Discord only ever invokes the subcommands.
+    Ok(())
+}
+
+/// Displays the staff log of someone other than you.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "view",
+    required_permissions = "ADMINISTRATOR",
+    default_member_permissions = "ADMINISTRATOR"
+)]
+async fn stafflog_view_slash(
+    ctx: SlashContext<'_>, #[description = "The user whose log to show"] user: Member,
+    #[description = "Whether to show removed entries too"] include_removed: Option<bool>,
+    #[description = "Only show entries of this category"]
+    #[autocomplete = "autocomplete_category"]
+    category: Option<String>,
+    #[description = "Only show entries of this severity"]
+    #[autocomplete = "autocomplete_severity"]
+    severity: Option<String>,
+) -> CommandResult {
+    if !check_staff_log_target(ctx, &user).await? {
+        return Ok(());
+    }
+
+    let (category, severity) = match slash_log_kind(category, severity) {
+        Ok(kind) => kind,
+        Err(reply) => {
+            ctx.say(reply).await?;
+
+            return Ok(());
+        },
+    };
+    let db = database::get(ctx.serenity_context()).await;
+    let include_deleted = include_removed.unwrap_or(false);
+    let reply = view_staff_log(&db, ctx.author(), &user, include_deleted, category, severity).await;
+
+    ctx.send(reply.into_reply()).await?;
+
+    Ok(())
+}
+
+/// Adds a staff log entry for someone other than you.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "addstafflog",
+    required_permissions = "ADMINISTRATOR",
+    default_member_permissions = "ADMINISTRATOR"
+)]
+pub async fn addstafflog_slash(
+    ctx: SlashContext<'_>, #[description = "The user to log"] user: Member,
+    #[description = "The reason for the log"] reason: String,
+    #[description = "The kind of log, a note if not given"]
+    #[autocomplete = "autocomplete_category"]
+    category: Option<String>,
+    #[description = "How serious it is, the category's usual severity if not given"]
+    #[autocomplete = "autocomplete_severity"]
+    severity: Option<String>,
+) -> CommandResult {
+    if !check_staff_log_target(ctx, &user).await? {
+        return Ok(());
+    }
+
+    let (category, severity) = match slash_category_and_severity(category, severity) {
+        Ok(kind) => kind,
+        Err(reply) => {
+            ctx.say(reply).await?;
+
+            return Ok(());
+        },
+    };
+
+    // Logs link to the message they were made with, which is the response here.
+    let handle = ctx.say("Adding staff log...").await?;
+    let link = response_link(&*handle.message().await?, ctx.guild_id());
+    let db = database::get(ctx.serenity_context()).await;
+    let reply = add_staff_log(&db, ctx.author(), &user, link, &reason, category, severity).await;
+
+    handle.edit(ctx, reply.into_reply()).await?;
+
+    Ok(())
+}
+
+/// Links to a slash command's response. Responses fetched over REST don't know their guild, so
+/// [`Message::link`] would lead to DMs instead.
+fn response_link(response: &Message, guild_id: Option<GuildId>) -> String {
+    response.id.link(response.channel_id, guild_id)
+}
+
+/// Parses the category and severity options of a slash command, if they're given.
+fn slash_log_kind(
+    category: Option<String>, severity: Option<String>,
+) -> Result<(Option<StaffLogCategory>, Option<StaffLogSeverity>), &'static str> {
+    let category = category
+        .as_deref()
+        .map(StaffLogCategory::from_str)
+        .transpose()
+        .map_err(|_| CATEGORY_CHOICES_REPLY)?;
+    let severity = severity
+        .as_deref()
+        .map(StaffLogSeverity::from_str)
+        .transpose()
+        .map_err(|_| SEVERITY_CHOICES_REPLY)?;
+
+    Ok((category, severity))
+}
+
+fn slash_category_and_severity(
+    category: Option<String>, severity: Option<String>,
+) -> Result<(StaffLogCategory, StaffLogSeverity), &'static str> {
+    let (category, severity) = slash_log_kind(category, severity)?;
+    let category = category.unwrap_or(StaffLogCategory::Note);
+
+    Ok((category, severity.unwrap_or_else(|| category.default_severity())))
+}
+
+async fn autocomplete_category<'a>(
+    _: SlashContext<'_>, partial: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    let partial = partial.to_lowercase();
+
+    StaffLogCategory::iter()
+        .map(|category| category.to_string())
+        .filter(move |name| name.starts_with(&partial))
+}
+
+async fn autocomplete_severity<'a>(
+    _: SlashContext<'_>, partial: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    let partial = partial.to_lowercase();
+
+    StaffLogSeverity::iter()
+        .map(|severity| severity.to_string())
+        .filter(move |name| name.starts_with(&partial))
+}
+
+/// Edits a staff log entry of someone other than you.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "editstafflog",
+    required_permissions = "ADMINISTRATOR",
+    default_member_permissions = "ADMINISTRATOR"
+)]
+pub async fn editstafflog_slash(
+    ctx: SlashContext<'_>, #[description = "The user whose log to edit"] user: Member,
+    #[description = "The number of the entry to edit"]
+    #[min = 1]
+    entry: i64,
+    #[description = "The new reason for the entry"] reason: String,
+) -> CommandResult {
+    if !check_staff_log_target(ctx, &user).await? {
+        return Ok(());
+    }
+
+    let handle = ctx.say("Editing staff log...").await?;
+    let link = response_link(&*handle.message().await?, ctx.guild_id());
+    let db = database::get(ctx.serenity_context()).await;
+    let reply = edit_staff_log(&db, ctx.author(), &user, entry, link, &reason).await?;
+
+    handle.edit(ctx, reply.into_reply()).await?;
+
+    Ok(())
+}
+
+/// Removes a staff log entry of someone other than you.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "removestafflog",
+    required_permissions = "ADMINISTRATOR",
+    default_member_permissions = "ADMINISTRATOR"
+)]
+pub async fn removestafflog_slash(
+    ctx: SlashContext<'_>, #[description = "The user whose log to remove from"] user: Member,
+    #[description = "The number of the entry to remove"]
+    #[min = 1]
+    entry: i64,
+) -> CommandResult {
+    if !check_staff_log_target(ctx, &user).await? {
+        return Ok(());
+    }
+
+    let db = database::get(ctx.serenity_context()).await;
+    let reply = remove_staff_log(&db, ctx.author(), &user, entry).await?;
+
+    ctx.send(reply.into_reply()).await?;
+
+    Ok(())
+}
+
+/// Brings back a removed staff log entry of someone other than you.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "restorestafflog",
+    required_permissions = "ADMINISTRATOR",
+    default_member_permissions = "ADMINISTRATOR"
+)]
+pub async fn restorestafflog_slash(
+    ctx: SlashContext<'_>, #[description = "The user whose log to restore to"] user: Member,
+    #[description = "The number of the entry to restore"]
+    #[min = 1]
+    entry: i64,
+) -> CommandResult {
+    if !check_staff_log_target(ctx, &user).await? {
+        return Ok(());
+    }
+
+    let db = database::get(ctx.serenity_context()).await;
+    let reply = restore_staff_log(&db, ctx.author(), &user, entry).await?;
+
+    ctx.send(reply.into_reply()).await?;
+
+    Ok(())
+}
+
+/// Shows every version of a staff log entry of someone other than you.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "history",
+    required_permissions = "ADMINISTRATOR",
+    default_member_permissions = "ADMINISTRATOR"
+)]
+async fn stafflog_history_slash(
+    ctx: SlashContext<'_>, #[description = "The user whose log to look at"] user: Member,
+    #[description = "The number of the entry"]
+    #[min = 1]
+    entry: i64,
+) -> CommandResult {
+    if !check_staff_log_target(ctx, &user).await? {
+        return Ok(());
+    }
+
+    let db = database::get(ctx.serenity_context()).await;
+    let reply = staff_log_history(&db, ctx.author(), &user, entry).await?;
+
+    ctx.send(reply.into_reply()).await?;
+
+    Ok(())
+}
+
+/// Searches every staff log entry in this server except your own, newest first.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "searchstafflogs",
+    required_permissions = "ADMINISTRATOR",
+    default_member_permissions = "ADMINISTRATOR"
+)]
+pub async fn searchstafflogs_slash(
+    ctx: SlashContext<'_>,
+    #[description = "Words that the reason has to have"] keywords: Option<String>,
+    #[description = "Who logged the entries"] author: Option<User>,
+    #[description = "Only find entries of this category"]
+    #[autocomplete = "autocomplete_category"]
+    category: Option<String>,
+    #[description = "The first day to search, like 2026-09-01"] from: Option<String>,
+    #[description = "The last day to search, like 2026-09-30"] to: Option<String>,
+    #[description = "The page of results to show"]
+    #[min = 1]
+    page: Option<u32>,
+) -> CommandResult {
+    let filters = slash_search_filters(category.as_deref(), from.as_deref(), to.as_deref());
+    let mut search = match filters {
+        Ok(search) => search,
+        Err(reply) => {
+            ctx.say(reply).await?;
+
+            return Ok(());
+        },
+    };
+
+    search.keywords = keywords;
+    search.author_id = author.map(|author| author.id.get());
+
+    let guild_id = ctx.guild_id().expect("Staff logs should only be available in guilds.");
+    let page = page.map_or(1, |page| page as usize);
+    let db = database::get(ctx.serenity_context()).await;
+    let reply = search_staff_logs(&db, ctx.author(), guild_id, search, page).await;
+
+    ctx.send(reply.into_reply()).await?;
+
+    Ok(())
+}
+
+/// Parses the category, start date and end date of a slash command search.
+fn slash_search_filters(
+    category: Option<&str>, from: Option<&str>, to: Option<&str>,
+) -> Result<StaffLogSearch, String> {
+    let category = category
+        .map(StaffLogCategory::from_str)
+        .transpose()
+        .map_err(|_| CATEGORY_CHOICES_REPLY.to_owned())?;
+    let logged_after = from.map(|from| parse_search_date(from, false)).transpose()?;
+    let logged_before = to.map(|to| parse_search_date(to, true)).transpose()?;
+
+    Ok(StaffLogSearch { category, logged_after, logged_before, ..Default::default() })
+}
+
+/// Shows the most used commands in this server, their error rates and uses per day.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "stats",
+    required_permissions = "ADMINISTRATOR",
+    default_member_permissions = "ADMINISTRATOR"
+)]
+pub async fn stats_slash(
+    ctx: SlashContext<'_>,
+    #[description = "How many days to look back on, 7 if not given"]
+    #[min = 1]
+    #[max = 30]
+    days: Option<i64>,
+) -> CommandResult {
+    let guild_id = ctx.guild_id().expect("Stats should only be available in guilds.");
+    let db = database::get(ctx.serenity_context()).await;
+    let embed = stats_reply(&db, guild_id, days.unwrap_or(DEFAULT_STATS_DAYS)).await;
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::model::id::{MessageId, UserId};
+
+    use super::*;
+    use crate::database::TempDatabase;
+    use crate::discord::recording::{RecordingHttp, test_member};
+
+    const LOG_CHANNEL_ID: u64 = 50;
+
+    fn invoker() -> User {
+        let mut invoker = User::default();
+
+        invoker.id = UserId::new(1);
+
+        invoker
+    }
+
+    async fn add_and_send(
+        db: &Database, http: &RecordingHttp, target: &Member, message_id: u64, reason: &str,
+    ) {
+        add_categorized(db, http, target, message_id, reason, StaffLogCategory::Note).await;
+    }
+
+    async fn add_categorized(
+        db: &Database, http: &RecordingHttp, target: &Member, message_id: u64, reason: &str,
+        category: StaffLogCategory,
+    ) {
+        let link = format!("https://discord.com/channels/10/{LOG_CHANNEL_ID}/{message_id}");
+        let severity = category.default_severity();
+        let reply = add_staff_log(db, &invoker(), target, link, reason, category, severity).await;
+
+        reply.send(http, ChannelId::new(LOG_CHANNEL_ID)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn added_staff_logs_are_saved_and_shown() {
+        let db = TempDatabase::new();
+        let http = RecordingHttp::new();
+        let target = test_member(10, 2, &[]);
+
+        add_and_send(&db, &http, &target, 1000000000000000000, "Spam").await;
+        add_and_send(&db, &http, &target, 1000000000000000001, "More spam").await;
+
+        let logs = db.staff_logs().get(10, 2, false).await.unwrap();
+        let reasons = logs.iter().map(|log| log.reason.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(reasons, vec!["Spam", "More spam"]);
+
+        let sent = http.sent_to(ChannelId::new(LOG_CHANNEL_ID));
+
+        assert_eq!(sent.len(), 2);
+
+        let fields = sent[1]["embeds"][0]["fields"].as_array().unwrap();
+
+        assert_eq!(fields.len(), 2);
+        assert!(fields[1]["value"].as_str().unwrap().contains("More spam"));
+    }
+
+    #[tokio::test]
+    async fn removed_staff_logs_are_only_shown_in_the_full_log() {
+        let db = TempDatabase::new();
+        let http = RecordingHttp::new();
+        let target = test_member(10, 2, &[]);
+
+        add_and_send(&db, &http, &target, 1000000000000000000, "Spam").await;
+        add_and_send(&db, &http, &target, 1000000000000000001, "More spam").await;
+
+        let removed = remove_staff_log(&db, &invoker(), &target, 1).await.unwrap();
+        let fields = serde_json::to_value(removed.embed.unwrap()).unwrap()["fields"].clone();
+
+        // The remaining entry keeps its number.
+        assert_eq!(fields.as_array().unwrap().len(), 1);
+        assert_eq!(fields[0]["name"], "⁣Log #2 📝 Note:");
+
+        let full = view_staff_log(&db, &invoker(), &target, true, None, None).await;
+        let fields = serde_json::to_value(full.embed.unwrap()).unwrap()["fields"].clone();
+
+        assert_eq!(fields.as_array().unwrap().len(), 2);
+        assert!(fields[0]["value"].as_str().unwrap().contains("**Removed by**: <@1>"));
+
+        let restored = restore_staff_log(&db, &invoker(), &target, 1).await.unwrap();
+
+        assert_eq!(restored.content, Some("Restored staff log entry."));
+
+        let again = restore_staff_log(&db, &invoker(), &target, 1).await.unwrap();
+
+        assert_eq!(again.content, Some(REMOVED_LOG_NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn staff_logs_can_be_filtered_by_category() {
+        let db = TempDatabase::new();
+        let http = RecordingHttp::new();
+        let target = test_member(10, 2, &[]);
+
+        add_and_send(&db, &http, &target, 1000000000000000000, "Spam").await;
+        add_categorized(&db, &http, &target, 1000000000000000001, "Slurs", StaffLogCategory::Ban)
+            .await;
+        add_categorized(&db, &http, &target, 1000000000000000002, "More", StaffLogCategory::Ban)
+            .await;
+
+        let bans =
+            view_staff_log(&db, &invoker(), &target, false, Some(StaffLogCategory::Ban), None)
+                .await;
+        let embed = serde_json::to_value(bans.embed.unwrap()).unwrap();
+        let fields = embed["fields"].as_array().unwrap();
+
+        assert_eq!(embed["description"], "📝 Note: 1 | 🔨 Ban: 2");
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0]["name"], "⁣Log #2 🔨 Ban:");
+        assert!(fields[0]["value"].as_str().unwrap().contains("**Severity**: high"));
+        assert!(fields[1]["value"].as_str().unwrap().starts_with("**Log #3 🔨 Ban**:"));
+
+        let kicks =
+            view_staff_log(&db, &invoker(), &target, false, Some(StaffLogCategory::Kick), None)
+                .await;
+        let embed = serde_json::to_value(kicks.embed.unwrap()).unwrap();
+
+        assert_eq!(embed["description"], "📝 Note: 1 | 🔨 Ban: 2\nThis user has no kick logs.");
+        assert!(embed["fields"].as_array().is_none_or(Vec::is_empty));
+    }
+
+    #[tokio::test]
+    async fn staff_logs_can_be_filtered_by_severity() {
+        let db = TempDatabase::new();
+        let http = RecordingHttp::new();
+        let target = test_member(10, 2, &[]);
+
+        add_and_send(&db, &http, &target, 1000000000000000000, "Spam").await;
+        add_categorized(&db, &http, &target, 1000000000000000001, "Slurs", StaffLogCategory::Ban)
+            .await;
+
+        let high = Some(StaffLogSeverity::High);
+        let reply = view_staff_log(&db, &invoker(), &target, false, None, high).await;
+        let embed = serde_json::to_value(reply.embed.unwrap()).unwrap();
+        let fields = embed["fields"].as_array().unwrap();
+
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0]["name"], "⁣Log #2 🔨 Ban:");
+
+        let notes = Some(StaffLogCategory::Note);
+        let reply = view_staff_log(&db, &invoker(), &target, false, notes, high).await;
+        let embed = serde_json::to_value(reply.embed.unwrap()).unwrap();
+
+        assert_eq!(
+            embed["description"],
+            "📝 Note: 1 | 🔨 Ban: 1\nThis user has no high-severity note logs."
+        );
+    }
+
+    #[tokio::test]
+    async fn slash_command_logs_link_to_their_guild() {
+        let db = TempDatabase::new();
+        let target = test_member(10, 2, &[]);
+        let mut response = Message::default();
+
+        response.id = MessageId::new(1000000000000000000);
+        response.channel_id = ChannelId::new(LOG_CHANNEL_ID);
+
+        let link = response_link(&response, Some(GuildId::new(10)));
+        let (category, severity) = (StaffLogCategory::Note, StaffLogSeverity::Low);
+
+        add_staff_log(&db, &invoker(), &target, link, "Spam", category, severity).await;
+
+        let logs = db.staff_logs().get(10, 2, false).await.unwrap();
+
+        assert_eq!(
+            logs[0].original_link,
+            format!("https://discord.com/channels/10/{LOG_CHANNEL_ID}/1000000000000000000")
+        );
+    }
+
+    #[test]
+    fn log_kinds_need_their_prefix() {
+        use serenity::framework::standard::Delimiter;
+
+        let kind_options = |text| {
+            let mut args = Args::new(text, &[Delimiter::Single(' ')]);
+
+            parse_log_kind_options(&mut args).map(|options| (options, args.rest().to_owned()))
+        };
+
+        assert_eq!(
+            kind_options("severity:high Category:ban For being a bad burd"),
+            Ok((
+                (Some(StaffLogCategory::Ban), Some(StaffLogSeverity::High)),
+                "For being a bad burd".to_owned()
+            ))
+        );
+        // A reason starting with a category or severity is kept whole.
+        assert_eq!(kind_options("Warning: spam"), Ok(((None, None), "Warning: spam".to_owned())));
+        assert_eq!(kind_options("category:spam Spam"), Err(CATEGORY_CHOICES_REPLY));
+    }
+
+    #[test]
+    fn search_terms_can_have_filters_anywhere() {
+        let terms = "spam category:Warning by:367538590520967181 links from:2026-09-01 \
+            to:2026-09-30 page:2";
+        let (search, page) = parse_search_terms(terms).unwrap();
+
+        assert_eq!(search.keywords.as_deref(), Some("spam links"));
+        assert_eq!(search.author_id, Some(367538590520967181));
+        assert_eq!(search.category, Some(StaffLogCategory::Warning));
+        assert_eq!(search.logged_after.unwrap().to_rfc3339(), "2026-09-01T00:00:00+00:00");
+        // The last day is searched too.
+        assert_eq!(search.logged_before.unwrap().to_rfc3339(), "2026-10-01T00:00:00+00:00");
+        assert_eq!(page, 2);
+
+        let (search, _) = parse_search_terms("see https://example.com").unwrap();
+
+        assert_eq!(search.keywords.as_deref(), Some("see https://example.com"));
+        assert_eq!(
+            parse_search_terms("from:yesterday").unwrap_err(),
+            "``yesterday`` isn't a date like 2026-09-01."
+        );
+        assert!(parse_search_terms("page:0").is_err());
+    }
+
+    #[tokio::test]
+    async fn searches_leave_out_the_invokers_own_logs() {
+        let db = TempDatabase::new();
+        let http = RecordingHttp::new();
+        let (target, own) = (test_member(10, 2, &[]), test_member(10, 1, &[]));
+
+        add_and_send(&db, &http, &target, 1000000000000000000, "Spam").await;
+        add_and_send(&db, &http, &target, 1000000000000000001, "More spam").await;
+        add_and_send(&db, &http, &own, 1000000000000000002, "Spam too").await;
+
+        let search = StaffLogSearch { keywords: Some("spam".to_owned()), ..Default::default() };
+        let embed =
+            make_search_embed(&db, &invoker(), GuildId::new(10), search.clone(), 1).await.unwrap();
+        let embed = serde_json::to_value(embed).unwrap();
+
+        assert_eq!(embed["description"], "**2** logs matched.");
+        assert_eq!(embed["fields"][0]["name"], "⁣Log #2 📝 Note:");
+        assert!(embed["fields"][0]["value"].as_str().unwrap().starts_with("**User**: <@2>"));
+
+        let past_end =
+            make_search_embed(&db, &invoker(), GuildId::new(10), search, 2).await.unwrap();
+        let past_end = serde_json::to_value(past_end).unwrap();
+
+        assert_eq!(past_end["description"], "There's no page 2. The last one is 1.");
+    }
+
+    #[test]
+    fn word_diffs_mark_removed_and_added_words() {
+        assert_eq!(
+            word_diff("Threw too many presents", "Threw way too many gifts"),
+            "Threw **way** too many ~~presents~~ **gifts**"
+        );
+        assert_eq!(word_diff("Spam", "Spam"), "Spam");
+        assert_eq!(word_diff("", "Spam"), "**Spam**");
+    }
+
+    #[tokio::test]
+    async fn history_shows_what_each_edit_changed() {
+        let db = TempDatabase::new();
+        let http = RecordingHttp::new();
+        let target = test_member(10, 2, &[]);
+        let link = format!("https://discord.com/channels/10/{LOG_CHANNEL_ID}/1000000000000000001");
+
+        add_and_send(&db, &http, &target, 1000000000000000000, "Spam in general").await;
+        edit_staff_log(&db, &invoker(), &target, 1, link, "Spam in memes").await.unwrap();
+
+        let reply = staff_log_history(&db, &invoker(), &target, 1).await.unwrap();
+        let embed = serde_json::to_value(reply.embed.unwrap()).unwrap();
+        let fields = embed["fields"].as_array().unwrap();
+
+        assert_eq!(fields.len(), 2);
+        assert!(fields[0]["value"].as_str().unwrap().contains("**Reason**: Spam in general"));
+        assert!(fields[1]["value"].as_str().unwrap().contains("Spam in ~~general~~ **memes**"));
+
+        let missing = staff_log_history(&db, &invoker(), &target, 2).await.unwrap();
+
+        assert_eq!(missing.content, Some(LOG_NOT_FOUND));
+    }
+
+    #[test]
+    fn history_cuts_long_reasons_short_but_keeps_the_link() {
+        let revision = StaffLogRevision {
+            author_id: Some(1),
+            link: "https://discord.com/channels/10/2/3".to_owned(),
+            reason: "spam ".repeat(300),
+            revised_at: Utc::now(),
+        };
+        let embed = make_history_embed(&invoker(), &test_member(10, 2, &[]), 1, &[revision]);
+        let embed = serde_json::to_value(embed).unwrap();
+        let value = embed["fields"][0]["value"].as_str().unwrap();
+
+        assert_eq!(value.chars().count(), EMBED_FIELD_MAX_LENGTH);
+        assert!(value.ends_with("…\n[See message](https://discord.com/channels/10/2/3)"));
+    }
+
+    #[test]
+    fn history_leaves_out_the_oldest_revisions_that_do_not_fit() {
+        let revisions = (0..10)
+            .map(|idx| StaffLogRevision {
+                author_id: Some(1),
+                link: "https://discord.com/channels/10/2/3".to_owned(),
+                reason: format!("{idx} {}", "spam ".repeat(150)),
+                revised_at: Utc::now(),
+            })
+            .collect::<Vec<_>>();
+        let embed = make_history_embed(&invoker(), &test_member(10, 2, &[]), 1, &revisions);
+        let embed = serde_json::to_value(embed).unwrap();
+        let fields = embed["fields"].as_array().unwrap();
+        let length = fields
+            .iter()
+            .map(|field| {
+                field["name"].as_str().unwrap().chars().count()
+                    + field["value"].as_str().unwrap().chars().count()
+            })
+            .sum::<usize>();
+
+        assert!(length <= HISTORY_FIELDS_MAX_LENGTH);
+        assert_eq!(fields.last().unwrap()["name"], "⁣Revision #10:");
+        assert_eq!(
+            embed["description"],
+            format!("Showing the last {} of 10 revisions.", fields.len())
+        );
+    }
+
+    #[tokio::test]
+    async fn stats_fill_in_days_without_uses() {
+        use chrono::TimeZone;
+
+        use crate::database::NewCommandUsage;
+
+        let db = TempDatabase::new();
+        let used_at = |day| Utc.with_ymd_and_hms(2026, 5, day, 12, 0, 0).unwrap();
+
+        for (command, error_kind, day) in
+            [("pronounce", None, 1), ("pronounce", Some("discord"), 3), ("stafflog", None, 3)]
+        {
+            let usage = NewCommandUsage {
+                command: command.to_owned(),
+                guild_id: Some(10),
+                channel_id: 2,
+                user_id: 3,
+                error_kind,
+                duration_ms: 10,
+                used_at: used_at(day),
+            };
+
+            db.command_usage().record(usage).await.unwrap();
+        }
+
+        let embed = make_stats_embed(&db, GuildId::new(10), 3, used_at(3)).await.unwrap();
+        let embed = serde_json::to_value(embed).unwrap();
+
+        assert_eq!(embed["description"], "**3** commands used, **1** failed (33.3%).");
+        assert_eq!(
+            embed["fields"][0]["value"],
+            "``pronounce``: 2 uses, 50.0% errors\n``stafflog``: 1 uses, 0.0% errors"
+        );
+        assert_eq!(embed["fields"][1]["value"], "2026-05-01: 1\n2026-05-02: 0\n2026-05-03: 2");
+    }
+}
@@ -1,67 +1,59 @@
+use chrono::{Datelike, Utc};
+use chrono_tz::TZ_VARIANTS;
+use poise::CreateReply;
+use serenity::all::{Attachment, CreateEmbed, CreateMessage, GuildId, Member, MessageId, Role};
 use serenity::client::Context;
 use serenity::model::Permissions;
 
-use std::collections::HashMap;
-use std::time::Duration;
+use std::str::FromStr;
 
 use serenity::framework::standard::{Args, CommandResult};
 
 use serenity::framework::standard::macros::{command, group};
 
 use serenity::model::channel::Message;
-use serenity::prelude::TypeMapKey;
-
-use tokio::sync::RwLock;
-use tokio::task::JoinHandle;
-use tokio::time::sleep;
-
-use log::error;
-
-use crate::argument_parser::{self, ArgumentInfo, BoundedArgumentInfo};
-use crate::birthday_tracker::{self, add_birthday_to_db};
-use crate::commands::error_util;
-use crate::error::SerenitySQLiteError;
+use serenity::model::id::ChannelId;
+
+use strum::IntoEnumIterator;
+
+use crate::argument_parser::{
+    self, ArgumentConversionError, ArgumentInfo, BoundedArgumentInfo, ConversionType,
+    NotEnoughArgumentsError,
+};
+use crate::birthday_tracker::{
+    self, BirthdayDetails, BirthdayFileFormat, BirthdayTimeZone, BirthdayVisibility, MIN_BIRTH_YEAR,
+};
+use crate::commands::SlashContext;
+use crate::database::{self, PendingBirthday};
+use crate::guild_config;
 use crate::util;
 
-pub const MONTH_TO_DAYS: [i64; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+pub const MONTH_TO_DAYS: [i64; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+const DEFAULT_UPCOMING_DAYS: i64 = 7;
+const MAX_UPCOMING_DAYS: i64 = 366;
+const VISIBILITY_CHOICES_REPLY: &str =
+    "The visibility has to be ``public``, ``staff`` or ``hidden``.";
+
 pub const MONTH_TO_NAME: [&str; 12] = [
     "January", "February", "March", "April", "May", "June", "July", "August", "September",
     "October", "November", "December",
 ];
 
-pub struct BirthdayInfoConfirmation {
-    pub user_id: u64,
-    pub month: u32,
-    pub day: u32,
-    pub time_zone: i64,
-    pub is_privileged: bool,
-    handle: JoinHandle<()>,
-}
-
-impl BirthdayInfoConfirmation {
-    pub fn new(
-        user_id: u64, month: u32, day: u32, time_zone: i64, handle: JoinHandle<()>,
-        is_privileged: bool,
-    ) -> BirthdayInfoConfirmation {
-        BirthdayInfoConfirmation { user_id, month, day, time_zone, is_privileged, handle }
-    }
-}
-
-struct BirthdayInfoConfirmationKey;
-
-impl TypeMapKey for BirthdayInfoConfirmationKey {
-    type Value = RwLock<HashMap<u64, BirthdayInfoConfirmation>>;
-}
-
 #[command]
 #[only_in("guilds")]
 #[description(
-    "Sets your birthday so that you get a special role for the day. Make sure the time zone you select is the \
-        correct time zone for the given date. (Take into account daylight savings if needed.)"
+    "Sets your birthday so that you get a special role for the day in every server with a birthday \
+        role. The time zone can be a UTC offset like -7 or +5:30, or a time zone name like America/New_York. Make sure a UTC offset is the \
+        correct one for the given date. (Take into account daylight savings if needed.) You can also \
+        give your birth year to show the age you're turning, and who can see your birthday: \
+        public, staff or hidden. Hidden birthdays still get the role, but are never shown."
 )]
-#[usage("<MONTH> <DAY> <UTC TIME ZONE ON DATE>")]
+#[usage("<MONTH> <DAY> <TIME ZONE ON DATE> [BIRTH YEAR] [public/staff/hidden]")]
 #[example("10 6 -7")]
-#[example("10 6 7")]
+#[example("10 6 +5:30")]
+#[example("10 6 America/Argentina/Buenos_Aires")]
+#[example("10 6 -7 2000 staff")]
+#[example("10 6 -7 hidden")]
 #[aliases("setmybday")]
 #[bucket("db_operations")]
 async fn setmybirthday(context: &Context, message: &Message, mut args: Args) -> CommandResult {
@@ -74,7 +66,7 @@ async fn setmybirthday(context: &Context, message: &Message, mut args: Args) ->
     let is_privileged_option = permissions.map(Permissions::manage_roles);
 
     if let Some(is_privileged) = is_privileged_option {
-        set_birthday(context, message, args, message.author.id.get(), is_privileged).await
+        set_birthday(context, message, args, message.author.id.get(), is_privileged, true).await
     } else {
         Ok(())
     }
@@ -84,12 +76,13 @@ async fn setmybirthday(context: &Context, message: &Message, mut args: Args) ->
 #[only_in("guilds")]
 #[required_permissions(MANAGE_ROLES)]
 #[description(
-    "Sets a user's birthday so that they get a special role for the day. Make sure the time zone selected is the \
-        correct time zone for the given date. (Take into account daylight savings if needed.)"
+    "Sets a user's birthday so that they get a special role for the day in every server with a \
+        birthday role. The time zone can be a UTC offset like -7 or +5:30, or a time zone name like America/New_York. Make sure a UTC offset is \
+        the correct one for the given date. (Take into account daylight savings if needed.)"
 )]
-#[usage("<USER> <MONTH> <DAY> <UTC TIME ZONE ON DATE>")]
+#[usage("<USER> <MONTH> <DAY> <TIME ZONE ON DATE>")]
 #[example("367538590520967181 10 6 -7")]
-#[example("DELIBURD#7741 10 6 7")]
+#[example("DELIBURD#7741 10 6 Asia/Kolkata")]
 #[aliases("setusrbday", "setuserbday")]
 #[bucket("db_operations")]
 async fn setuserbirthday(context: &Context, message: &Message, mut args: Args) -> CommandResult {
@@ -98,11 +91,14 @@ async fn setuserbirthday(context: &Context, message: &Message, mut args: Args) -
     let arg_info = ArgumentInfo::new(&mut args, 1, 4);
     let member = argument_parser::parse_member(context, message, arg_info).await?;
 
-    set_birthday(context, message, args, member.user.id.get(), true).await
+    set_birthday(context, message, args, member.user.id.get(), true, false).await
 }
 
+/// Asks to set a birthday. If `with_details` is set, an optional birth year and visibility can
+/// follow the time zone.
 async fn set_birthday(
     context: &Context, message: &Message, mut args: Args, target_id: u64, is_privileged: bool,
+    with_details: bool,
 ) -> CommandResult {
     args.quoted();
 
@@ -114,137 +110,182 @@ async fn set_birthday(
     let day_arg_info = BoundedArgumentInfo::new(&mut args, 2, 3, 1, max_day_count);
     let day = argument_parser::parse_bounded_arg(context, message, day_arg_info).await? as u32;
 
-    let time_zone_arg_info = BoundedArgumentInfo::new(&mut args, 3, 3, -11, 14);
-    let time_zone =
-        argument_parser::parse_bounded_arg(context, message, time_zone_arg_info).await?;
+    let time_zone = parse_time_zone(context, message, &mut args, 3, 3).await?;
+    let birthday = birthday_tracker::Birthday::new(month, day, time_zone)
+        .expect("The day was checked to be in the month.");
+    let details = if with_details && !args.is_empty() {
+        Some(parse_birthday_details(context, message, &mut args).await?)
+    } else {
+        None
+    };
 
-    {
-        let mut data = context.data.write().await;
+    let request = BirthdayRequest {
+        author_id: message.author.id.get(),
+        channel_id: message.channel_id,
+        guild_id: message.guild_id.unwrap(),
+        target_id,
+        birthday,
+        details,
+        is_privileged,
+    };
+    let content = birthday_confirmation_message(context, &request).await;
+    let builder = CreateMessage::new()
+        .embed(util::message_embed(content))
+        .components(birthday_tracker::birthday_confirmation_buttons());
+    let sent = message.channel_id.send_message(context, builder).await?;
 
-        if !data.contains_key::<BirthdayInfoConfirmationKey>() {
-            data.insert::<BirthdayInfoConfirmationKey>(RwLock::new(HashMap::new()));
-        }
-    }
+    birthday_tracker::add_pending_birthday(context, request.into_pending(sent.id)).await?;
 
-    let mut time_zone_string: String;
+    Ok(())
+}
 
-    if time_zone >= 0 {
-        time_zone_string = String::with_capacity(3);
-        time_zone_string.push('+');
-        time_zone_string.push_str(time_zone.to_string().as_str());
-    } else {
-        time_zone_string = time_zone.to_string();
-    };
+/// Parses the birth year and visibility after a birthday's time zone. Either can be left out.
+async fn parse_birthday_details(
+    context: &Context, message: &Message, args: &mut Args,
+) -> argument_parser::Result<BirthdayDetails> {
+    let mut details = BirthdayDetails::default();
+    let mut arg_pos = 4;
+
+    if args.parse::<i64>().is_ok() {
+        let max_year = Utc::now().year() as i64;
+        let year_arg_info =
+            BoundedArgumentInfo::new(args, arg_pos, arg_pos, MIN_BIRTH_YEAR as i64, max_year);
+
+        details.birth_year =
+            Some(argument_parser::parse_bounded_arg(context, message, year_arg_info).await? as i32);
+        arg_pos += 1;
+    }
 
-    let birthday_set_message = if is_privileged {
-        format!(
-            "{}'s birthday will be set as ``{} {}``. They will get the birthday role for 24 \
-                hours starting at 0:00 UTC{} of that day. Are you sure this is what you want? \
-                Type ``{}birthdayconfirm`` to confirm this. This will expire in 30 seconds.",
-            target_id,
-            MONTH_TO_NAME[month_index],
-            day,
-            time_zone_string,
-            crate::PREFIX
-        )
-    } else {
-        format!(
-            "Your birthday will be set as ``{} {}``. You will get the birthday role for 24 \
-                hours starting at 0:00 UTC{} of that day. Are you sure this is what you want? You won't be able to change this again \
-                unless a moderator does it for you. Type ``{}birthdayconfirm`` to confirm this. This will expire in 30 seconds.",
-            MONTH_TO_NAME[month_index],
-            day,
-            time_zone_string,
-            crate::PREFIX
-        )
-    };
+    if !args.is_empty() {
+        let arg_info = ArgumentInfo::new(args, arg_pos, arg_pos);
 
-    let channel_id = message.channel_id;
+        details.visibility =
+            argument_parser::parse_choices(context, message, arg_info, BirthdayVisibility::iter())
+                .await?;
+    }
 
-    util::send_message(context, channel_id, birthday_set_message, "setbirthday").await;
+    Ok(details)
+}
 
-    let ctx_data = context.data.clone();
-    let ctx_http = context.http.clone();
-    let author_id = message.author.id.get();
-    let handle = tokio::spawn(async move {
-        sleep(Duration::from_millis(30000)).await;
+/// Parses a UTC offset or time zone name, telling the user what's wrong if it's invalid.
+async fn parse_time_zone(
+    context: &Context, message: &Message, args: &mut Args, arg_pos: usize, args_needed: usize,
+) -> argument_parser::Result<BirthdayTimeZone> {
+    let Some(arg) = args.current().map(str::to_owned) else {
+        argument_parser::not_enough_arguments(
+            context,
+            message.channel_id,
+            arg_pos - 1,
+            args_needed,
+        )
+        .await;
 
-        let data = ctx_data.read().await;
-        let mut birthday_info_map =
-            data.get::<BirthdayInfoConfirmationKey>().unwrap().write().await;
+        return Err(NotEnoughArgumentsError::new(args_needed, arg_pos - 1).into());
+    };
 
-        util::send_message(&ctx_http, channel_id, "Add birthday request expired.", "setbirthday")
-            .await;
+    match arg.parse::<BirthdayTimeZone>() {
+        Ok(time_zone) => {
+            args.advance();
 
-        birthday_info_map.remove(&author_id);
-    });
+            Ok(time_zone)
+        },
+        Err(error) => {
+            let reply = format!("Invalid argument #{arg_pos}. {error}");
 
-    let data = context.data.read().await;
-    let mut birthday_info_map = data.get::<BirthdayInfoConfirmationKey>().unwrap().write().await;
-    let info =
-        BirthdayInfoConfirmation::new(target_id, month, day, time_zone, handle, is_privileged);
+            util::send_message(context, message.channel_id, reply, "parse_time_zone").await;
 
-    if let Some(old_info) = birthday_info_map.insert(author_id, info) {
-        old_info.handle.abort(); // Abort the old timed remove.
+            Err(ArgumentConversionError::new(arg_pos, arg, ConversionType::TimeZone).into())
+        },
     }
-
-    Ok(())
 }
 
-#[command]
-#[only_in("guilds")]
-#[description("Confirms a birthday set with a previous command.")]
-#[aliases("bdayconfirm")]
-#[bucket("default")]
-async fn birthdayconfirm(context: &Context, message: &Message) -> CommandResult {
-    let data = context.data.read().await;
-    let birthday_info_map_lock_option = data.get::<BirthdayInfoConfirmationKey>();
-    let birthday_info_map;
-
-    if let Some(birthday_info_map_lock) = birthday_info_map_lock_option {
-        birthday_info_map = birthday_info_map_lock.read().await;
-
-        if let Some(info) = birthday_info_map.get(&message.author.id.get()) {
-            info.handle.abort(); // Abort the request expired message
-
-            if let Err(error) =
-                add_birthday_to_db(context, message.guild_id.unwrap(), message.channel_id, info)
-                    .await
-            {
-                match error {
-                    SerenitySQLiteError::SerenityError(errors) => error!(
-                        "Serenity error while adding birthday to db: {}",
-                        errors.serenity_errors[0]
-                    ),
-                    SerenitySQLiteError::SQLiteError(error) => {
-                        error!("SQLite error while adding birthday to db: {error}")
-                    },
-                }
-
-                error_util::generic_fail(context, message.channel_id).await;
-            }
+struct BirthdayRequest {
+    author_id: u64,
+    channel_id: ChannelId,
+    guild_id: GuildId,
+    target_id: u64,
+    birthday: birthday_tracker::Birthday,
+    details: Option<BirthdayDetails>,
+    is_privileged: bool,
+}
 
-            return Ok(());
+impl BirthdayRequest {
+    fn into_pending(self, message_id: MessageId) -> PendingBirthday {
+        PendingBirthday {
+            message_id: message_id.get(),
+            channel_id: self.channel_id.get(),
+            guild_id: self.guild_id.get(),
+            author_id: self.author_id,
+            user_id: self.target_id,
+            birthday: self.birthday,
+            details: self.details,
+            is_privileged: self.is_privileged,
+            expires_at: Utc::now() + birthday_tracker::BIRTHDAY_CONFIRMATION_TIMEOUT,
         }
     }
+}
 
-    let set_first_message = format!(
-        "Set your birthday first with {}setmybirthday if you're setting your own birthday \
-    or with {}setuserbirthday if you're setting someone else's birthday.",
-        crate::PREFIX,
-        crate::PREFIX
-    );
+/// The message asking the author to confirm a birthday with the buttons under it.
+async fn birthday_confirmation_message(context: &Context, request: &BirthdayRequest) -> String {
+    let BirthdayRequest { guild_id, target_id, birthday, details, is_privileged, .. } = *request;
+    let (month_index, day) = ((birthday.month() - 1) as usize, birthday.day());
+    let leap_day_policy = guild_config::get(context, guild_id).await.leap_day_policy;
+    let timeout = birthday_tracker::BIRTHDAY_CONFIRMATION_TIMEOUT.num_seconds();
+
+    // The offset shown for a named time zone is the one on this year's birthday.
+    let date = birthday.date_in(Utc::now().year(), leap_day_policy);
+    let time_zone_string = birthday.time_zone().describe_on(date);
+    let leap_day_note = if birthday.is_leap_day() {
+        format!(" In years without February 29, this server celebrates it on {leap_day_policy}.")
+    } else {
+        String::new()
+    };
+    let details_note = details.map(birthday_details_note).unwrap_or_default();
 
-    util::send_message(context, message.channel_id, set_first_message, "birthdayconfirm").await;
+    if is_privileged {
+        format!(
+            "{}'s birthday will be set as ``{} {}``. They will get the birthday role for \
+                all of that day in {}.{}{} Are you sure this is what you want? \
+                Press Confirm to save it. This will expire in {} seconds.",
+            target_id,
+            MONTH_TO_NAME[month_index],
+            day,
+            time_zone_string,
+            leap_day_note,
+            details_note,
+            timeout
+        )
+    } else {
+        format!(
+            "Your birthday will be set as ``{} {}``. You will get the birthday role for \
+                all of that day in {}.{}{} Are you sure this is what you want? You won't be able to change this again \
+                unless a moderator does it for you. Press Confirm to save it. This will expire in {} seconds.",
+            MONTH_TO_NAME[month_index], day, time_zone_string, leap_day_note, details_note, timeout
+        )
+    }
+}
 
-    Ok(())
+/// Describes a birth year and visibility for the confirmation message.
+fn birthday_details_note(details: BirthdayDetails) -> String {
+    let year_note = match details.birth_year {
+        Some(birth_year) => format!(" The birth year will be {birth_year}."),
+        None => String::new(),
+    };
+    let visibility_note = match details.visibility {
+        BirthdayVisibility::Public => " It will be shown to everyone.",
+        BirthdayVisibility::Staff => " It will only be shown to staff.",
+        BirthdayVisibility::Hidden => " It will be hidden from everyone.",
+    };
+
+    format!("{year_note}{visibility_note}")
 }
 
 #[command]
 #[only_in("guilds")]
 #[required_permissions(MANAGE_ROLES)]
 #[description(
-    "Removes a user's birthday so that they don't get any special roles on the configured day."
+    "Removes a user's birthday so that they don't get any special roles on the configured day, \
+        in any server."
 )]
 #[usage("<USER>")]
 #[example("367538590520967181")]
@@ -254,9 +295,11 @@ async fn birthdayconfirm(context: &Context, message: &Message) -> CommandResult
 async fn removeuserbirthday(context: &Context, message: &Message, mut args: Args) -> CommandResult {
     let arg_info = ArgumentInfo::new(&mut args, 1, 1);
     let user_id = argument_parser::parse_member(context, message, arg_info).await?.user.id;
-    let guild_id = message.guild_id.unwrap();
 
-    birthday_tracker::remove_birthday(context, message.channel_id, guild_id, user_id).await?;
+    let db = database::get(context).await;
+    let reply = birthday_tracker::remove_birthday(&*context.http, &db, user_id).await?;
+
+    util::send_message(context, message.channel_id, reply, "removeuserbirthday").await;
 
     Ok(())
 }
@@ -267,9 +310,11 @@ async fn removeuserbirthday(context: &Context, message: &Message, mut args: Args
 #[aliases("getmybday")]
 #[bucket("db_operations")]
 async fn getmybirthday(context: &Context, message: &Message) -> CommandResult {
-    let user_id = message.author.id.get();
+    let guild_id = message.guild_id.unwrap();
+    let embed =
+        birthday_tracker::get_birthday(context, guild_id, message.author.id.get(), false).await?;
 
-    birthday_tracker::get_birthday(context, message.channel_id, user_id).await?;
+    message.channel_id.send_message(context, CreateMessage::new().embed(embed)).await?;
 
     Ok(())
 }
@@ -286,8 +331,197 @@ async fn getmybirthday(context: &Context, message: &Message) -> CommandResult {
 async fn getuserbirthday(context: &Context, message: &Message, mut args: Args) -> CommandResult {
     let arg_info = ArgumentInfo::new(&mut args, 1, 1);
     let member = argument_parser::parse_member(context, message, arg_info).await?;
+    let guild_id = message.guild_id.unwrap();
+
+    let embed =
+        birthday_tracker::get_birthday(context, guild_id, member.user.id.get(), true).await?;
+
+    message.channel_id.send_message(context, CreateMessage::new().embed(embed)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in("guilds")]
+#[description(
+    "Stops you from getting this server's birthday role, without removing your birthday."
+)]
+#[aliases("bdayoptout")]
+#[bucket("db_operations")]
+async fn birthdayoptout(context: &Context, message: &Message) -> CommandResult {
+    let guild_id = message.guild_id.unwrap();
+    let db = database::get(context).await;
+    let reply =
+        birthday_tracker::opt_out_of_guild(&*context.http, &db, guild_id, message.author.id)
+            .await?;
+
+    util::send_message(context, message.channel_id, reply, "birthdayoptout").await;
+
+    Ok(())
+}
+
+#[command]
+#[only_in("guilds")]
+#[description("Lets you get this server's birthday role again after opting out.")]
+#[aliases("bdayoptin")]
+#[bucket("db_operations")]
+async fn birthdayoptin(context: &Context, message: &Message) -> CommandResult {
+    let guild_id = message.guild_id.unwrap();
+    let reply = birthday_tracker::opt_in_to_guild(context, guild_id, message.author.id).await?;
+
+    util::send_message(context, message.channel_id, reply, "birthdayoptin").await;
+
+    Ok(())
+}
+
+#[command]
+#[only_in("guilds")]
+#[description(
+    "Sets who can see your birthday. Public birthdays are announced and listed, staff birthdays \
+        are only shown to staff, and hidden birthdays are never shown. You get the birthday role \
+        either way."
+)]
+#[usage("<public/staff/hidden>")]
+#[example("staff")]
+#[aliases("bdayvisibility")]
+#[bucket("db_operations")]
+async fn birthdayvisibility(context: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let arg_info = ArgumentInfo::new(&mut args, 1, 1);
+    let visibility =
+        argument_parser::parse_choices(context, message, arg_info, BirthdayVisibility::iter())
+            .await?;
+    let reply =
+        birthday_tracker::set_birthday_visibility(context, message.author.id, visibility).await?;
+
+    util::send_message(context, message.channel_id, reply, "birthdayvisibility").await;
+
+    Ok(())
+}
+
+#[command]
+#[only_in("guilds")]
+#[description(
+    "Lists the birthdays in this server that are happening now or in the next few days. \
+        Looks at the next 7 days unless given a number of days."
+)]
+#[usage("[DAYS]")]
+#[example("")]
+#[example("30")]
+#[aliases("upcomingbdays")]
+#[bucket("db_operations")]
+async fn upcomingbirthdays(context: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let days = if args.is_empty() {
+        DEFAULT_UPCOMING_DAYS
+    } else {
+        let arg_info = BoundedArgumentInfo::new(&mut args, 1, 1, 1, MAX_UPCOMING_DAYS);
+
+        argument_parser::parse_bounded_arg(context, message, arg_info).await?
+    };
+
+    let guild_id = message.guild_id.unwrap();
+    let embed_chunks = birthday_tracker::upcoming_birthday_embeds(context, guild_id, days).await?;
+
+    send_embed_chunks(context, message.channel_id, embed_chunks).await
+}
 
-    birthday_tracker::get_birthday(context, message.channel_id, member.user.id.get()).await?;
+#[command]
+#[only_in("guilds")]
+#[description(
+    "Lists the birthdays in this server in a month. If the month is over for this year, \
+        it's next year's."
+)]
+#[usage("<MONTH>")]
+#[example("10")]
+#[aliases("bdays")]
+#[bucket("db_operations")]
+async fn birthdays(context: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let arg_info = BoundedArgumentInfo::new(&mut args, 1, 1, 1, 12);
+    let month = argument_parser::parse_bounded_arg(context, message, arg_info).await? as u32;
+    let guild_id = message.guild_id.unwrap();
+    let embed_chunks = birthday_tracker::month_birthday_embeds(context, guild_id, month).await?;
+
+    send_embed_chunks(context, message.channel_id, embed_chunks).await
+}
+
+#[command]
+#[only_in("guilds")]
+#[description(
+    "Sends a calendar file with everyone's birthdays in this server, repeating every year, \
+        for adding to calendar apps."
+)]
+#[aliases("bdaycalendar")]
+#[bucket("intense")]
+async fn birthdaycalendar(context: &Context, message: &Message) -> CommandResult {
+    let guild_id = message.guild_id.unwrap();
+    let attachment = birthday_tracker::birthday_calendar_attachment(context, guild_id).await?;
+
+    message.channel_id.send_message(context, CreateMessage::new().add_file(attachment)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[only_in("guilds")]
+#[required_permissions(ADMINISTRATOR)]
+#[description(
+    "Imports birthdays from an attached CSV or JSON file, like one from exportbirthdays. \
+        Every row needs a user ID, month, day and time zone, and can have a birth year and visibility. \
+        Every user has to be in this server. \
+        If any row has a problem, nothing is imported. Users who already have a birthday keep it."
+)]
+#[aliases("importbdays")]
+#[bucket("very_intense")]
+async fn importbirthdays(context: &Context, message: &Message) -> CommandResult {
+    let reply = match message.attachments.first() {
+        Some(attachment) => {
+            let guild_id = message.guild_id.unwrap();
+
+            birthday_tracker::import_birthdays(context, guild_id, attachment).await?
+        },
+        None => "Attach a ``.csv`` or ``.json`` file with the birthdays.".to_owned(),
+    };
+
+    util::send_message(context, message.channel_id, reply, "importbirthdays").await;
+
+    Ok(())
+}
+
+#[command]
+#[only_in("guilds")]
+#[required_permissions(ADMINISTRATOR)]
+#[description(
+    "Sends a CSV or JSON file with everyone's birthdays in this server, which importbirthdays \
+        can read back. The file is CSV unless JSON is asked for."
+)]
+#[usage("[csv/json]")]
+#[example("")]
+#[example("json")]
+#[aliases("exportbdays")]
+#[bucket("intense")]
+async fn exportbirthdays(context: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let format = if args.is_empty() {
+        BirthdayFileFormat::Csv
+    } else {
+        let arg_info = ArgumentInfo::new(&mut args, 1, 1);
+
+        argument_parser::parse_choices(context, message, arg_info, BirthdayFileFormat::iter())
+            .await?
+    };
+
+    let guild_id = message.guild_id.unwrap();
+    let attachment = birthday_tracker::export_birthdays(context, guild_id, format).await?;
+
+    message.channel_id.send_message(context, CreateMessage::new().add_file(attachment)).await?;
+
+    Ok(())
+}
+
+async fn send_embed_chunks(
+    context: &Context, channel_id: ChannelId, embed_chunks: Vec<Vec<CreateEmbed>>,
+) -> CommandResult {
+    for embeds in embed_chunks {
+        channel_id.send_message(context, CreateMessage::new().embeds(embeds)).await?;
+    }
 
     Ok(())
 }
@@ -308,7 +542,9 @@ async fn setserverbirthdayrole(
     let role_id = argument_parser::parse_role(context, message, arg_info).await?.get();
     let guild_id = message.guild_id.unwrap().get();
 
-    birthday_tracker::set_birthday_role(context, message.channel_id, guild_id, role_id).await?;
+    let reply = birthday_tracker::set_birthday_role(context, guild_id, role_id).await?;
+
+    util::send_message(context, message.channel_id, reply, "setserverbirthdayrole").await;
 
     Ok(())
 }
@@ -322,7 +558,9 @@ async fn setserverbirthdayrole(
 async fn getserverbirthdayrole(context: &Context, message: &Message) -> CommandResult {
     let guild_id = message.guild_id.unwrap().get();
 
-    birthday_tracker::get_birthday_role(context, message.channel_id, guild_id).await?;
+    let reply = birthday_tracker::get_birthday_role(context, guild_id).await?;
+
+    util::send_message(context, message.channel_id, reply, "getserverbirthdayrole").await;
 
     Ok(())
 }
@@ -334,15 +572,450 @@ async fn getserverbirthdayrole(context: &Context, message: &Message) -> CommandR
 #[aliases("removeserverbdayrole", "rmserverbdayrole", "removesvbdayrole", "rmsvbdayrole")]
 #[bucket("db_operations")]
 async fn removeserverbirthdayrole(context: &Context, message: &Message) -> CommandResult {
-    birthday_tracker::remove_birthday_role(context, message.channel_id, message.guild_id.unwrap())
-        .await?;
+    let reply = birthday_tracker::remove_birthday_role(context, message.guild_id.unwrap()).await?;
+
+    util::send_message(context, message.channel_id, reply, "removeserverbirthdayrole").await;
 
     Ok(())
 }
 
 #[group]
 #[commands(
-    setmybirthday, birthdayconfirm, setuserbirthday, removeuserbirthday, getuserbirthday,
-    getmybirthday, setserverbirthdayrole, getserverbirthdayrole, removeserverbirthdayrole
+    setmybirthday, setuserbirthday, removeuserbirthday, getuserbirthday, getmybirthday,
+    birthdayoptout, birthdayoptin, birthdayvisibility, upcomingbirthdays, birthdays,
+    birthdaycalendar, importbirthdays, exportbirthdays, setserverbirthdayrole,
+    getserverbirthdayrole, removeserverbirthdayrole
 )]
 struct Birthday;
+
+/// Checks the day against the month, which slash command options can't do on their own.
+async fn set_birthday_slash(
+    ctx: SlashContext<'_>, target_id: u64, month: u32, day: u32, time_zone: &str,
+    details: Option<BirthdayDetails>, is_privileged: bool,
+) -> CommandResult {
+    let month_index = (month - 1) as usize;
+    let time_zone = match time_zone.parse::<BirthdayTimeZone>() {
+        Ok(time_zone) => time_zone,
+        Err(error) => {
+            util::send_reply(ctx, error).await?;
+
+            return Ok(());
+        },
+    };
+
+    let Some(birthday) = birthday_tracker::Birthday::new(month, day, time_zone) else {
+        let reply =
+            format!("{} only has {} days.", MONTH_TO_NAME[month_index], MONTH_TO_DAYS[month_index]);
+
+        util::send_reply(ctx, reply).await?;
+
+        return Ok(());
+    };
+
+    let request = BirthdayRequest {
+        author_id: ctx.author().id.get(),
+        channel_id: ctx.channel_id(),
+        guild_id: ctx.guild_id().unwrap(),
+        target_id,
+        birthday,
+        details,
+        is_privileged,
+    };
+    let content = birthday_confirmation_message(ctx.serenity_context(), &request).await;
+    let reply = CreateReply::default()
+        .embed(util::message_embed(content))
+        .components(birthday_tracker::birthday_confirmation_buttons());
+    let handle = ctx.send(reply).await?;
+    let sent = handle.message().await?;
+    let pending = request.into_pending(sent.id);
+
+    birthday_tracker::add_pending_birthday(ctx.serenity_context(), pending).await?;
+
+    Ok(())
+}
+
+/// Suggests time zone names. Offsets like +5:30 can still be typed in.
+async fn autocomplete_time_zone<'a>(
+    _: SlashContext<'_>, partial: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    let partial = partial.to_lowercase();
+
+    TZ_VARIANTS
+        .iter()
+        .map(|tz| tz.name())
+        .filter(move |name| name.to_lowercase().contains(&partial))
+        .map(str::to_owned)
+}
+
+/// Sets your birthday so that you get a special role for the day in every server.
+#[poise::command(slash_command, guild_only, rename = "setmybirthday")]
+pub async fn setmybirthday_slash(
+    ctx: SlashContext<'_>,
+    #[description = "The month of your birthday"]
+    #[min = 1]
+    #[max = 12]
+    month: u32,
+    #[description = "The day of your birthday"]
+    #[min = 1]
+    #[max = 31]
+    day: u32,
+    #[description = "Your UTC offset on that date like -7 or +5:30, or a name like America/New_York"]
+    #[autocomplete = "autocomplete_time_zone"]
+    time_zone: String,
+    #[description = "Your birth year, to show the age you're turning"]
+    #[min = 1900]
+    birth_year: Option<i32>,
+    #[description = "Who can see your birthday: public, staff or hidden"]
+    #[autocomplete = "autocomplete_visibility"]
+    visibility: Option<String>,
+) -> CommandResult {
+    let details = match slash_birthday_details(birth_year, visibility.as_deref()) {
+        Ok(details) => details,
+        Err(reply) => {
+            util::send_reply(ctx, reply).await?;
+
+            return Ok(());
+        },
+    };
+    let is_privileged = ctx
+        .author_member()
+        .await
+        .and_then(|member| member.permissions)
+        .is_some_and(Permissions::manage_roles);
+
+    set_birthday_slash(ctx, ctx.author().id.get(), month, day, &time_zone, details, is_privileged)
+        .await
+}
+
+/// Checks the optional birth year and visibility, or says what's wrong with them.
+fn slash_birthday_details(
+    birth_year: Option<i32>, visibility: Option<&str>,
+) -> Result<Option<BirthdayDetails>, String> {
+    if birth_year.is_none() && visibility.is_none() {
+        return Ok(None);
+    }
+
+    let max_year = Utc::now().year();
+
+    if birth_year.is_some_and(|birth_year| !(MIN_BIRTH_YEAR..=max_year).contains(&birth_year)) {
+        return Err(format!("The birth year has to be between {MIN_BIRTH_YEAR} and {max_year}."));
+    }
+
+    let visibility = match visibility.map(BirthdayVisibility::from_str) {
+        Some(Ok(visibility)) => visibility,
+        Some(Err(_)) => return Err(VISIBILITY_CHOICES_REPLY.to_owned()),
+        None => BirthdayVisibility::default(),
+    };
+
+    Ok(Some(BirthdayDetails { birth_year, visibility }))
+}
+
+async fn autocomplete_visibility<'a>(
+    _: SlashContext<'_>, partial: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    let partial = partial.to_lowercase();
+
+    BirthdayVisibility::iter()
+        .map(|visibility| visibility.to_string())
+        .filter(move |name| name.starts_with(&partial))
+}
+
+/// Sets a user's birthday so that they get a special role for the day in every server.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "setuserbirthday",
+    required_permissions = "MANAGE_ROLES",
+    default_member_permissions = "MANAGE_ROLES"
+)]
+pub async fn setuserbirthday_slash(
+    ctx: SlashContext<'_>, #[description = "The user whose birthday to set"] user: Member,
+    #[description = "The month of their birthday"]
+    #[min = 1]
+    #[max = 12]
+    month: u32,
+    #[description = "The day of their birthday"]
+    #[min = 1]
+    #[max = 31]
+    day: u32,
+    #[description = "Their UTC offset on that date like -7 or +5:30, or a name like America/New_York"]
+    #[autocomplete = "autocomplete_time_zone"]
+    time_zone: String,
+) -> CommandResult {
+    set_birthday_slash(ctx, user.user.id.get(), month, day, &time_zone, None, true).await
+}
+
+/// Removes a user's birthday so that they don't get any special roles on the day, in any server.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "removeuserbirthday",
+    required_permissions = "MANAGE_ROLES",
+    default_member_permissions = "MANAGE_ROLES"
+)]
+pub async fn removeuserbirthday_slash(
+    ctx: SlashContext<'_>, #[description = "The user whose birthday to remove"] user: Member,
+) -> CommandResult {
+    let db = database::get(ctx.serenity_context()).await;
+    let reply =
+        birthday_tracker::remove_birthday(&*ctx.serenity_context().http, &db, user.user.id).await?;
+
+    util::send_reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+/// Gets your birthday.
+#[poise::command(slash_command, guild_only, rename = "getmybirthday")]
+pub async fn getmybirthday_slash(ctx: SlashContext<'_>) -> CommandResult {
+    let guild_id = ctx.guild_id().unwrap();
+    let embed = birthday_tracker::get_birthday(
+        ctx.serenity_context(),
+        guild_id,
+        ctx.author().id.get(),
+        false,
+    )
+    .await?;
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Gets a user's birthday.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "getuserbirthday",
+    required_permissions = "MANAGE_ROLES",
+    default_member_permissions = "MANAGE_ROLES"
+)]
+pub async fn getuserbirthday_slash(
+    ctx: SlashContext<'_>, #[description = "The user whose birthday to get"] user: Member,
+) -> CommandResult {
+    let guild_id = ctx.guild_id().unwrap();
+    let embed =
+        birthday_tracker::get_birthday(ctx.serenity_context(), guild_id, user.user.id.get(), true)
+            .await?;
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Stops you from getting this server's birthday role, without removing your birthday.
+#[poise::command(slash_command, guild_only, rename = "birthdayoptout")]
+pub async fn birthdayoptout_slash(ctx: SlashContext<'_>) -> CommandResult {
+    let guild_id = ctx.guild_id().unwrap();
+    let db = database::get(ctx.serenity_context()).await;
+    let reply = birthday_tracker::opt_out_of_guild(
+        &*ctx.serenity_context().http,
+        &db,
+        guild_id,
+        ctx.author().id,
+    )
+    .await?;
+
+    util::send_reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+/// Lets you get this server's birthday role again after opting out.
+#[poise::command(slash_command, guild_only, rename = "birthdayoptin")]
+pub async fn birthdayoptin_slash(ctx: SlashContext<'_>) -> CommandResult {
+    let guild_id = ctx.guild_id().unwrap();
+    let reply =
+        birthday_tracker::opt_in_to_guild(ctx.serenity_context(), guild_id, ctx.author().id)
+            .await?;
+
+    util::send_reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+/// Sets who can see your birthday. You get the birthday role either way.
+#[poise::command(slash_command, guild_only, rename = "birthdayvisibility")]
+pub async fn birthdayvisibility_slash(
+    ctx: SlashContext<'_>,
+    #[description = "public, staff or hidden"]
+    #[autocomplete = "autocomplete_visibility"]
+    visibility: String,
+) -> CommandResult {
+    let reply = match BirthdayVisibility::from_str(&visibility) {
+        Ok(visibility) => {
+            birthday_tracker::set_birthday_visibility(
+                ctx.serenity_context(),
+                ctx.author().id,
+                visibility,
+            )
+            .await?
+        },
+        Err(_) => VISIBILITY_CHOICES_REPLY,
+    };
+
+    util::send_reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+/// Lists the birthdays in this server happening now or in the next few days.
+#[poise::command(slash_command, guild_only, rename = "upcomingbirthdays")]
+pub async fn upcomingbirthdays_slash(
+    ctx: SlashContext<'_>,
+    #[description = "How many days to look ahead, 7 if not given"]
+    #[min = 1]
+    #[max = 366]
+    days: Option<i64>,
+) -> CommandResult {
+    let guild_id = ctx.guild_id().unwrap();
+    let days = days.unwrap_or(DEFAULT_UPCOMING_DAYS);
+    let embed_chunks =
+        birthday_tracker::upcoming_birthday_embeds(ctx.serenity_context(), guild_id, days).await?;
+
+    for embeds in embed_chunks {
+        ctx.send(CreateReply { embeds, ..Default::default() }).await?;
+    }
+
+    Ok(())
+}
+
+/// Lists the birthdays in this server in a month.
+#[poise::command(slash_command, guild_only, rename = "birthdays")]
+pub async fn birthdays_slash(
+    ctx: SlashContext<'_>,
+    #[description = "The month, from 1 to 12"]
+    #[min = 1]
+    #[max = 12]
+    month: u32,
+) -> CommandResult {
+    let guild_id = ctx.guild_id().unwrap();
+    let embed_chunks =
+        birthday_tracker::month_birthday_embeds(ctx.serenity_context(), guild_id, month).await?;
+
+    for embeds in embed_chunks {
+        ctx.send(CreateReply { embeds, ..Default::default() }).await?;
+    }
+
+    Ok(())
+}
+
+/// Sends a calendar file with everyone's birthdays in this server.
+#[poise::command(slash_command, guild_only, rename = "birthdaycalendar")]
+pub async fn birthdaycalendar_slash(ctx: SlashContext<'_>) -> CommandResult {
+    let guild_id = ctx.guild_id().unwrap();
+    let attachment =
+        birthday_tracker::birthday_calendar_attachment(ctx.serenity_context(), guild_id).await?;
+
+    ctx.send(CreateReply::default().attachment(attachment)).await?;
+
+    Ok(())
+}
+
+/// Imports birthdays from a CSV or JSON file. Nothing is imported if any row has a problem.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "importbirthdays",
+    required_permissions = "ADMINISTRATOR",
+    default_member_permissions = "ADMINISTRATOR"
+)]
+pub async fn importbirthdays_slash(
+    ctx: SlashContext<'_>,
+    #[description = "A .csv or .json file of user_id, month, day, time_zone and optionally birth_year, visibility"]
+    file: Attachment,
+) -> CommandResult {
+    ctx.defer().await?;
+
+    let guild_id = ctx.guild_id().unwrap();
+    let reply = birthday_tracker::import_birthdays(ctx.serenity_context(), guild_id, &file).await?;
+
+    util::send_reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+/// Sends a file with everyone's birthdays in this server, which importbirthdays can read back.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "exportbirthdays",
+    required_permissions = "ADMINISTRATOR",
+    default_member_permissions = "ADMINISTRATOR"
+)]
+pub async fn exportbirthdays_slash(
+    ctx: SlashContext<'_>, #[description = "csv or json, csv by default"] format: Option<String>,
+) -> CommandResult {
+    let format = match format.as_deref().map(BirthdayFileFormat::from_str) {
+        None => BirthdayFileFormat::Csv,
+        Some(Ok(format)) => format,
+        Some(Err(_)) => {
+            util::send_reply(ctx, "The format has to be ``csv`` or ``json``.").await?;
+
+            return Ok(());
+        },
+    };
+
+    let guild_id = ctx.guild_id().unwrap();
+    let attachment =
+        birthday_tracker::export_birthdays(ctx.serenity_context(), guild_id, format).await?;
+
+    ctx.send(CreateReply::default().attachment(attachment)).await?;
+
+    Ok(())
+}
+
+/// Sets a role to give to users when it's their birthday.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "setserverbirthdayrole",
+    required_permissions = "MANAGE_ROLES",
+    default_member_permissions = "MANAGE_ROLES"
+)]
+pub async fn setserverbirthdayrole_slash(
+    ctx: SlashContext<'_>, #[description = "The birthday role"] role: Role,
+) -> CommandResult {
+    let guild_id = ctx.guild_id().unwrap().get();
+    let reply =
+        birthday_tracker::set_birthday_role(ctx.serenity_context(), guild_id, role.id.get())
+            .await?;
+
+    util::send_reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+/// Gets the role to give to users when it's their birthday.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "getserverbirthdayrole",
+    required_permissions = "MANAGE_ROLES",
+    default_member_permissions = "MANAGE_ROLES"
+)]
+pub async fn getserverbirthdayrole_slash(ctx: SlashContext<'_>) -> CommandResult {
+    let guild_id = ctx.guild_id().unwrap().get();
+    let reply = birthday_tracker::get_birthday_role(ctx.serenity_context(), guild_id).await?;
+
+    util::send_reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+/// Removes the role to give to users when it's their birthday.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "removeserverbirthdayrole",
+    required_permissions = "MANAGE_ROLES",
+    default_member_permissions = "MANAGE_ROLES"
+)]
+pub async fn removeserverbirthdayrole_slash(ctx: SlashContext<'_>) -> CommandResult {
+    let guild_id = ctx.guild_id().unwrap();
+    let reply = birthday_tracker::remove_birthday_role(ctx.serenity_context(), guild_id).await?;
+
+    util::send_reply(ctx, reply).await?;
+
+    Ok(())
+}
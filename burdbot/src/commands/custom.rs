@@ -1,31 +1,54 @@
 use crate::argument_parser::{self, ArgumentInfo};
-use crate::commands::error_util;
+use crate::commands::{SlashContext, error_util};
+use crate::database;
+use crate::discord::DiscordHttp;
+use crate::error::SerenitySQLiteResult;
+use crate::guild_config::{self, GuildConfig, GuildConfigKey, GuildConfigValue};
 use crate::image_checker::{ImageChecker, MessageImages};
-use crate::spanish_english::{
-    IS_SERVER_HELPER_OR_ABOVE_CHECK, SPANISH_ENGLISH_SERVER_ID, SPANISH_ENGLISH_STAFF_CHANNEL_ID,
-    SPANISH_ENGLISH_STAFF_ROLE,
-};
+use crate::metrics;
+use crate::spanish_english::{IS_SERVER_HELPER_OR_ABOVE_CHECK, is_server_helper_or_above_slash};
 use crate::util::{self, get_ids_from_msg_link};
 
 use chrono::TimeDelta;
 use log::{error, info};
+use poise::CreateReply;
 use serenity::all::{
-    CreateAllowedMentions, CreateEmbed, CreateMessage, EMBED_MAX_COUNT, GuildId, Mentionable,
-    Permissions, Timestamp,
+    CreateAllowedMentions, CreateEmbed, CreateMessage, EMBED_MAX_COUNT, GuildId, Member,
+    Mentionable, Permissions, Timestamp, User,
 };
 use serenity::client::Context;
 use serenity::framework::standard::macros::{command, group};
 use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::channel::Message;
 use serenity::model::colour::Color;
-use serenity::model::id::{ChannelId, RoleId};
 use strum_macros::{Display, FromRepr};
 
+fn no_ban_role_message(role_key: GuildConfigKey, ch_name: &str) -> String {
+    format!(
+        "This server has no role set up for bans from the {ch_name} channel(s). An administrator \
+        can set one with the ``setguildconfig {role_key} <ROLE>`` command."
+    )
+}
+
 async fn banfromchannel(
-    ctx: &Context, msg: &Message, mut args: Args, role_id: RoleId, ch_name: &str,
+    ctx: &Context, msg: &Message, mut args: Args, role_key: GuildConfigKey, ch_name: &str,
 ) -> CommandResult<String> {
     let target =
         argument_parser::parse_member(ctx, msg, ArgumentInfo::new(&mut args, 1, 1)).await?;
+
+    let config = guild_config::get(ctx, msg.guild_id.unwrap()).await;
+
+    ban_member_from_channel(&*ctx.http, &config, &msg.author, &target, role_key, ch_name).await
+}
+
+async fn ban_member_from_channel(
+    http: &impl DiscordHttp, config: &GuildConfig, author: &User, target: &Member,
+    role_key: GuildConfigKey, ch_name: &str,
+) -> CommandResult<String> {
+    let Some(GuildConfigValue::Role(role_id)) = config.get(role_key) else {
+        return Ok(no_ban_role_message(role_key, ch_name));
+    };
+
     let target_id = target.user.id;
 
     Ok(if target.roles.contains(&role_id) {
@@ -36,7 +59,7 @@ async fn banfromchannel(
             ch_name
         )
     } else {
-        match target.add_role(&ctx, role_id).await {
+        match http.add_member_role(target.guild_id, target_id, role_id, None).await {
             Ok(()) => {
                 let target_name = target.user.name.as_str();
                 let embed = CreateEmbed::new()
@@ -44,12 +67,12 @@ async fn banfromchannel(
                     .title("User banned from channel(s).")
                     .description(format!(
                         "{} ({}) banned {} ({}) from the {} channel(s).",
-                        msg.author.name, msg.author.id, target_name, target_id, ch_name
+                        author.name, author.id, target_name, target_id, ch_name
                     ));
 
-                ChannelId::from(873845572975603792)
-                    .send_message(&ctx, CreateMessage::new().embed(embed))
-                    .await?;
+                if let Some(mod_log_channel) = config.mod_log_channel {
+                    http.send_message(mod_log_channel, CreateMessage::new().embed(embed)).await?;
+                }
 
                 format!(
                     "Successfully banned {target_name} ({target_id}) from the {ch_name} channel(s)."
@@ -67,14 +90,28 @@ async fn banfromchannel(
 }
 
 async fn unbanfromchannel(
-    ctx: &Context, msg: &Message, mut args: Args, role_id: RoleId, ch_name: &str,
+    ctx: &Context, msg: &Message, mut args: Args, role_key: GuildConfigKey, ch_name: &str,
 ) -> CommandResult<String> {
     let target =
         argument_parser::parse_member(ctx, msg, ArgumentInfo::new(&mut args, 1, 1)).await?;
+
+    let config = guild_config::get(ctx, msg.guild_id.unwrap()).await;
+
+    unban_member_from_channel(&*ctx.http, &config, &msg.author, &target, role_key, ch_name).await
+}
+
+async fn unban_member_from_channel(
+    http: &impl DiscordHttp, config: &GuildConfig, author: &User, target: &Member,
+    role_key: GuildConfigKey, ch_name: &str,
+) -> CommandResult<String> {
+    let Some(GuildConfigValue::Role(role_id)) = config.get(role_key) else {
+        return Ok(no_ban_role_message(role_key, ch_name));
+    };
+
     let target_id = target.user.id;
 
     Ok(if target.roles.contains(&role_id) {
-        match target.remove_role(&ctx, role_id).await {
+        match http.remove_member_role(target.guild_id, target_id, role_id, None).await {
             Ok(_) => {
                 let target_name = target.user.name.as_str();
                 let embed = CreateEmbed::new()
@@ -82,12 +119,12 @@ async fn unbanfromchannel(
                     .title("User unbanned from channel(s)")
                     .description(format!(
                         "{} ({}) unbanned {} ({}) from the {} channel(s).",
-                        msg.author.name, msg.author.id, target_name, target_id, ch_name
+                        author.name, author.id, target_name, target_id, ch_name
                     ));
 
-                ChannelId::from(873845572975603792)
-                    .send_message(&ctx, CreateMessage::new().embed(embed))
-                    .await?;
+                if let Some(mod_log_channel) = config.mod_log_channel {
+                    http.send_message(mod_log_channel, CreateMessage::new().embed(embed)).await?;
+                }
 
                 format!(
                     "Successfully unbanned {target_name} ({target_id}) from the {ch_name} channels."
@@ -120,7 +157,7 @@ async fn unbanfromchannel(
 #[description("Ban a user from the memes channel.")]
 async fn banfrommemes(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     let message_to_send =
-        banfromchannel(ctx, msg, args, RoleId::from(863822767702409216), "memes").await?;
+        banfromchannel(ctx, msg, args, GuildConfigKey::MemesBanRole, "memes").await?;
 
     util::send_message(ctx, msg.channel_id, message_to_send, "banfrommemes").await;
 
@@ -136,7 +173,7 @@ async fn banfrommemes(ctx: &Context, msg: &Message, args: Args) -> CommandResult
 #[description("Unban a user from the memes channel.")]
 async fn unbanfrommemes(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     let message_to_send =
-        unbanfromchannel(ctx, msg, args, RoleId::from(863822767702409216), "memes").await?;
+        unbanfromchannel(ctx, msg, args, GuildConfigKey::MemesBanRole, "memes").await?;
 
     util::send_message(ctx, msg.channel_id, message_to_send, "unbanfrommemes").await;
 
@@ -152,8 +189,7 @@ async fn unbanfrommemes(ctx: &Context, msg: &Message, args: Args) -> CommandResu
 #[description("Ban a user from the sesión de lectura channel.")]
 async fn banfromlectura(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     let message_to_send =
-        banfromchannel(ctx, msg, args, RoleId::from(1467650674945822937), "Sesión de lectura")
-            .await?;
+        banfromchannel(ctx, msg, args, GuildConfigKey::LecturaBanRole, "Sesión de lectura").await?;
 
     util::send_message(ctx, msg.channel_id, message_to_send, "banfromlectura").await;
 
@@ -169,7 +205,7 @@ async fn banfromlectura(ctx: &Context, msg: &Message, args: Args) -> CommandResu
 #[description("Unban a user from the sesión de lectura channel.")]
 async fn unbanfromlectura(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     let message_to_send =
-        unbanfromchannel(ctx, msg, args, RoleId::from(1467650674945822937), "Sesión de lectura")
+        unbanfromchannel(ctx, msg, args, GuildConfigKey::LecturaBanRole, "Sesión de lectura")
             .await?;
 
     util::send_message(ctx, msg.channel_id, message_to_send, "unbanfromlectura").await;
@@ -180,37 +216,24 @@ async fn unbanfromlectura(ctx: &Context, msg: &Message, args: Args) -> CommandRe
 /// Validates an image link by parsing and checking that:
 /// - The linked message isn't from the provided guild ID
 /// - The message link wasn't valid
+///
+/// Returns the reason as an error if it's invalid.
 async fn validate_image_link(
-    ctx: &Context, curr_channel: ChannelId, link: &str, from_guild: GuildId,
-) -> Option<Message> {
+    ctx: &Context, link: &str, from_guild: GuildId,
+) -> Result<Message, &'static str> {
     // Parse link
     let Some((guild_id, channel_id, msg_id)) = get_ids_from_msg_link(link) else {
-        util::send_message(ctx, curr_channel, "Invalid message link", "validate_image_link").await;
-        return None;
+        return Err("Invalid message link");
     };
 
     if from_guild != guild_id {
-        util::send_message(
-            ctx, curr_channel, "Message link must be from this server", "validate_image_link",
-        )
-        .await;
-        return None;
+        return Err("Message link must be from this server");
     }
 
-    let msg = channel_id.message(ctx, msg_id).await.ok();
-
-    if msg.is_none() {
-        util::send_message(
-            ctx,
-            curr_channel,
-            "Couldn't fetch message. Check message link is valid and I have \
-             permission to access it",
-            "validate_image_link",
-        )
-        .await;
-    }
-
-    msg
+    channel_id.message(ctx, msg_id).await.map_err(|_| {
+        "Couldn't fetch message. Check message link is valid and I have \
+         permission to access it"
+    })
 }
 
 static TIMEOUT_DURATION: TimeDelta = TimeDelta::days(7);
@@ -224,16 +247,16 @@ pub enum HashType {
 }
 
 /// Times out the user for 7 days and
-/// deletes the message. If it's the Spanish-English discord server,
-/// or a test server, then also notify in a channel.
+/// deletes the message. If the server has a staff channel configured,
+/// then notify staff there, otherwise reply to the message.
 /// Prints info trace and returns if no perms to time out, or delete
 ///
-/// Must provide the offending message and the guild ID
+/// Must provide the offending message and the guild's configuration
 async fn time_out_delete_and_notify(
-    ctx: &Context, msg: &Message, banned_img_link: &str, img_msg_link_db_ref: String,
-    guild_id: GuildId,
+    http: &impl DiscordHttp, config: &GuildConfig, msg: &Message, banned_img_link: &str,
+    img_msg_link_db_ref: String, guild_id: GuildId,
 ) {
-    let Ok(mut member) = guild_id.member(ctx, msg.author.id).await else {
+    let Ok(member) = http.get_member(guild_id, msg.author.id).await else {
         error!(
             "Failed to get member when trying to time out and delete their msgs. Guild ID \
                 {guild_id}. user id: {}",
@@ -244,12 +267,8 @@ async fn time_out_delete_and_notify(
 
     // Only delete msg if we have permission to timeout the member
     // Because it could be staff who's trying to paste the image
-    let timeout_res = member
-        .disable_communication_until_datetime(
-            ctx,
-            Timestamp::now().checked_add_signed(TIMEOUT_DURATION).unwrap().into(),
-        )
-        .await;
+    let timeout_end = Timestamp::now().checked_add_signed(TIMEOUT_DURATION).unwrap().into();
+    let timeout_res = http.time_out_member(guild_id, member.user.id, timeout_end).await;
 
     let timeout_str = format!("Timed out user for {} days", TIMEOUT_DURATION.num_days());
     let could_timeout = if let Err(e) = timeout_res {
@@ -269,11 +288,14 @@ async fn time_out_delete_and_notify(
         .field("Action taken", could_timeout, false)
         .timestamp(Timestamp::now());
 
-    let (ch_id, response) = if guild_id == SPANISH_ENGLISH_SERVER_ID {
-        let staff_notification = CreateMessage::new()
-            .embed(embed)
-            .content(SPANISH_ENGLISH_STAFF_ROLE.mention().to_string());
-        (SPANISH_ENGLISH_STAFF_CHANNEL_ID, staff_notification)
+    let (ch_id, response) = if let Some(staff_channel) = config.staff_channel {
+        let mut staff_notification = CreateMessage::new().embed(embed);
+
+        if let Some(staff_role) = config.staff_role {
+            staff_notification = staff_notification.content(staff_role.mention().to_string());
+        }
+
+        (staff_channel, staff_notification)
     } else {
         (
             msg.channel_id,
@@ -284,18 +306,17 @@ async fn time_out_delete_and_notify(
         )
     };
 
-    if let Err(e) = ch_id.send_message(ctx, response).await {
+    if let Err(e) = http.send_message(ch_id, response).await {
         error!("Error sending banned image resp to server {guild_id} channel {ch_id}. Err: {e}");
     }
 
-    if let Err(e) = msg.delete(ctx).await {
-        util::send_message(
-            ctx,
-            ch_id,
-            format!("Failed to delete message. Err: {e}"),
+    if let Err(e) = http.delete_message(msg.channel_id, msg.id).await {
+        let failure = util::message_embed(format!("Failed to delete message. Err: {e}"));
+
+        util::check_message_sending(
+            http.send_message(ch_id, CreateMessage::new().embed(failure)).await,
             "time_out_delete_and_notify",
-        )
-        .await;
+        );
     }
 
     info!("Deleted banned image in server: {} from {}", guild_id, member.user.id);
@@ -324,10 +345,19 @@ pub async fn on_message_receive(ctx: &Context, msg: &Message) {
         }
     }
 
+    let db = database::get(ctx).await;
+
     for image @ (img_link, ..) in images.to_vec() {
-        match IMAGE_HASHER.check_image(guild_id, image).await {
+        match IMAGE_HASHER.check_image(&db, guild_id, image).await {
             Ok(Some(db_link_ref)) => {
-                time_out_delete_and_notify(ctx, msg, img_link, db_link_ref, guild_id).await;
+                metrics::BANNED_IMAGE_HITS.inc();
+
+                let config = guild_config::get(ctx, guild_id).await;
+
+                time_out_delete_and_notify(
+                    &*ctx.http, &config, msg, img_link, db_link_ref, guild_id,
+                )
+                .await;
                 break;
             },
             Err(e) => error!("Internal error checking for banned image: {e:?}"),
@@ -336,6 +366,82 @@ pub async fn on_message_receive(ctx: &Context, msg: &Message) {
     }
 }
 
+async fn ban_image(ctx: &Context, guild_id: GuildId, link: &str, desc: &str) -> String {
+    let target_msg = match validate_image_link(ctx, link, guild_id).await {
+        Ok(target_msg) => target_msg,
+        Err(reason) => return reason.to_owned(),
+    };
+
+    let db = database::get(ctx).await;
+
+    match IMAGE_HASHER.add_image(&db, desc, guild_id, &target_msg, IMAGE_HASHER_TYPE as u16).await {
+        Ok(image_outcome) => image_outcome.to_string(),
+        Err(err) => {
+            error!("Error banning image: {err:?}");
+
+            error_util::GENERIC_FAIL.to_owned()
+        },
+    }
+}
+
+async fn unban_image(ctx: &Context, guild_id: GuildId, link: &str) -> String {
+    let db = database::get(ctx).await;
+
+    match IMAGE_HASHER.remove_image(&db, guild_id, link).await {
+        Ok(image_outcome) => image_outcome.to_string(),
+        Err(err) => {
+            error!("Error unbanning image: {err:?}");
+
+            error_util::GENERIC_FAIL.to_owned()
+        },
+    }
+}
+
+/// Makes an embed for every banned image, split into groups that fit in a message.
+async fn banned_image_embeds(
+    ctx: &Context, guild_id: GuildId,
+) -> SerenitySQLiteResult<Vec<Vec<CreateEmbed>>> {
+    let db = database::get(ctx).await;
+    let images = IMAGE_HASHER.get_images(&db, guild_id).await?;
+
+    if images.is_empty() {
+        let embed = CreateEmbed::new().color(Color::RED).title("No banned images found");
+
+        return Ok(vec![vec![embed]]);
+    }
+
+    let mut embed_chunks = Vec::new();
+
+    for image_chunk in images.chunks(EMBED_MAX_COUNT) {
+        let mut embeds = Vec::with_capacity(image_chunk.len());
+
+        for image in image_chunk {
+            let msg_link_parts = get_ids_from_msg_link(&image.link_ref);
+            let hash_type = HashType::from_repr(image.hash_type as usize).unwrap().to_string();
+            let mut embed = CreateEmbed::new()
+                .color(Color::DARK_GREEN)
+                .title(image.description.to_string())
+                .field("Link", image.link_ref.clone(), true)
+                .field("Dimensions", format!("{}x{}", image.width, image.height), true)
+                .field(format!("{hash_type} hash"), &image.hash_hex, false);
+
+            // Set thumbnail for the embed if available. If not, it may have been deleted
+            if let Some((_, ch_id, msg_id)) = msg_link_parts
+                && let Ok(msg) = ch_id.message(ctx, msg_id).await
+                && let Some(&(url, ..)) = MessageImages(&msg).to_vec().first()
+            {
+                embed = embed.thumbnail(url)
+            }
+
+            embeds.push(embed);
+        }
+
+        embed_chunks.push(embeds);
+    }
+
+    Ok(embed_chunks)
+}
+
 #[command]
 #[checks(is_server_helper_or_above)]
 #[only_in("guilds")]
@@ -357,28 +463,14 @@ async fn banimage(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
         return Ok(());
     }
 
-    let validated =
-        validate_image_link(ctx, msg.channel_id, args.current().unwrap(), msg.guild_id.unwrap())
-            .await;
-    let Some(target_msg) = validated else {
-        return Ok(());
-    };
+    let link = args.current().unwrap().to_owned();
 
     args.advance();
+
     let desc = args.remains().unwrap();
+    let reply = ban_image(ctx, msg.guild_id.unwrap(), &link, desc).await;
 
-    match IMAGE_HASHER
-        .add_image(desc, msg.guild_id.unwrap(), &target_msg, IMAGE_HASHER_TYPE as u16)
-        .await
-    {
-        Ok(image_outcome) => {
-            util::send_message(ctx, msg.channel_id, image_outcome.to_string(), "banimage").await;
-        },
-        Err(err) => {
-            error_util::generic_fail(ctx, msg.channel_id).await;
-            error!("Error banning image: {err:?}");
-        },
-    };
+    util::send_message(ctx, msg.channel_id, reply, "banimage").await;
 
     Ok(())
 }
@@ -401,15 +493,9 @@ async fn unbanimage(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
         return Ok(());
     }
 
-    match IMAGE_HASHER.remove_image(msg.guild_id.unwrap(), args.current().unwrap()) {
-        Ok(image_outcome) => {
-            util::send_message(ctx, msg.channel_id, image_outcome.to_string(), "unbanimage").await;
-        },
-        Err(err) => {
-            error_util::generic_fail(ctx, msg.channel_id).await;
-            error!("Error unbanning image: {err:?}");
-        },
-    };
+    let reply = unban_image(ctx, msg.guild_id.unwrap(), args.current().unwrap()).await;
+
+    util::send_message(ctx, msg.channel_id, reply, "unbanimage").await;
 
     Ok(())
 }
@@ -419,59 +505,26 @@ async fn unbanimage(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
 #[only_in("guilds")]
 #[description("Lists info on all banned images for the server.")]
 async fn bannedimages(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
-    let banned = IMAGE_HASHER.get_images(msg.guild_id.unwrap());
-
-    if let Ok(images) = banned {
-        if images.is_empty() {
-            let reply = CreateMessage::new()
-                .add_embed(CreateEmbed::new().color(Color::RED).title("No banned images found"));
-
-            if let Err(e) = msg.channel_id.send_message(ctx, reply).await {
-                info!(
-                    "Couldn't send message to {}. Likely have read perms but not write. error: {e:?}",
-                    msg.channel_id
-                );
-            }
+    let embed_chunks = match banned_image_embeds(ctx, msg.guild_id.unwrap()).await {
+        Ok(embed_chunks) => embed_chunks,
+        Err(e) => {
+            error_util::generic_fail(ctx, msg.channel_id).await;
+            error!("Error getting banned images: {e:?}");
 
             return Ok(());
-        }
-
-        for image_chunk in images.chunks(EMBED_MAX_COUNT) {
-            let mut reply = CreateMessage::new();
-
-            for image in image_chunk {
-                let msg_link_parts = get_ids_from_msg_link(&image.link_ref);
-                let hash_type = HashType::from_repr(image.hash_type as usize).unwrap().to_string();
-                let mut embed = CreateEmbed::new()
-                    .color(Color::DARK_GREEN)
-                    .title(image.description.to_string())
-                    .field("Link", image.link_ref.clone(), true)
-                    .field("Dimensions", format!("{}x{}", image.width, image.height), true)
-                    .field(format!("{hash_type} hash"), &image.hash_hex, false);
-
-                // Set thumbnail for the embed if available. If not, it may have been deleted
-                if let Some((_, ch_id, msg_id)) = msg_link_parts
-                    && let Ok(msg) = ch_id.message(ctx, msg_id).await
-                    && let Some(&(url, ..)) = MessageImages(&msg).to_vec().first()
-                {
-                    embed = embed.thumbnail(url)
-                }
-
-                reply = reply.add_embed(embed);
-            }
+        },
+    };
 
-            if let Err(e) = msg.channel_id.send_message(ctx, reply).await {
-                info!(
-                    "Couldn't send message to {}. Likely have read perms but not write. error: {e:?}",
-                    msg.channel_id
-                );
+    for embeds in embed_chunks {
+        if let Err(e) = msg.channel_id.send_message(ctx, CreateMessage::new().embeds(embeds)).await
+        {
+            info!(
+                "Couldn't send message to {}. Likely have read perms but not write. error: {e:?}",
+                msg.channel_id
+            );
 
-                return Ok(());
-            }
+            return Ok(());
         }
-    } else if let Err(e) = banned {
-        error_util::generic_fail(ctx, msg.channel_id).await;
-        error!("Error getting banned images: {e:?}");
     }
 
     Ok(())
@@ -483,3 +536,303 @@ async fn bannedimages(ctx: &Context, msg: &Message, _args: Args) -> CommandResul
     bannedimages
 )]
 struct Custom;
+
+/// Ban a user from the memes channel.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "banfrommemes",
+    check = "is_server_helper_or_above_slash"
+)]
+pub async fn banfrommemes_slash(
+    ctx: SlashContext<'_>, #[description = "The user to ban"] user: Member,
+) -> CommandResult {
+    let config = guild_config::get(ctx.serenity_context(), ctx.guild_id().unwrap()).await;
+    let reply = ban_member_from_channel(
+        &*ctx.serenity_context().http,
+        &config,
+        ctx.author(),
+        &user,
+        GuildConfigKey::MemesBanRole,
+        "memes",
+    )
+    .await?;
+
+    util::send_reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+/// Unban a user from the memes channel.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "unbanfrommemes",
+    check = "is_server_helper_or_above_slash"
+)]
+pub async fn unbanfrommemes_slash(
+    ctx: SlashContext<'_>, #[description = "The user to unban"] user: Member,
+) -> CommandResult {
+    let config = guild_config::get(ctx.serenity_context(), ctx.guild_id().unwrap()).await;
+    let reply = unban_member_from_channel(
+        &*ctx.serenity_context().http,
+        &config,
+        ctx.author(),
+        &user,
+        GuildConfigKey::MemesBanRole,
+        "memes",
+    )
+    .await?;
+
+    util::send_reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+/// Ban a user from the sesión de lectura channel.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "banfromlectura",
+    check = "is_server_helper_or_above_slash"
+)]
+pub async fn banfromlectura_slash(
+    ctx: SlashContext<'_>, #[description = "The user to ban"] user: Member,
+) -> CommandResult {
+    let config = guild_config::get(ctx.serenity_context(), ctx.guild_id().unwrap()).await;
+    let reply = ban_member_from_channel(
+        &*ctx.serenity_context().http,
+        &config,
+        ctx.author(),
+        &user,
+        GuildConfigKey::LecturaBanRole,
+        "Sesión de lectura",
+    )
+    .await?;
+
+    util::send_reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+/// Unban a user from the sesión de lectura channel.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "unbanfromlectura",
+    check = "is_server_helper_or_above_slash"
+)]
+pub async fn unbanfromlectura_slash(
+    ctx: SlashContext<'_>, #[description = "The user to unban"] user: Member,
+) -> CommandResult {
+    let config = guild_config::get(ctx.serenity_context(), ctx.guild_id().unwrap()).await;
+    let reply = unban_member_from_channel(
+        &*ctx.serenity_context().http,
+        &config,
+        ctx.author(),
+        &user,
+        GuildConfigKey::LecturaBanRole,
+        "Sesión de lectura",
+    )
+    .await?;
+
+    util::send_reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+/// Bans an image given a link to a message in this server with the image.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "banimage",
+    check = "is_server_helper_or_above_slash"
+)]
+pub async fn banimage_slash(
+    ctx: SlashContext<'_>,
+    #[description = "A link to the message with exactly one image"] link: String,
+    #[description = "What the image is"] description: String,
+) -> CommandResult {
+    // Hashing the image can take longer than Discord waits for a response.
+    ctx.defer().await?;
+
+    let guild_id = ctx.guild_id().unwrap();
+    let reply = ban_image(ctx.serenity_context(), guild_id, &link, &description).await;
+
+    util::send_reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+/// Unbans an image given a link to the message in this server with the image.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "unbanimage",
+    check = "is_server_helper_or_above_slash"
+)]
+pub async fn unbanimage_slash(
+    ctx: SlashContext<'_>,
+    #[description = "A link to the message with the banned image"] link: String,
+) -> CommandResult {
+    let reply = unban_image(ctx.serenity_context(), ctx.guild_id().unwrap(), &link).await;
+
+    util::send_reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+/// Lists info on all banned images for the server.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "bannedimages",
+    check = "is_server_helper_or_above_slash"
+)]
+pub async fn bannedimages_slash(ctx: SlashContext<'_>) -> CommandResult {
+    // Fetching the thumbnails can take a while.
+    ctx.defer().await?;
+
+    let embed_chunks = banned_image_embeds(ctx.serenity_context(), ctx.guild_id().unwrap()).await?;
+
+    for embeds in embed_chunks {
+        ctx.send(CreateReply { embeds, ..Default::default() }).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::all::{ChannelId, MessageId, RoleId, UserId};
+
+    use super::*;
+    use crate::discord::recording::{DiscordCall, RecordingHttp, test_member};
+
+    const GUILD_ID: u64 = 10;
+    const BAN_ROLE_ID: u64 = 20;
+    const MOD_LOG_CHANNEL_ID: u64 = 30;
+    const STAFF_CHANNEL_ID: u64 = 40;
+
+    fn config() -> GuildConfig {
+        GuildConfig {
+            memes_ban_role: Some(RoleId::new(BAN_ROLE_ID)),
+            mod_log_channel: Some(ChannelId::new(MOD_LOG_CHANNEL_ID)),
+            staff_channel: Some(ChannelId::new(STAFF_CHANNEL_ID)),
+            ..Default::default()
+        }
+    }
+
+    fn author() -> User {
+        let mut author = User::default();
+
+        author.id = UserId::new(1);
+        author.name = "helper".to_owned();
+
+        author
+    }
+
+    async fn ban(http: &RecordingHttp, target: &Member) -> String {
+        let role_key = GuildConfigKey::MemesBanRole;
+
+        ban_member_from_channel(http, &config(), &author(), target, role_key, "memes")
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn banning_from_channel_adds_role_and_logs() {
+        let http = RecordingHttp::new();
+        let target = test_member(GUILD_ID, 2, &[]);
+        let reply = ban(&http, &target).await;
+
+        assert!(reply.starts_with("Successfully banned"), "{reply}");
+        assert_eq!(
+            http.calls()[0],
+            DiscordCall::AddMemberRole {
+                guild_id: GuildId::new(GUILD_ID),
+                user_id: UserId::new(2),
+                role_id: RoleId::new(BAN_ROLE_ID),
+            }
+        );
+
+        let mod_logs = http.sent_to(ChannelId::new(MOD_LOG_CHANNEL_ID));
+
+        assert_eq!(mod_logs.len(), 1);
+        assert_eq!(mod_logs[0]["embeds"][0]["title"], "User banned from channel(s).");
+    }
+
+    #[tokio::test]
+    async fn banning_already_banned_member_does_nothing() {
+        let http = RecordingHttp::new();
+        let reply = ban(&http, &test_member(GUILD_ID, 2, &[BAN_ROLE_ID])).await;
+
+        assert!(reply.contains("already is banned"), "{reply}");
+        assert!(http.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_ban_is_not_logged() {
+        let http = RecordingHttp::new()
+            .fail_when(|call| matches!(call, DiscordCall::AddMemberRole { .. }));
+        let reply = ban(&http, &test_member(GUILD_ID, 2, &[])).await;
+
+        assert!(reply.starts_with("Failed to ban"), "{reply}");
+        assert!(http.sent_to(ChannelId::new(MOD_LOG_CHANNEL_ID)).is_empty());
+    }
+
+    fn image_message(author_id: u64) -> Message {
+        let mut msg = Message::default();
+
+        msg.id = MessageId::new(50);
+        msg.channel_id = ChannelId::new(60);
+        msg.guild_id = Some(GuildId::new(GUILD_ID));
+        msg.author.id = UserId::new(author_id);
+
+        msg
+    }
+
+    async fn handle_banned_image(http: &RecordingHttp, msg: &Message) {
+        let link = "https://discord.com/channels/10/60/40".to_owned();
+
+        time_out_delete_and_notify(http, &config(), msg, "image.png", link, GuildId::new(GUILD_ID))
+            .await;
+    }
+
+    #[tokio::test]
+    async fn banned_image_times_out_notifies_and_deletes() {
+        let http = RecordingHttp::new().with_member(test_member(GUILD_ID, 2, &[]));
+        let msg = image_message(2);
+
+        handle_banned_image(&http, &msg).await;
+
+        let calls = http.calls();
+
+        assert!(matches!(calls[1], DiscordCall::TimeOutMember { user_id, .. }
+            if user_id == UserId::new(2)));
+        assert_eq!(
+            calls.last(),
+            Some(&DiscordCall::DeleteMessage { channel_id: msg.channel_id, message_id: msg.id })
+        );
+
+        let notifications = http.sent_to(ChannelId::new(STAFF_CHANNEL_ID));
+
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0]["embeds"][0]["title"], "Banned Image Detected");
+    }
+
+    #[tokio::test]
+    async fn banned_image_from_unknown_member_is_left_alone() {
+        let http = RecordingHttp::new();
+
+        handle_banned_image(&http, &image_message(2)).await;
+
+        assert_eq!(
+            http.calls(),
+            vec![DiscordCall::GetMember {
+                guild_id: GuildId::new(GUILD_ID),
+                user_id: UserId::new(2),
+            }]
+        );
+    }
+}
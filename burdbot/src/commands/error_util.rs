@@ -1,16 +1,34 @@
 use serenity::client::Context;
+use serenity::framework::standard::CommandError;
 use serenity::model::id::ChannelId;
 
+use crate::argument_parser::ArgumentParseError;
+use crate::error::{SerenityErrors, SerenitySQLiteError};
 use crate::util;
 
+pub const GENERIC_FAIL: &str =
+    "Something went wrong. The owner of the bot has been notified of this.";
+
 pub async fn generic_fail(ctx: &Context, ch: ChannelId) {
-    util::send_message(
-        &ctx.http,
-        ch,
-        "Something went wrong. The owner of the bot has been notified of this.",
-        "generic_fail",
-    )
-    .await;
+    util::send_message(&ctx.http, ch, GENERIC_FAIL, "generic_fail").await;
+}
+
+/// A short name for what a command failed with, so failures can be grouped.
+pub fn error_kind(error: &CommandError) -> &'static str {
+    if error.is::<ArgumentParseError>() {
+        "argument"
+    } else if error.is::<serenity::Error>() || error.is::<SerenityErrors>() {
+        "discord"
+    } else if error.is::<rusqlite::Error>() {
+        "database"
+    } else if let Some(error) = error.downcast_ref::<SerenitySQLiteError>() {
+        match error {
+            SerenitySQLiteError::SerenityError(_) => "discord",
+            SerenitySQLiteError::SQLiteError(_) => "database",
+        }
+    } else {
+        "other"
+    }
 }
 
 /*pub async fn unknown_command_message(ctx: impl AsRef<Http>, ch: ChannelId) {
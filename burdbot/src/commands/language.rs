@@ -1,10 +1,13 @@
 use std::borrow::Borrow;
+use std::str::FromStr;
 
 use futures::StreamExt;
 use futures::future::join_all;
 use futures::stream;
 use log::debug;
 use log::error;
+use poise::CreateReply;
+use serenity::all::AutocompleteChoice;
 use serenity::all::CreateAttachment;
 use serenity::all::CreateMessage;
 use serenity::client::Context;
@@ -12,7 +15,6 @@ use serenity::framework::standard::macros::command;
 use serenity::framework::standard::macros::group;
 use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::channel::Message;
-use serenity::model::id::ChannelId;
 use strum::IntoEnumIterator;
 
 use crate::argument_parser;
@@ -23,7 +25,9 @@ use crate::forvo::Country;
 use crate::forvo::ForvoError;
 use crate::util;
 
-use super::error_util;
+use super::{SlashContext, error_util};
+
+const NO_PRONUNCIATION: &str = "No pronunciation found for the given term.";
 
 // Sanitizes a parsed word, percent-encoding it
 async fn sanitize_parsed_word(
@@ -81,26 +85,57 @@ async fn send_forvo_recording(
     }
 }
 
-async fn handle_recording_error<T>(
-    ctx: &Context, ch_id: ChannelId, term: &str, recording: &forvo::Result<T>, is_error: bool,
-) {
+/// Logs a recording error, returning whether it's one the user should be told about.
+fn log_recording_error<T>(term: &str, recording: &forvo::Result<T>, is_error: bool) -> bool {
     if let Err(err) = recording {
         if is_error {
             error!("{err} -- caused by term: {term}.");
 
-            error_util::generic_fail(ctx, ch_id).await;
-        } else {
-            debug!("Got likely harmless error - {err} -- caused by term: {term}.");
+            return true;
         }
+
+        debug!("Got likely harmless error - {err} -- caused by term: {term}.");
     }
+
+    false
 }
 
-async fn handle_recording_error_res<T>(
-    ctx: &Context, ch_id: ChannelId, term: &str, recording: forvo::Result<T>, is_error: bool,
-) -> forvo::Result<T> {
-    handle_recording_error(ctx, ch_id, term, &recording, is_error).await;
+struct Pronunciations {
+    recordings: Vec<(Vec<u8>, Country)>,
+    had_errors: bool,
+}
+
+/// Fetches every recording of `term` for the requested country, or the closest ones to it.
+async fn fetch_pronunciations(
+    term: &str, requested_country: Option<Country>,
+) -> forvo::Result<Pronunciations> {
+    let data_res = forvo::fetch_pronunciation(term, requested_country).await;
+
+    log_recording_error(term, &data_res, true);
+
+    let mut had_errors = false;
+    let mut recording_futures = Vec::new();
+
+    for res in data_res? {
+        match res {
+            err @ Err(ForvoError::InvalidMatchedCountry(_)) => {
+                log_recording_error(term, &err, false);
+            },
+            Err(_) => had_errors |= log_recording_error(term, &res, true),
+            Ok(recording) => recording_futures.push(recording),
+        }
+    }
+
+    let mut recordings = Vec::with_capacity(recording_futures.len());
 
-    recording
+    for recording in join_all(recording_futures.iter_mut().map(|r| r.get_recording())).await {
+        match recording {
+            Ok((data, country, _)) => recordings.push((data.to_vec(), country)),
+            Err(_) => had_errors |= log_recording_error(term, &recording, true),
+        }
+    }
+
+    Ok(Pronunciations { recordings, had_errors })
 }
 
 #[command]
@@ -131,41 +166,34 @@ async fn pronounce(ctx: &Context, msg: &Message, mut args: Args) -> CommandResul
         None
     };
 
-    let data_res = forvo::fetch_pronunciation(term.as_str(), requested_country).await;
-    let pronunciation_data =
-        handle_recording_error_res(ctx, msg.channel_id, term.as_str(), data_res, true).await?;
-    let mut recording_futures = Vec::new();
+    let pronunciations = match fetch_pronunciations(term.as_str(), requested_country).await {
+        Ok(pronunciations) => pronunciations,
+        Err(err) => {
+            error_util::generic_fail(ctx, msg.channel_id).await;
 
-    for recording_data_res in pronunciation_data {
-        let res = recording_data_res;
+            return Err(err.into());
+        },
+    };
 
-        match res {
-            err @ Err(ForvoError::InvalidMatchedCountry(_)) => {
-                handle_recording_error(ctx, msg.channel_id, term.as_str(), &err, false).await
-            },
-            Err(_) => handle_recording_error(ctx, msg.channel_id, term.as_str(), &res, true).await,
-            Ok(recording) => recording_futures.push(recording),
-        }
+    if pronunciations.had_errors {
+        error_util::generic_fail(ctx, msg.channel_id).await;
     }
 
-    if recording_futures.is_empty() {
-        util::send_message(
-            ctx,
-            msg.channel_id,
-            "No pronunciation found for the given term.",
-            "pronounce",
-        )
-        .await;
+    if pronunciations.recordings.is_empty() {
+        if !pronunciations.had_errors {
+            util::send_message(ctx, msg.channel_id, NO_PRONUNCIATION, "pronounce").await;
+        }
 
         return Ok(());
     }
 
-    stream::iter(join_all(recording_futures.iter_mut().map(|r| r.get_recording())).await)
-        .filter_map(|r| async {
-            handle_recording_error_res(ctx, msg.channel_id, term.as_str(), r, true).await.ok()
-        })
-        .for_each_concurrent(None, |(data, country, term)| async move {
-            send_forvo_recording(ctx, msg, term, country, data, requested_country).await;
+    stream::iter(pronunciations.recordings)
+        .for_each_concurrent(None, |(data, country)| {
+            let term = term.as_str();
+
+            async move {
+                send_forvo_recording(ctx, msg, term, country, &data, requested_country).await;
+            }
         })
         .await;
 
@@ -175,3 +203,61 @@ async fn pronounce(ctx: &Context, msg: &Message, mut args: Args) -> CommandResul
 #[group]
 #[commands(pronounce)]
 struct Language;
+
+async fn autocomplete_country<'a>(
+    _: SlashContext<'_>, partial: &'a str,
+) -> impl Iterator<Item = AutocompleteChoice> + 'a {
+    let partial = partial.to_lowercase();
+
+    Country::iter()
+        .filter(move |country| {
+            country.name().to_lowercase().contains(&partial) || country.to_string() == partial
+        })
+        .map(|country| {
+            AutocompleteChoice::new(format!("{country} {}", country.name()), country.to_string())
+        })
+}
+
+/// Fetches the pronunciation of something, optionally from a specific country.
+#[poise::command(slash_command, rename = "pronounce")]
+pub async fn pronounce_slash(
+    ctx: SlashContext<'_>, #[description = "What to pronounce"] term: String,
+    #[description = "The country whose accent to use"]
+    #[autocomplete = "autocomplete_country"]
+    country: Option<String>,
+) -> CommandResult {
+    let requested_country = match country.as_deref().map(Country::from_str) {
+        Some(Ok(country)) => Some(country),
+        Some(Err(_)) => {
+            util::send_reply(ctx, "Pick one of the suggested countries.").await?;
+
+            return Ok(());
+        },
+        None => None,
+    };
+
+    // Fetching from Forvo can take longer than Discord waits for a response.
+    ctx.defer().await?;
+
+    let term = urlencoding::encode(&term).into_owned();
+    let pronunciations = fetch_pronunciations(&term, requested_country).await?;
+
+    if pronunciations.recordings.is_empty() {
+        let reply =
+            if pronunciations.had_errors { error_util::GENERIC_FAIL } else { NO_PRONUNCIATION };
+
+        util::send_reply(ctx, reply).await?;
+
+        return Ok(());
+    }
+
+    for (data, country) in pronunciations.recordings {
+        let reply = CreateReply::default()
+            .content(get_pronounce_message(&term, country, requested_country))
+            .attachment(CreateAttachment::bytes(data, "forvo.mp3"));
+
+        ctx.send(reply).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,278 @@
+use std::str::FromStr;
+
+use log::error;
+use poise::CreateReply;
+use serenity::all::{AutocompleteChoice, Color, CreateEmbed, CreateMessage, GuildId};
+use serenity::client::{Cache, Context};
+use serenity::framework::standard::macros::{command, group};
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::channel::Message;
+use strum::IntoEnumIterator;
+
+use crate::argument_parser::{self, ArgumentInfo};
+use crate::commands::{SlashContext, error_util};
+use crate::guild_config::{self, GuildConfig, GuildConfigKey, GuildConfigValue};
+use crate::util;
+
+/// Checks that the channels and roles in a value exist in the guild
+fn exists_in_guild(cache: impl AsRef<Cache>, guild_id: GuildId, value: &GuildConfigValue) -> bool {
+    let Some(guild) = cache.as_ref().guild(guild_id) else {
+        return false;
+    };
+
+    match value {
+        GuildConfigValue::Channel(id) => guild.channels.contains_key(id),
+        GuildConfigValue::Role(id) => guild.roles.contains_key(id),
+        GuildConfigValue::Roles(ids) => ids.iter().all(|id| guild.roles.contains_key(id)),
+        GuildConfigValue::Bool(_)
+        | GuildConfigValue::Prefix(_)
+        | GuildConfigValue::LeapDayPolicy(_)
+        | GuildConfigValue::Template(_) => true,
+    }
+}
+
+fn config_embed(config: &GuildConfig) -> CreateEmbed {
+    let mut embed = CreateEmbed::new().title("Server configuration").color(Color::GOLD);
+
+    for key in GuildConfigKey::iter() {
+        let value = match config.get(key) {
+            Some(value) => value.to_string(),
+            None => "Not set".to_owned(),
+        };
+
+        embed = embed.field(key.to_string(), format!("{value}\n*{}*", key.description()), false);
+    }
+
+    embed
+}
+
+/// Sets `key` to the parsed `raw_value` and returns the reply.
+async fn set_guild_config(
+    ctx: &Context, guild_id: GuildId, key: GuildConfigKey, raw_value: &str,
+) -> String {
+    let value = match GuildConfigValue::parse(key.kind(), raw_value) {
+        Ok(value) => value,
+        Err(err) => return err.to_string(),
+    };
+
+    if !exists_in_guild(ctx, guild_id, &value) {
+        return format!("Couldn't find {value} in this server.");
+    }
+
+    let reply = format!("Set ``{key}`` to {value}.");
+
+    if let Err(err) = guild_config::set(ctx, guild_id, key, Some(value)).await {
+        error!("Error while setting guild config key {key} in {guild_id}: {err:?}");
+
+        return error_util::GENERIC_FAIL.to_owned();
+    }
+
+    reply
+}
+
+async fn unset_guild_config(ctx: &Context, guild_id: GuildId, key: GuildConfigKey) -> String {
+    if let Err(err) = guild_config::set(ctx, guild_id, key, None).await {
+        error!("Error while unsetting guild config key {key} in {guild_id}: {err:?}");
+
+        return error_util::GENERIC_FAIL.to_owned();
+    }
+
+    format!("Unset ``{key}``.")
+}
+
+#[command]
+#[description("Shows this server's configuration.")]
+#[aliases("serverconfig", "getguildconfig")]
+#[bucket("db_operations")]
+async fn guildconfig(ctx: &Context, msg: &Message) -> CommandResult {
+    let config = guild_config::get(ctx, msg.guild_id.unwrap()).await;
+
+    msg.channel_id.send_message(ctx, CreateMessage::new().embed(config_embed(&config))).await?;
+
+    Ok(())
+}
+
+#[command]
+#[description(
+    "Sets a configuration key for this server. Channels and roles can be given as IDs or mentions. \
+    Lists of roles are separated by commas or spaces."
+)]
+#[usage("<KEY> <VALUE>")]
+#[example("mod_log_channel #mod-log")]
+#[example("helper_roles 243854949522472971, 258806166770024449")]
+#[example("helper_check_bypass true")]
+#[aliases("setserverconfig")]
+#[bucket("db_operations")]
+async fn setguildconfig(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let arg_info = ArgumentInfo::new(&mut args, 1, 2);
+    let key = argument_parser::parse_choices(ctx, msg, arg_info, GuildConfigKey::iter()).await?;
+    let Some(raw_value) = args.remains() else {
+        argument_parser::not_enough_arguments(ctx, msg.channel_id, 1, 2).await;
+
+        return Ok(());
+    };
+
+    let reply = set_guild_config(ctx, msg.guild_id.unwrap(), key, raw_value).await;
+
+    util::send_message(ctx, msg.channel_id, reply, "setguildconfig").await;
+
+    Ok(())
+}
+
+#[command]
+#[description("Unsets a configuration key for this server.")]
+#[usage("<KEY>")]
+#[example("mod_log_channel")]
+#[aliases("unsetserverconfig")]
+#[bucket("db_operations")]
+async fn unsetguildconfig(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let arg_info = ArgumentInfo::new(&mut args, 1, 1);
+    let key = argument_parser::parse_choices(ctx, msg, arg_info, GuildConfigKey::iter()).await?;
+    let reply = unset_guild_config(ctx, msg.guild_id.unwrap(), key).await;
+
+    util::send_message(ctx, msg.channel_id, reply, "unsetguildconfig").await;
+
+    Ok(())
+}
+
+#[command]
+#[description(
+    "Sets the prefix for commands in this server. Leave it out to go back to the default prefix."
+)]
+#[usage("[PREFIX]")]
+#[example("!")]
+#[bucket("db_operations")]
+async fn setprefix(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap();
+    let reply = match args.remains() {
+        Some(prefix) => set_guild_config(ctx, guild_id, GuildConfigKey::Prefix, prefix).await,
+        None => unset_guild_config(ctx, guild_id, GuildConfigKey::Prefix).await,
+    };
+
+    util::send_message(ctx, msg.channel_id, reply, "setprefix").await;
+
+    Ok(())
+}
+
+#[group]
+#[only_in("guilds")]
+#[required_permissions("Administrator")]
+#[commands(guildconfig, setguildconfig, unsetguildconfig, setprefix)]
+struct ServerConfig;
+
+async fn autocomplete_key<'a>(
+    _: SlashContext<'_>, partial: &'a str,
+) -> impl Iterator<Item = AutocompleteChoice> + 'a {
+    GuildConfigKey::iter().filter(move |key| key.to_string().contains(&partial.to_lowercase())).map(
+        |key| AutocompleteChoice::new(format!("{key}: {}", key.description()), key.to_string()),
+    )
+}
+
+/// Parses a key from autocomplete, replying if it isn't one.
+async fn parse_key_slash(
+    ctx: SlashContext<'_>, key: &str,
+) -> CommandResult<Option<GuildConfigKey>> {
+    match GuildConfigKey::from_str(key) {
+        Ok(key) => Ok(Some(key)),
+        Err(_) => {
+            util::send_reply(ctx, format!("``{key}`` isn't a configuration key.")).await?;
+
+            Ok(None)
+        },
+    }
+}
+
+/// Shows this server's configuration.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "guildconfig",
+    required_permissions = "ADMINISTRATOR",
+    default_member_permissions = "ADMINISTRATOR"
+)]
+pub async fn guildconfig_slash(ctx: SlashContext<'_>) -> CommandResult {
+    let config = guild_config::get(ctx.serenity_context(), ctx.guild_id().unwrap()).await;
+
+    ctx.send(CreateReply::default().embed(config_embed(&config))).await?;
+
+    Ok(())
+}
+
+/// Sets a configuration key for this server.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "setguildconfig",
+    required_permissions = "ADMINISTRATOR",
+    default_member_permissions = "ADMINISTRATOR"
+)]
+pub async fn setguildconfig_slash(
+    ctx: SlashContext<'_>,
+    #[description = "The key to set"]
+    #[autocomplete = "autocomplete_key"]
+    key: String,
+    #[description = "A channel, role, list of roles, true/false or prefix, depending on the key"]
+    value: String,
+) -> CommandResult {
+    let Some(key) = parse_key_slash(ctx, &key).await? else {
+        return Ok(());
+    };
+
+    let reply =
+        set_guild_config(ctx.serenity_context(), ctx.guild_id().unwrap(), key, &value).await;
+
+    util::send_reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+/// Unsets a configuration key for this server.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "unsetguildconfig",
+    required_permissions = "ADMINISTRATOR",
+    default_member_permissions = "ADMINISTRATOR"
+)]
+pub async fn unsetguildconfig_slash(
+    ctx: SlashContext<'_>,
+    #[description = "The key to unset"]
+    #[autocomplete = "autocomplete_key"]
+    key: String,
+) -> CommandResult {
+    let Some(key) = parse_key_slash(ctx, &key).await? else {
+        return Ok(());
+    };
+
+    let reply = unset_guild_config(ctx.serenity_context(), ctx.guild_id().unwrap(), key).await;
+
+    util::send_reply(ctx, reply).await?;
+
+    Ok(())
+}
+
+/// Sets the prefix for text commands in this server, or goes back to the default one.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "setprefix",
+    required_permissions = "ADMINISTRATOR",
+    default_member_permissions = "ADMINISTRATOR"
+)]
+pub async fn setprefix_slash(
+    ctx: SlashContext<'_>,
+    #[description = "The new prefix, or none for the default"] prefix: Option<String>,
+) -> CommandResult {
+    let guild_id = ctx.guild_id().unwrap();
+    let reply = match prefix {
+        Some(prefix) => {
+            set_guild_config(ctx.serenity_context(), guild_id, GuildConfigKey::Prefix, &prefix)
+                .await
+        },
+        None => unset_guild_config(ctx.serenity_context(), guild_id, GuildConfigKey::Prefix).await,
+    };
+
+    util::send_reply(ctx, reply).await?;
+
+    Ok(())
+}
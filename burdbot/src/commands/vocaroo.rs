@@ -3,7 +3,6 @@ use std::collections::HashSet;
 
 use bytes::Bytes;
 use log::{debug, error, warn};
-use rusqlite::Connection;
 use serenity::Error;
 use serenity::all::CreateAttachment;
 use serenity::builder::CreateMessage;
@@ -13,8 +12,8 @@ use serenity::framework::standard::macros::{command, group};
 use serenity::model::channel::{Message, MessageReference, ReactionType};
 use serenity::prelude::TypeMapKey;
 
-use crate::BURDBOT_DB;
-use crate::commands::error_util;
+use crate::commands::{SlashContext, error_util};
+use crate::database;
 use crate::vocaroo::VocarooError;
 use crate::{util, vocaroo};
 
@@ -149,30 +148,8 @@ pub async fn on_message_received(ctx: &Context, msg: &Message) {
     }
 }
 
-fn get_all_vocaroo_servers() -> rusqlite::Result<HashSet<u64>> {
-    let mut vocaroo_servers = HashSet::new();
-
-    let connection = Connection::open(BURDBOT_DB)?;
-
-    let mut statement = connection.prepare(
-        "
-            SELECT guild_id FROM vocaroo_enabled;
-        ",
-    )?;
-
-    let query = statement.query_map([], |row| row.get::<_, u64>(0))?;
-
-    for guild_id in query {
-        let id = guild_id?;
-
-        vocaroo_servers.insert(id);
-    }
-
-    Ok(vocaroo_servers)
-}
-
 pub async fn on_ready(ctx: &Context) {
-    let vocaroo_servers = get_all_vocaroo_servers();
+    let vocaroo_servers = database::get(ctx).await.feature_toggles().vocaroo_servers().await;
 
     if let Ok(servers) = vocaroo_servers {
         let mut data = ctx.data.write().await;
@@ -181,55 +158,65 @@ pub async fn on_ready(ctx: &Context) {
     }
 }
 
-#[command]
-#[bucket("db_operations")]
-async fn enablevocarootomp3(ctx: &Context, msg: &Message) -> CommandResult {
+/// Turns the conversions on or off for a server and returns the reply,
+/// or `None` if the servers with conversions enabled never loaded.
+async fn set_vocaroo_enabled(
+    ctx: &Context, guild_id: u64, enable: bool,
+) -> CommandResult<Option<&'static str>> {
+    let db = database::get(ctx).await;
     let mut data = ctx.data.write().await;
     let vocaroo_servers = match data.get_mut::<VocarooEnabled>() {
         Some(servers) => servers,
-        None => return Ok(()),
+        None => return Ok(None),
     };
 
-    let guild_id = msg.guild_id.unwrap().get();
-
-    if vocaroo_servers.insert(guild_id) {
-        let connection = Connection::open(BURDBOT_DB)?;
-        let stmt = "
-                    INSERT OR IGNORE INTO vocaroo_enabled
-                    VALUES (?);
-            ";
+    if enable {
+        if !vocaroo_servers.insert(guild_id) {
+            return Ok(Some("Vocaroo to MP3 conversions are already enabled on this server."));
+        }
 
-        connection.execute(stmt, [guild_id])?;
+        db.feature_toggles().enable_vocaroo(guild_id).await?;
 
-        let enabled_str = "Enabled Vocaroo to MP3 conversions.";
+        Ok(Some("Enabled Vocaroo to MP3 conversions."))
+    } else {
+        if !vocaroo_servers.remove(&guild_id) {
+            return Ok(Some("Vocaroo to MP3 conversions are already disabled on this server."));
+        }
 
-        util::send_message(ctx, msg.channel_id, enabled_str, "enablevocarootomp3").await;
+        db.feature_toggles().disable_vocaroo(guild_id).await?;
 
-        return Ok(());
+        Ok(Some("Disabled Vocaroo to MP3 conversions."))
     }
+}
+
+async fn get_vocaroo_status(ctx: &Context, guild_id: u64) -> Option<&'static str> {
+    let data = ctx.data.read().await;
+    let vocaroo_servers = data.get::<VocarooEnabled>()?;
+
+    Some(if vocaroo_servers.contains(&guild_id) {
+        "Vocaroo to MP3 conversions are enabled in this server."
+    } else {
+        "Vocaroo to MP3 conversions are disabled in this server."
+    })
+}
 
-    let already_enabled_str = "Vocaroo to MP3 conversions are already enabled on this server.";
+#[command]
+#[bucket("db_operations")]
+async fn enablevocarootomp3(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap().get();
 
-    util::send_message(ctx, msg.channel_id, already_enabled_str, "enablevocarootomp3").await;
+    if let Some(reply) = set_vocaroo_enabled(ctx, guild_id, true).await? {
+        util::send_message(ctx, msg.channel_id, reply, "enablevocarootomp3").await;
+    }
 
     Ok(())
 }
 
 #[command]
 async fn isvocarootomp3enabled(ctx: &Context, msg: &Message) -> CommandResult {
-    let mut data = ctx.data.write().await;
-    let vocaroo_servers = match data.get_mut::<VocarooEnabled>() {
-        Some(servers) => servers,
-        None => return Ok(()),
-    };
-
-    let response = if vocaroo_servers.contains(&msg.guild_id.unwrap().get()) {
-        "Vocaroo to MP3 conversions are enabled in this server."
-    } else {
-        "Vocaroo to MP3 conversions are disabled in this server."
-    };
-
-    util::send_message(ctx, msg.channel_id, response, "isvocarootomp3enabled").await;
+    if let Some(reply) = get_vocaroo_status(ctx, msg.guild_id.unwrap().get()).await {
+        util::send_message(ctx, msg.channel_id, reply, "isvocarootomp3enabled").await;
+    }
 
     Ok(())
 }
@@ -237,40 +224,68 @@ async fn isvocarootomp3enabled(ctx: &Context, msg: &Message) -> CommandResult {
 #[command]
 #[bucket("db_operations")]
 async fn disablevocarootomp3(ctx: &Context, msg: &Message) -> CommandResult {
-    let mut data = ctx.data.write().await;
-    let vocaroo_servers = match data.get_mut::<VocarooEnabled>() {
-        Some(servers) => servers,
-        None => return Ok(()),
-    };
+    let guild_id = msg.guild_id.unwrap().get();
 
-    let guild_id = msg.guild_id.unwrap();
-    let id = guild_id.get();
+    if let Some(reply) = set_vocaroo_enabled(ctx, guild_id, false).await? {
+        util::send_message(ctx, msg.channel_id, reply, "disablevocarootomp3").await;
+    }
 
-    if vocaroo_servers.remove(&id) {
-        let connection = Connection::open(BURDBOT_DB)?;
-        let stmt = "
-                    DELETE FROM vocaroo_enabled
-                    WHERE guild_id = ?;
-            ";
+    Ok(())
+}
 
-        connection.execute(stmt, [id])?;
+#[group]
+#[required_permissions("manage_guild")]
+#[only_in("guilds")]
+#[commands(enablevocarootomp3, disablevocarootomp3, isvocarootomp3enabled)]
+struct Vocaroo;
 
-        let disabled_str = "Disabled Vocaroo to MP3 conversions.";
+/// Enables converting Vocaroo links in this server to MP3 files.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "enablevocarootomp3",
+    required_permissions = "MANAGE_GUILD",
+    default_member_permissions = "MANAGE_GUILD"
+)]
+pub async fn enablevocarootomp3_slash(ctx: SlashContext<'_>) -> CommandResult {
+    let guild_id = ctx.guild_id().unwrap().get();
+    let reply = set_vocaroo_enabled(ctx.serenity_context(), guild_id, true).await?;
 
-        util::send_message(ctx, msg.channel_id, disabled_str, "disablevocarootomp3").await;
+    util::send_reply(ctx, reply.unwrap_or(error_util::GENERIC_FAIL)).await?;
 
-        return Ok(());
-    }
+    Ok(())
+}
 
-    let already_enabled_str = "Vocaroo to MP3 conversions are already disabled on this server.";
+/// Disables converting Vocaroo links in this server to MP3 files.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "disablevocarootomp3",
+    required_permissions = "MANAGE_GUILD",
+    default_member_permissions = "MANAGE_GUILD"
+)]
+pub async fn disablevocarootomp3_slash(ctx: SlashContext<'_>) -> CommandResult {
+    let guild_id = ctx.guild_id().unwrap().get();
+    let reply = set_vocaroo_enabled(ctx.serenity_context(), guild_id, false).await?;
 
-    util::send_message(ctx, msg.channel_id, already_enabled_str, "disablevocarootomp3").await;
+    util::send_reply(ctx, reply.unwrap_or(error_util::GENERIC_FAIL)).await?;
 
     Ok(())
 }
 
-#[group]
-#[required_permissions("manage_guild")]
-#[only_in("guilds")]
-#[commands(enablevocarootomp3, disablevocarootomp3, isvocarootomp3enabled)]
-struct Vocaroo;
+/// Shows whether Vocaroo links in this server get converted to MP3 files.
+#[poise::command(
+    slash_command,
+    guild_only,
+    rename = "isvocarootomp3enabled",
+    required_permissions = "MANAGE_GUILD",
+    default_member_permissions = "MANAGE_GUILD"
+)]
+pub async fn isvocarootomp3enabled_slash(ctx: SlashContext<'_>) -> CommandResult {
+    let guild_id = ctx.guild_id().unwrap().get();
+    let reply = get_vocaroo_status(ctx.serenity_context(), guild_id).await;
+
+    util::send_reply(ctx, reply.unwrap_or(error_util::GENERIC_FAIL)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,418 @@
+//! Runtime settings.
+//!
+//! Settings are read from the TOML file passed with `--config <PATH>`, with every setting,
+//! even within a table, being optional. See `config.example.toml` for all of them and their
+//! defaults.
+//!
+//! Any setting can be overridden with an environment variable named `BURDBOT_CONFIG_`
+//! followed by the path to the setting in uppercase, with tables separated by `__`.
+//! For example, `BURDBOT_CONFIG_PREFIX=!` or `BURDBOT_CONFIG_BUCKETS__INTENSE__LIMIT=3`.
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs, io};
+
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use serenity::all::standard::BucketBuilder;
+use thiserror::Error;
+use toml::{Table, Value};
+
+const ENV_OVERRIDE_PREFIX: &str = "BURDBOT_CONFIG_";
+const ENV_OVERRIDE_SEPARATOR: &str = "__";
+
+static CONFIG: OnceCell<Config> = OnceCell::new();
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Couldn't read config file {0}: {1}")]
+    Io(PathBuf, io::Error),
+    #[error("Invalid config: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Invalid config: {0}")]
+    Invalid(String),
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The name of the environment variable holding the bot token.
+    pub token_env_var: String,
+    pub database: PathBuf,
+    /// Receives the bot's logs and is the bot's only owner.
+    pub owner_id: u64,
+    /// Used in DMs and in servers that haven't set their own prefix.
+    pub prefix: String,
+    /// Seconds to wait before retrying when the client fails to start.
+    pub retry_connection_interval: u64,
+    pub logger: LoggerConfig,
+    pub buckets: BucketsConfig,
+    pub metrics: MetricsConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            token_env_var: "BURDBOT_TOKEN".to_owned(),
+            database: PathBuf::from("burdbot.db"),
+            owner_id: 367538590520967181,
+            prefix: ",".to_owned(),
+            retry_connection_interval: 30,
+            logger: LoggerConfig::default(),
+            buckets: BucketsConfig::default(),
+            metrics: MetricsConfig::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggerConfig {
+    /// Buffer size in bytes for BurdBot's own logs.
+    pub burdbot_buffer_size: usize,
+    /// Buffer size in bytes for the logs of dependencies.
+    pub default_buffer_size: usize,
+    /// Seconds between sending logs.
+    pub write_cooldown: u64,
+    pub failed_file: String,
+    pub file_name: String,
+}
+
+impl Default for LoggerConfig {
+    fn default() -> Self {
+        LoggerConfig {
+            burdbot_buffer_size: (1 << 10) * 32, // 32KB
+            default_buffer_size: 1 << 10,        // 1KB
+            write_cooldown: 15,
+            failed_file: "failed-to-send-logs.txt".to_owned(),
+            file_name: "log.txt".to_owned(),
+        }
+    }
+}
+
+impl LoggerConfig {
+    pub fn write_cooldown(&self) -> Duration {
+        Duration::from_secs(self.write_cooldown)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct BucketConfig {
+    /// Seconds a user has to wait between commands.
+    pub delay: u64,
+    /// How many commands a user can use within `time_span`.
+    pub limit: u32,
+    /// Length of the rate limit window in seconds.
+    pub time_span: u64,
+}
+
+impl BucketConfig {
+    const fn new(delay: u64, limit: u32, time_span: u64) -> Self {
+        BucketConfig { delay, limit, time_span }
+    }
+
+    pub fn to_builder(self) -> BucketBuilder {
+        BucketBuilder::new_user().delay(self.delay).limit(self.limit).time_span(self.time_span)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct BucketsConfig {
+    pub default: BucketConfig,
+    pub intense: BucketConfig,
+    pub db_operations: BucketConfig,
+    pub very_intense: BucketConfig,
+}
+
+impl Default for BucketsConfig {
+    fn default() -> Self {
+        BucketsConfig {
+            default: BucketConfig::new(1, 5, 10),
+            intense: BucketConfig::new(2, 2, 10),
+            db_operations: BucketConfig::new(3, 3, 10),
+            very_intense: BucketConfig::new(10, 4, 600),
+        }
+    }
+}
+
+impl BucketsConfig {
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, BucketConfig)> {
+        [
+            ("default", self.default),
+            ("intense", self.intense),
+            ("db_operations", self.db_operations),
+            ("very_intense", self.very_intense),
+        ]
+        .into_iter()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    /// Whether to serve Prometheus metrics at `http://<address>/metrics`.
+    pub enabled: bool,
+    pub address: String,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        MetricsConfig { enabled: false, address: "127.0.0.1:9184".to_owned() }
+    }
+}
+
+impl Config {
+    /// Loads the config from `path`, or the defaults if there's no path,
+    /// then applies the environment variable overrides.
+    pub fn load(path: Option<&Path>) -> Result<Config, ConfigError> {
+        let table = match path {
+            Some(path) => fs::read_to_string(path)
+                .map_err(|err| ConfigError::Io(path.to_owned(), err))?
+                .parse::<Table>()?,
+            None => Table::new(),
+        };
+
+        Config::from_table(table, env::vars())
+    }
+
+    /// Applies `table` and then the environment variable overrides on top of the defaults, so
+    /// that tables only need the settings that differ from them.
+    fn from_table(
+        table: Table, env_vars: impl Iterator<Item = (String, String)>,
+    ) -> Result<Config, ConfigError> {
+        let defaults = Table::try_from(Config::default()).expect("The defaults should serialize.");
+        let mut table = {
+            let mut merged = defaults.clone();
+
+            merge_tables(&mut merged, table);
+
+            merged
+        };
+
+        apply_env_overrides(&mut table, &defaults, env_vars)?;
+
+        let config = table.try_into::<Config>()?;
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |reason: &str| Err(ConfigError::Invalid(reason.to_owned()));
+
+        if self.token_env_var.is_empty() {
+            return invalid("token_env_var can't be empty");
+        }
+
+        if self.prefix.is_empty() || self.prefix.contains(char::is_whitespace) {
+            return invalid("prefix can't be empty or contain whitespace");
+        }
+
+        if self.owner_id == 0 {
+            return invalid("owner_id must be a user ID");
+        }
+
+        if self.logger.burdbot_buffer_size == 0 || self.logger.default_buffer_size == 0 {
+            return invalid("logger buffer sizes must be greater than 0");
+        }
+
+        if self.logger.failed_file.is_empty() || self.logger.file_name.is_empty() {
+            return invalid("logger file names can't be empty");
+        }
+
+        for (name, bucket) in self.buckets.iter() {
+            if bucket.limit == 0 || bucket.time_span == 0 {
+                let reason =
+                    format!("bucket {name} must have a limit and time_span greater than 0");
+
+                return Err(ConfigError::Invalid(reason));
+            }
+        }
+
+        if self.metrics.enabled && self.metrics.address.parse::<SocketAddr>().is_err() {
+            return invalid("metrics address must be an IP address and port");
+        }
+
+        Ok(())
+    }
+}
+
+/// Replaces the values in `base` with the ones in `overrides`, merging tables key by key.
+fn merge_tables(base: &mut Table, overrides: Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base_inner)), Value::Table(inner)) => {
+                merge_tables(base_inner, inner)
+            },
+            (_, value) => {
+                base.insert(key, value);
+            },
+        }
+    }
+}
+
+/// The value at `keys` in `table`, if there is one.
+fn lookup<'a>(table: &'a Table, keys: &[&str]) -> Option<&'a Value> {
+    let (last_key, keys) = keys.split_last()?;
+    let mut current = table;
+
+    for key in keys {
+        current = current.get(*key)?.as_table()?;
+    }
+
+    current.get(*last_key)
+}
+
+/// Parses an override as the same type as the setting's `default`, so that string settings stay
+/// strings even if they look like numbers. Otherwise it's an integer or bool if possible.
+fn parse_override_value(value: String, default: Option<&Value>) -> Value {
+    if let Some(Value::String(_)) = default {
+        Value::String(value)
+    } else if let Ok(integer) = value.parse::<i64>() {
+        Value::Integer(integer)
+    } else if let Ok(boolean) = value.parse::<bool>() {
+        Value::Boolean(boolean)
+    } else {
+        Value::String(value)
+    }
+}
+
+fn apply_env_overrides(
+    table: &mut Table, defaults: &Table, vars: impl Iterator<Item = (String, String)>,
+) -> Result<(), ConfigError> {
+    for (name, value) in vars {
+        let Some(path) = name.strip_prefix(ENV_OVERRIDE_PREFIX) else {
+            continue;
+        };
+
+        let path = path.to_lowercase();
+        let mut keys = path.split(ENV_OVERRIDE_SEPARATOR).collect::<Vec<_>>();
+        let value = parse_override_value(value, lookup(defaults, &keys));
+        let last_key = keys.pop().unwrap();
+        let mut current = &mut *table;
+
+        for key in keys {
+            let entry = current.entry(key).or_insert_with(|| Value::Table(Table::new()));
+
+            current = match entry {
+                Value::Table(inner) => inner,
+                _ => {
+                    return Err(ConfigError::Invalid(format!(
+                        "{name} overrides a non-table value"
+                    )));
+                },
+            };
+        }
+
+        current.insert(last_key.to_owned(), value);
+    }
+
+    Ok(())
+}
+
+/// Sets the config for the rest of the program. Can only be called once.
+pub fn init(config: Config) {
+    CONFIG.set(config).expect("The config should only be initialized once.");
+}
+
+/// Gets the config, which is the default config if it was never initialized.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_str(toml: &str, vars: &[(&str, &str)]) -> Result<Config, ConfigError> {
+        let vars = vars.iter().map(|(name, value)| (name.to_string(), value.to_string()));
+
+        Config::from_table(toml.parse::<Table>()?, vars)
+    }
+
+    #[test]
+    fn example_config_is_default() {
+        let example = include_str!("../config.example.toml");
+
+        assert_eq!(load_str(example, &[]).unwrap(), Config::default());
+    }
+
+    #[test]
+    fn partial_config() {
+        let config =
+            load_str("prefix = \"!\"\n[buckets.intense]\ndelay = 1\nlimit = 1\ntime_span = 5", &[])
+                .unwrap();
+
+        assert_eq!(config.prefix, "!");
+        assert_eq!(config.buckets.intense, BucketConfig::new(1, 1, 5));
+        assert_eq!(config.buckets.default, BucketsConfig::default().default);
+        assert_eq!(config.database, PathBuf::from("burdbot.db"));
+    }
+
+    #[test]
+    fn env_overrides() {
+        let vars = [
+            ("BURDBOT_CONFIG_PREFIX", "?"),
+            ("BURDBOT_CONFIG_DATABASE", "staging.db"),
+            ("BURDBOT_CONFIG_LOGGER__WRITE_COOLDOWN", "60"),
+            ("BURDBOT_TOKEN", "not an override"),
+        ];
+        let config = load_str("prefix = \"!\"", &vars).unwrap();
+
+        assert_eq!(config.prefix, "?");
+        assert_eq!(config.database, PathBuf::from("staging.db"));
+        assert_eq!(config.logger.write_cooldown, 60);
+    }
+
+    #[test]
+    fn partial_tables_keep_their_defaults() {
+        let vars = [("BURDBOT_CONFIG_BUCKETS__INTENSE__LIMIT", "3")];
+        let config = load_str("", &vars).unwrap();
+
+        assert_eq!(config.buckets.intense, BucketConfig::new(2, 3, 10));
+
+        let config = load_str("[buckets.very_intense]\ndelay = 5", &[]).unwrap();
+
+        assert_eq!(config.buckets.very_intense, BucketConfig::new(5, 4, 600));
+    }
+
+    #[test]
+    fn string_overrides_stay_strings() {
+        let vars = [("BURDBOT_CONFIG_PREFIX", "1"), ("BURDBOT_CONFIG_METRICS__ADDRESS", "true")];
+        let config = load_str("", &vars).unwrap();
+
+        assert_eq!(config.prefix, "1");
+        assert_eq!(config.metrics.address, "true");
+        assert!(matches!(
+            load_str("", &[("BURDBOT_CONFIG_OWNER_ID", "me")]),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(matches!(load_str("prefx = \"!\"", &[]), Err(ConfigError::Parse(_))));
+        assert!(matches!(
+            load_str("", &[("BURDBOT_CONFIG_LOGGER__SIZE", "1")]),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert!(matches!(load_str("prefix = \"\"", &[]), Err(ConfigError::Invalid(_))));
+        assert!(matches!(load_str("owner_id = \"me\"", &[]), Err(ConfigError::Parse(_))));
+        assert!(matches!(
+            load_str("[buckets.default]\ndelay = 1\nlimit = 0\ntime_span = 10", &[]),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            load_str("[metrics]\nenabled = true\naddress = \"localhost\"", &[]),
+            Err(ConfigError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,150 @@
+//! The shared handle to BurdBot's database.
+//!
+//! There's one connection for the whole bot. Queries run on tokio's blocking
+//! thread pool so SQLite I/O never blocks the runtime, and the connection is long-lived
+//! so prepared statements stay cached between calls.
+//!
+//! Queries are grouped into repositories by domain, which are accessed through
+//! [`Database`]. Callers outside this module shouldn't need to write SQL.
+
+mod banned_images;
+mod birthdays;
+mod command_usage;
+mod feature_toggles;
+mod guild_configs;
+mod staff_logs;
+
+pub use banned_images::{BannedImages, NewBannedImage};
+pub use birthdays::{Birthdays, PendingBirthday};
+pub use command_usage::{CommandUsage, NewCommandUsage};
+pub use feature_toggles::FeatureToggles;
+pub use guild_configs::GuildConfigs;
+pub use staff_logs::{
+    StaffLog, StaffLogCategory, StaffLogDeletion, StaffLogRevision, StaffLogSearch,
+    StaffLogSeverity, StaffLogs,
+};
+
+use std::sync::{Arc, Mutex, PoisonError};
+
+use rusqlite::Connection;
+use serenity::client::Context;
+use serenity::prelude::TypeMapKey;
+
+const PREPARED_STATEMENT_CACHE_CAPACITY: usize = 64;
+
+#[derive(Clone)]
+pub struct Database {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl TypeMapKey for Database {
+    type Value = Database;
+}
+
+impl Database {
+    pub fn new(connection: Connection) -> Self {
+        connection.set_prepared_statement_cache_capacity(PREPARED_STATEMENT_CACHE_CAPACITY);
+
+        Database { connection: Arc::new(Mutex::new(connection)) }
+    }
+
+    /// Runs `func` with the connection on a blocking thread.
+    pub async fn call<T, F>(&self, func: F) -> rusqlite::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+
+        tokio::task::spawn_blocking(move || {
+            // A panic mid-query leaves nothing half done since transactions roll back on drop.
+            let mut connection = connection.lock().unwrap_or_else(PoisonError::into_inner);
+
+            func(&mut connection)
+        })
+        .await
+        .expect("Database task panicked.")
+    }
+
+    pub fn banned_images(&self) -> BannedImages<'_> {
+        BannedImages(self)
+    }
+
+    pub fn birthdays(&self) -> Birthdays<'_> {
+        Birthdays(self)
+    }
+
+    pub fn command_usage(&self) -> CommandUsage<'_> {
+        CommandUsage(self)
+    }
+
+    pub fn feature_toggles(&self) -> FeatureToggles<'_> {
+        FeatureToggles(self)
+    }
+
+    pub fn guild_configs(&self) -> GuildConfigs<'_> {
+        GuildConfigs(self)
+    }
+
+    pub fn staff_logs(&self) -> StaffLogs<'_> {
+        StaffLogs(self)
+    }
+}
+
+/// Gets the database handle inserted when the client was built.
+pub async fn get(ctx: &Context) -> Database {
+    let data = ctx.data.read().await;
+
+    data.get::<Database>().expect("The database should be in the type map.").clone()
+}
+
+#[cfg(test)]
+pub(crate) fn open_test_database() -> Database {
+    let mut connection = Connection::open_in_memory().unwrap();
+
+    crate::migrations::run_migrations(&mut connection, false).unwrap();
+
+    Database::new(connection)
+}
+
+/// A migrated database in a temporary file, for tests that go through whole command flows.
+/// The file gets deleted when this is dropped.
+#[cfg(test)]
+pub(crate) struct TempDatabase {
+    db: Database,
+    path: std::path::PathBuf,
+}
+
+#[cfg(test)]
+impl TempDatabase {
+    pub(crate) fn new() -> Self {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        static NEXT_ID: AtomicU32 = AtomicU32::new(0);
+
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let path =
+            std::env::temp_dir().join(format!("burdbot-test-{}-{id}.db", std::process::id()));
+        let mut connection = Connection::open(&path).unwrap();
+
+        crate::migrations::run_migrations(&mut connection, false).unwrap();
+
+        TempDatabase { db: Database::new(connection), path }
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TempDatabase {
+    type Target = Database;
+
+    fn deref(&self) -> &Database {
+        &self.db
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempDatabase {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
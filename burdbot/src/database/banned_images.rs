@@ -0,0 +1,93 @@
+use rusqlite::params;
+
+use crate::image_checker::ImageResult;
+
+use super::Database;
+
+pub struct NewBannedImage {
+    pub link_ref: String,
+    pub width: u32,
+    pub height: u32,
+    pub description: String,
+    pub hash: Vec<u8>,
+    pub hash_type: u16,
+}
+
+/// Image hashes banned per guild.
+pub struct BannedImages<'a>(pub(super) &'a Database);
+
+impl BannedImages<'_> {
+    /// Returns false if the image was already banned.
+    pub async fn add(&self, guild_id: u64, image: NewBannedImage) -> rusqlite::Result<bool> {
+        self.0
+            .call(move |connection| {
+                let mut statement = connection.prepare_cached(
+                    "
+                    INSERT OR IGNORE INTO fxhash_image_checksums
+                        VALUES (?, ?, ?, ?, ?, ?, ?);
+                    ",
+                )?;
+
+                let rows_updated = statement.execute(params!(
+                    image.link_ref, image.width, image.height, image.description, image.hash,
+                    image.hash_type, guild_id
+                ))?;
+
+                Ok(rows_updated > 0)
+            })
+            .await
+    }
+
+    /// Returns false if the image wasn't banned.
+    pub async fn remove(&self, guild_id: u64, link_ref: String) -> rusqlite::Result<bool> {
+        self.0
+            .call(move |connection| {
+                let mut statement = connection.prepare_cached(
+                    "
+                    DELETE FROM fxhash_image_checksums
+                        WHERE link_reference = ? AND guild_id = ?;
+                    ",
+                )?;
+
+                Ok(statement.execute(params!(link_ref, guild_id))? > 0)
+            })
+            .await
+    }
+
+    /// Gets the hash and link reference of every banned image with the given dimensions.
+    pub async fn get_hashes(
+        &self, guild_id: u64, width: u32, height: u32,
+    ) -> rusqlite::Result<Vec<(Vec<u8>, String)>> {
+        self.0
+            .call(move |connection| {
+                let mut statement = connection.prepare_cached(
+                    "
+                    SELECT hash, link_reference FROM fxhash_image_checksums
+                    WHERE guild_id = ?1 AND width = ?2 AND height = ?3;
+                    ",
+                )?;
+
+                statement
+                    .query_map(params![guild_id, width, height], |row| {
+                        Ok((row.get(0)?, row.get(1)?))
+                    })?
+                    .collect()
+            })
+            .await
+    }
+
+    pub async fn get_all(&self, guild_id: u64) -> rusqlite::Result<Vec<ImageResult>> {
+        self.0
+            .call(move |connection| {
+                let mut statement = connection.prepare_cached(
+                    "
+                    SELECT link_reference, width, height, description, hash, hash_type FROM fxhash_image_checksums
+                    WHERE guild_id = ?1;
+                    ",
+                )?;
+
+                statement.query_and_then([guild_id], |row| ImageResult::try_from(row))?.collect()
+            })
+            .await
+    }
+}
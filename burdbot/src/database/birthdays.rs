@@ -0,0 +1,874 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, Row, Transaction, params};
+
+use crate::birthday_tracker::{Birthday, BirthdayDetails, BirthdayVisibility, LeapDayPolicy};
+
+use super::Database;
+
+/// A birthday that just started in a guild.
+pub struct StartedBirthday {
+    pub user_id: u64,
+    pub guild_id: u64,
+    pub role_id: u64,
+    pub ends_at: DateTime<Utc>,
+}
+
+/// Users who should lose or gain their birthday role. Removals are `(user_id, guild_id, role_id)`.
+pub struct BirthdayRoleChanges {
+    pub removals: Vec<(u64, u64, u64)>,
+    pub additions: Vec<StartedBirthday>,
+}
+
+pub struct RemovedBirthday {
+    /// The guilds the birthday was ongoing in, as `(guild_id, role_id)`.
+    pub ongoing_roles: Vec<(u64, u64)>,
+}
+
+/// A guild's birthday role and the users whose birthdays are ongoing there, who should have it.
+pub struct OngoingBirthdayRole {
+    pub guild_id: u64,
+    pub role_id: u64,
+    pub user_ids: Vec<u64>,
+}
+
+pub struct RemovedBirthdayRole {
+    pub role_id: u64,
+    /// Users whose birthdays were ongoing and so still have the role.
+    pub user_ids: Vec<u64>,
+}
+
+/// A birthday waiting for its author to press the confirm button on `message_id`.
+pub struct PendingBirthday {
+    pub message_id: u64,
+    pub channel_id: u64,
+    pub guild_id: u64,
+    pub author_id: u64,
+    /// The user whose birthday it is, who can be someone else for privileged authors.
+    pub user_id: u64,
+    pub birthday: Birthday,
+    /// `None` keeps the details of the birthday being replaced, or the defaults.
+    pub details: Option<BirthdayDetails>,
+    /// Whether the author can overwrite a birthday that's already set.
+    pub is_privileged: bool,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Reads a birthday from a row with `month`, `day` and `time_zone` columns.
+fn read_birthday(row: &Row) -> rusqlite::Result<Birthday> {
+    let day: u32 = row.get("day")?;
+    let day_index = row.as_ref().column_index("day")?;
+
+    Birthday::new(row.get("month")?, day, row.get("time_zone")?)
+        .ok_or(rusqlite::Error::IntegralValueOutOfRange(day_index, day.into()))
+}
+
+fn read_details(row: &Row) -> rusqlite::Result<BirthdayDetails> {
+    Ok(BirthdayDetails { birth_year: row.get("birth_year")?, visibility: row.get("visibility")? })
+}
+
+fn read_pending_birthday(row: &Row) -> rusqlite::Result<PendingBirthday> {
+    let expires_at = row.get("expires_at")?;
+    let expires_at_index = row.as_ref().column_index("expires_at")?;
+    let visibility: Option<BirthdayVisibility> = row.get("visibility")?;
+
+    Ok(PendingBirthday {
+        message_id: row.get("message_id")?,
+        channel_id: row.get("channel_id")?,
+        guild_id: row.get("guild_id")?,
+        author_id: row.get("author_id")?,
+        user_id: row.get("user_id")?,
+        birthday: read_birthday(row)?,
+        details: visibility.map(|_| read_details(row)).transpose()?,
+        is_privileged: row.get("is_privileged")?,
+        expires_at: DateTime::from_timestamp(expires_at, 0)
+            .ok_or(rusqlite::Error::IntegralValueOutOfRange(expires_at_index, expires_at))?,
+    })
+}
+
+fn query_details(
+    connection: &Connection, user_id: u64,
+) -> rusqlite::Result<Option<BirthdayDetails>> {
+    connection
+        .prepare_cached(
+            "
+            SELECT birth_year, visibility
+            FROM bday
+            WHERE user_id = ?;
+            ",
+        )?
+        .query_row([user_id], read_details)
+        .optional()
+}
+
+fn query_role(connection: &Connection, guild_id: u64) -> rusqlite::Result<Option<u64>> {
+    connection
+        .prepare_cached(
+            "
+            SELECT role_id
+            FROM bday_role_list
+            WHERE guild_id = ?;
+            ",
+        )?
+        .query_row([guild_id], |row| row.get::<_, u64>(0))
+        .optional()
+}
+
+fn get_and_delete_old_bdays(
+    transaction: &Transaction, date_time: DateTime<Utc>,
+) -> rusqlite::Result<Vec<(u64, u64, u64)>> {
+    let timestamp = date_time.timestamp();
+    let query_info = transaction
+        .prepare_cached(
+            "
+            SELECT
+                bday_user_list.user_id,
+                bday_user_list.guild_id,
+                bday_role_list.role_id
+            FROM bday_user_list
+                INNER JOIN bday_role_list ON bday_user_list.guild_id = bday_role_list.guild_id
+            WHERE bday_user_list.ends_at <= ?;
+            ",
+        )?
+        .query_map([timestamp], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    transaction
+        .prepare_cached(
+            "
+            DELETE FROM bday_user_list
+            WHERE ends_at <= ?;
+            ",
+        )?
+        .execute([timestamp])?;
+
+    // Announcements only need remembering while their birthday could start again.
+    transaction
+        .prepare_cached(
+            "
+            DELETE FROM bday_announcements
+            WHERE ends_at <= ?;
+            ",
+        )?
+        .execute([timestamp])?;
+
+    Ok(query_info)
+}
+
+fn add_new_bdays(
+    transaction: &Transaction, date_time: DateTime<Utc>,
+    leap_day_policies: &HashMap<u64, LeapDayPolicy>, is_member: impl Fn(u64, u64) -> bool,
+) -> rusqlite::Result<Vec<StartedBirthday>> {
+    // Every birthday gets checked in every guild with a birthday role, since when they start
+    // depends on the year and on the guild's leap day policy.
+    let bdays = transaction
+        .prepare_cached(
+            "
+            SELECT
+                bday.user_id,
+                bday_role_list.guild_id,
+                bday_role_list.role_id,
+                bday.month,
+                bday.day,
+                bday.time_zone
+            FROM bday
+                CROSS JOIN bday_role_list
+            WHERE NOT EXISTS
+            (
+                SELECT 1
+                FROM bday_opt_outs
+                WHERE bday_opt_outs.user_id = bday.user_id
+                    AND bday_opt_outs.guild_id = bday_role_list.guild_id
+            );
+            ",
+        )?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, read_birthday(row)?)))?
+        .collect::<rusqlite::Result<Vec<(u64, u64, u64, Birthday)>>>()?;
+
+    let mut insertion_statement = transaction.prepare_cached(
+        "
+        INSERT OR IGNORE INTO bday_user_list (user_id, guild_id, ends_at)
+            VALUES (?, ?, ?);
+        ",
+    )?;
+    let mut query_info = Vec::new();
+
+    for (user_id, guild_id, bday_role_id, birthday) in bdays {
+        let policy = leap_day_policies.get(&guild_id).copied().unwrap_or_default();
+        let Some(window) = birthday.window_at(date_time, policy) else {
+            continue;
+        };
+
+        if !is_member(guild_id, user_id) {
+            continue;
+        }
+
+        let rows_changed =
+            insertion_statement.execute(params![user_id, guild_id, window.end.timestamp()])?;
+
+        if rows_changed != 0 {
+            query_info.push(StartedBirthday {
+                user_id,
+                guild_id,
+                role_id: bday_role_id,
+                ends_at: window.end,
+            });
+        }
+    }
+
+    Ok(query_info)
+}
+
+/// Birthdays, the birthdays currently ongoing in each guild, and each guild's birthday role.
+/// Birthdays belong to users, so they count in every guild with a birthday role that
+/// the user is in, unless they opted out of it.
+pub struct Birthdays<'a>(pub(super) &'a Database);
+
+impl Birthdays<'_> {
+    /// Saves a birthday, only replacing an existing one if `overwrite` is set. Without
+    /// `details`, the replaced birthday's details are kept. Returns whether it was saved.
+    pub async fn set(
+        &self, user_id: u64, birthday: Birthday, details: Option<BirthdayDetails>, overwrite: bool,
+    ) -> rusqlite::Result<bool> {
+        self.0
+            .call(move |connection| {
+                let transaction = connection.transaction()?;
+                let details = match details {
+                    Some(details) => details,
+                    None => query_details(&transaction, user_id)?.unwrap_or_default(),
+                };
+                let saved = {
+                    let mut statement = if overwrite {
+                        transaction.prepare_cached(
+                            "
+                            INSERT OR REPLACE INTO bday
+                                (user_id, month, day, time_zone, birth_year, visibility)
+                            VALUES (?, ?, ?, ?, ?, ?);
+                            ",
+                        )?
+                    } else {
+                        transaction.prepare_cached(
+                            "
+                            INSERT OR IGNORE INTO bday
+                                (user_id, month, day, time_zone, birth_year, visibility)
+                            VALUES (?, ?, ?, ?, ?, ?);
+                            ",
+                        )?
+                    };
+                    let (month, day, time_zone) =
+                        (birthday.month(), birthday.day(), birthday.time_zone());
+
+                    statement.execute(params!(
+                        user_id, month, day, time_zone, details.birth_year, details.visibility
+                    ))? > 0
+                };
+
+                transaction.commit()?;
+
+                Ok(saved)
+            })
+            .await
+    }
+
+    /// Saves several birthdays at once, all or none of them. Existing birthdays are kept.
+    /// Returns how many were saved.
+    pub async fn set_many(
+        &self, birthdays: Vec<(u64, Birthday, BirthdayDetails)>,
+    ) -> rusqlite::Result<usize> {
+        self.0
+            .call(move |connection| {
+                let transaction = connection.transaction()?;
+                let mut saved = 0;
+
+                {
+                    let mut statement = transaction.prepare_cached(
+                        "
+                        INSERT OR IGNORE INTO bday
+                            (user_id, month, day, time_zone, birth_year, visibility)
+                        VALUES (?, ?, ?, ?, ?, ?);
+                        ",
+                    )?;
+
+                    for (user_id, birthday, details) in birthdays {
+                        let (month, day, time_zone) =
+                            (birthday.month(), birthday.day(), birthday.time_zone());
+
+                        saved += statement.execute(params!(
+                            user_id, month, day, time_zone, details.birth_year, details.visibility
+                        ))?;
+                    }
+                }
+
+                transaction.commit()?;
+
+                Ok(saved)
+            })
+            .await
+    }
+
+    pub async fn get(&self, user_id: u64) -> rusqlite::Result<Option<(Birthday, BirthdayDetails)>> {
+        self.0
+            .call(move |connection| {
+                connection
+                    .prepare_cached(
+                        "
+                        SELECT month, day, time_zone, birth_year, visibility
+                        FROM bday
+                        WHERE user_id = ?;
+                        ",
+                    )?
+                    .query_row([user_id], |row| Ok((read_birthday(row)?, read_details(row)?)))
+                    .optional()
+            })
+            .await
+    }
+
+    /// Changes who can see the birthday. Returns whether the user has a birthday.
+    pub async fn set_visibility(
+        &self, user_id: u64, visibility: BirthdayVisibility,
+    ) -> rusqlite::Result<bool> {
+        self.0
+            .call(move |connection| {
+                let changed = connection
+                    .prepare_cached(
+                        "
+                        UPDATE bday
+                        SET visibility = ?
+                        WHERE user_id = ?;
+                        ",
+                    )?
+                    .execute(params!(visibility, user_id))?;
+
+                Ok(changed > 0)
+            })
+            .await
+    }
+
+    /// Every birthday that counts in `guild_id`, as `(user_id, birthday, details)`. Users who
+    /// opted out are left out, but whether the rest are in the guild has to be checked
+    /// separately.
+    pub async fn get_all_in_guild(
+        &self, guild_id: u64,
+    ) -> rusqlite::Result<Vec<(u64, Birthday, BirthdayDetails)>> {
+        self.0
+            .call(move |connection| {
+                connection
+                    .prepare_cached(
+                        "
+                        SELECT user_id, month, day, time_zone, birth_year, visibility
+                        FROM bday
+                        WHERE user_id NOT IN
+                        (
+                            SELECT user_id
+                            FROM bday_opt_outs
+                            WHERE guild_id = ?
+                        );
+                        ",
+                    )?
+                    .query_map([guild_id], |row| {
+                        Ok((row.get("user_id")?, read_birthday(row)?, read_details(row)?))
+                    })?
+                    .collect()
+            })
+            .await
+    }
+
+    /// Marks a birthday as ongoing in `guild_id` until `ends_at`. Returns whether it was,
+    /// which it isn't if it already was or the user opted out of the guild.
+    pub async fn add_ongoing(
+        &self, user_id: u64, guild_id: u64, ends_at: DateTime<Utc>,
+    ) -> rusqlite::Result<bool> {
+        self.0
+            .call(move |connection| {
+                let added = connection
+                    .prepare_cached(
+                        "
+                        INSERT OR IGNORE INTO bday_user_list (user_id, guild_id, ends_at)
+                            SELECT ?1, ?2, ?3
+                            WHERE NOT EXISTS
+                            (
+                                SELECT 1
+                                FROM bday_opt_outs
+                                WHERE user_id = ?1 AND guild_id = ?2
+                            );
+                        ",
+                    )?
+                    .execute(params!(user_id, guild_id, ends_at.timestamp()))?;
+
+                Ok(added > 0)
+            })
+            .await
+    }
+
+    /// Returns `None` if the user had no birthday.
+    pub async fn remove(&self, user_id: u64) -> rusqlite::Result<Option<RemovedBirthday>> {
+        self.0
+            .call(move |connection| {
+                let transaction = connection.transaction()?;
+                let ongoing_roles = transaction
+                    .prepare_cached(
+                        "
+                        SELECT bday_user_list.guild_id, bday_role_list.role_id
+                        FROM bday_user_list
+                            INNER JOIN bday_role_list
+                                ON bday_user_list.guild_id = bday_role_list.guild_id
+                        WHERE bday_user_list.user_id = ?;
+                        ",
+                    )?
+                    .query_map([user_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+
+                transaction
+                    .prepare_cached(
+                        "
+                        DELETE FROM bday_user_list
+                        WHERE user_id = ?;
+                        ",
+                    )?
+                    .execute([user_id])?;
+
+                let removed = transaction
+                    .prepare_cached(
+                        "
+                        DELETE FROM bday
+                        WHERE user_id = ?;
+                        ",
+                    )?
+                    .execute([user_id])?;
+
+                transaction.commit()?;
+
+                Ok((removed > 0).then_some(RemovedBirthday { ongoing_roles }))
+            })
+            .await
+    }
+
+    /// The guild's birthday role if the user's birthday is ongoing there.
+    pub async fn ongoing_role(&self, user_id: u64, guild_id: u64) -> rusqlite::Result<Option<u64>> {
+        self.0
+            .call(move |connection| {
+                let ongoing = connection
+                    .prepare_cached(
+                        "
+                        SELECT EXISTS (
+                            SELECT 1
+                            FROM bday_user_list
+                            WHERE user_id = ? AND guild_id = ?
+                        );
+                        ",
+                    )?
+                    .query_row([user_id, guild_id], |row| row.get::<_, bool>(0))?;
+
+                if ongoing { query_role(connection, guild_id) } else { Ok(None) }
+            })
+            .await
+    }
+
+    /// Stops the user's birthday from counting in `guild_id`, ending it there if it's ongoing.
+    /// Returns the guild's birthday role if it was, since the user still has it.
+    pub async fn opt_out(&self, user_id: u64, guild_id: u64) -> rusqlite::Result<Option<u64>> {
+        self.0
+            .call(move |connection| {
+                let transaction = connection.transaction()?;
+
+                transaction
+                    .prepare_cached(
+                        "
+                        INSERT OR IGNORE INTO bday_opt_outs (user_id, guild_id)
+                            VALUES (?, ?);
+                        ",
+                    )?
+                    .execute([user_id, guild_id])?;
+
+                let ongoing = transaction
+                    .prepare_cached(
+                        "
+                        DELETE FROM bday_user_list
+                        WHERE user_id = ? AND guild_id = ?;
+                        ",
+                    )?
+                    .execute([user_id, guild_id])?;
+                let role_id = if ongoing > 0 { query_role(&transaction, guild_id)? } else { None };
+
+                transaction.commit()?;
+
+                Ok(role_id)
+            })
+            .await
+    }
+
+    /// Undoes [`Birthdays::opt_out`]. Returns whether the user had opted out of `guild_id`.
+    pub async fn opt_in(&self, user_id: u64, guild_id: u64) -> rusqlite::Result<bool> {
+        self.0
+            .call(move |connection| {
+                let removed = connection
+                    .prepare_cached(
+                        "
+                        DELETE FROM bday_opt_outs
+                        WHERE user_id = ? AND guild_id = ?;
+                        ",
+                    )?
+                    .execute([user_id, guild_id])?;
+
+                Ok(removed > 0)
+            })
+            .await
+    }
+
+    /// Records that the birthday ending at `ends_at` is being announced in `guild_id`.
+    /// Returns `false` if it already was, so that it doesn't get announced twice.
+    pub async fn claim_announcement(
+        &self, user_id: u64, guild_id: u64, ends_at: DateTime<Utc>,
+    ) -> rusqlite::Result<bool> {
+        self.0
+            .call(move |connection| {
+                let claimed = connection
+                    .prepare_cached(
+                        "
+                        INSERT OR IGNORE INTO bday_announcements (user_id, guild_id, ends_at)
+                            VALUES (?, ?, ?);
+                        ",
+                    )?
+                    .execute(params!(user_id, guild_id, ends_at.timestamp()))?;
+
+                Ok(claimed > 0)
+            })
+            .await
+    }
+
+    /// Keeps a birthday until it's confirmed, cancelled or expires.
+    pub async fn add_pending(&self, pending: PendingBirthday) -> rusqlite::Result<()> {
+        self.0
+            .call(move |connection| {
+                let birthday = pending.birthday;
+                let birth_year = pending.details.and_then(|details| details.birth_year);
+                let visibility = pending.details.map(|details| details.visibility);
+
+                connection
+                    .prepare_cached(
+                        "
+                        INSERT OR REPLACE INTO bday_confirmations (
+                            message_id, channel_id, guild_id, author_id, user_id, month, day,
+                            time_zone, is_privileged, expires_at, birth_year, visibility
+                        )
+                        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
+                        ",
+                    )?
+                    .execute(params!(
+                        pending.message_id,
+                        pending.channel_id,
+                        pending.guild_id,
+                        pending.author_id,
+                        pending.user_id,
+                        birthday.month(),
+                        birthday.day(),
+                        birthday.time_zone(),
+                        pending.is_privileged,
+                        pending.expires_at.timestamp(),
+                        birth_year,
+                        visibility
+                    ))?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    pub async fn get_pending(&self, message_id: u64) -> rusqlite::Result<Option<PendingBirthday>> {
+        self.0
+            .call(move |connection| {
+                connection
+                    .prepare_cached(
+                        "
+                        SELECT *
+                        FROM bday_confirmations
+                        WHERE message_id = ?;
+                        ",
+                    )?
+                    .query_row([message_id], read_pending_birthday)
+                    .optional()
+            })
+            .await
+    }
+
+    /// Returns whether the birthday was still pending, so that only one button press uses it.
+    pub async fn remove_pending(&self, message_id: u64) -> rusqlite::Result<bool> {
+        self.0
+            .call(move |connection| {
+                let removed = connection
+                    .prepare_cached(
+                        "
+                        DELETE FROM bday_confirmations
+                        WHERE message_id = ?;
+                        ",
+                    )?
+                    .execute([message_id])?;
+
+                Ok(removed > 0)
+            })
+            .await
+    }
+
+    /// Removes and returns the pending birthdays that expired by `date_time`.
+    pub async fn take_expired_pending(
+        &self, date_time: DateTime<Utc>,
+    ) -> rusqlite::Result<Vec<PendingBirthday>> {
+        self.0
+            .call(move |connection| {
+                let transaction = connection.transaction()?;
+                let timestamp = date_time.timestamp();
+                let expired = transaction
+                    .prepare_cached(
+                        "
+                        SELECT *
+                        FROM bday_confirmations
+                        WHERE expires_at <= ?;
+                        ",
+                    )?
+                    .query_map([timestamp], read_pending_birthday)?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+
+                transaction
+                    .prepare_cached(
+                        "
+                        DELETE FROM bday_confirmations
+                        WHERE expires_at <= ?;
+                        ",
+                    )?
+                    .execute([timestamp])?;
+
+                transaction.commit()?;
+
+                Ok(expired)
+            })
+            .await
+    }
+
+    pub async fn get_role(&self, guild_id: u64) -> rusqlite::Result<Option<u64>> {
+        self.0.call(move |connection| query_role(connection, guild_id)).await
+    }
+
+    pub async fn set_role(&self, guild_id: u64, role_id: u64) -> rusqlite::Result<()> {
+        self.0
+            .call(move |connection| {
+                connection
+                    .prepare_cached(
+                        "
+                        INSERT OR REPLACE INTO bday_role_list
+                            VALUES(?, ?);
+                        ",
+                    )?
+                    .execute([guild_id, role_id])?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Removes the guild's birthday role and ends all of its ongoing birthdays.
+    /// Returns `None` if the guild had no birthday role.
+    pub async fn remove_role(
+        &self, guild_id: u64,
+    ) -> rusqlite::Result<Option<RemovedBirthdayRole>> {
+        self.0
+            .call(move |connection| {
+                let transaction = connection.transaction()?;
+                let Some(role_id) = query_role(&transaction, guild_id)? else {
+                    return Ok(None);
+                };
+
+                let user_ids = transaction
+                    .prepare_cached(
+                        "
+                        SELECT user_id
+                        FROM bday_user_list
+                        WHERE guild_id = ?;
+                        ",
+                    )?
+                    .query_map([guild_id], |row| row.get(0))?
+                    .collect::<rusqlite::Result<Vec<u64>>>()?;
+
+                transaction
+                    .prepare_cached(
+                        "
+                        DELETE FROM bday_user_list
+                        WHERE guild_id = ?;
+                        ",
+                    )?
+                    .execute([guild_id])?;
+
+                transaction
+                    .prepare_cached(
+                        "
+                        DELETE FROM bday_role_list
+                        WHERE guild_id = ?;
+                        ",
+                    )?
+                    .execute([guild_id])?;
+
+                transaction.commit()?;
+
+                Ok(Some(RemovedBirthdayRole { role_id, user_ids }))
+            })
+            .await
+    }
+
+    /// Gets every guild's birthday role along with its ongoing birthdays, including ones that
+    /// are over but haven't been ended yet.
+    pub async fn get_ongoing_roles(&self) -> rusqlite::Result<Vec<OngoingBirthdayRole>> {
+        self.0
+            .call(move |connection| {
+                let rows = connection
+                    .prepare_cached(
+                        "
+                        SELECT
+                            bday_role_list.guild_id,
+                            bday_role_list.role_id,
+                            bday_user_list.user_id
+                        FROM bday_role_list
+                            LEFT JOIN bday_user_list
+                                ON bday_role_list.guild_id = bday_user_list.guild_id
+                        ORDER BY bday_role_list.guild_id;
+                        ",
+                    )?
+                    .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                    .collect::<rusqlite::Result<Vec<(u64, u64, Option<u64>)>>>()?;
+                let mut ongoing_roles = Vec::<OngoingBirthdayRole>::new();
+
+                for (guild_id, role_id, user_id) in rows {
+                    let ongoing_role = match ongoing_roles.last_mut() {
+                        Some(ongoing_role) if ongoing_role.guild_id == guild_id => ongoing_role,
+                        _ => {
+                            ongoing_roles.push(OngoingBirthdayRole {
+                                guild_id,
+                                role_id,
+                                user_ids: Vec::new(),
+                            });
+
+                            ongoing_roles.last_mut().unwrap()
+                        },
+                    };
+
+                    ongoing_role.user_ids.extend(user_id);
+                }
+
+                Ok(ongoing_roles)
+            })
+            .await
+    }
+
+    /// Ends the birthdays that are over and starts the ones that have begun as of `date_time`.
+    /// Guilds missing from `leap_day_policies` get the default policy. Birthdays only start
+    /// in guilds where `is_member(guild_id, user_id)` holds.
+    pub async fn update_ongoing(
+        &self, date_time: DateTime<Utc>, leap_day_policies: HashMap<u64, LeapDayPolicy>,
+        is_member: impl Fn(u64, u64) -> bool + Send + 'static,
+    ) -> rusqlite::Result<BirthdayRoleChanges> {
+        self.0
+            .call(move |connection| {
+                let transaction = connection.transaction()?;
+                let removals = get_and_delete_old_bdays(&transaction, date_time)?;
+                let additions =
+                    add_new_bdays(&transaction, date_time, &leap_day_policies, is_member)?;
+
+                transaction.commit()?;
+
+                Ok(BirthdayRoleChanges { removals, additions })
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::all::GuildId;
+
+    use super::*;
+    use crate::database::open_test_database;
+
+    #[tokio::test]
+    async fn birthday_role_removal_clears_ongoing_birthdays() {
+        let db = open_test_database();
+        let birthdays = db.birthdays();
+        let guild_id = GuildId::new(10);
+        let birthday = Birthday::new(1, 1, "UTC".parse().unwrap()).unwrap();
+
+        birthdays.set_role(guild_id.get(), 20).await.unwrap();
+        birthdays.set_role(11, 21).await.unwrap();
+        birthdays.set(1, birthday, None, false).await.unwrap();
+        birthdays.add_ongoing(1, guild_id.get(), chrono::Utc::now()).await.unwrap();
+        birthdays.add_ongoing(1, 11, chrono::Utc::now()).await.unwrap();
+
+        let removed = birthdays.remove_role(guild_id.get()).await.unwrap().unwrap();
+
+        assert_eq!(removed.role_id, 20);
+        assert_eq!(removed.user_ids, vec![1]);
+        assert_eq!(birthdays.get_role(guild_id.get()).await.unwrap(), None);
+        assert!(birthdays.remove_role(guild_id.get()).await.unwrap().is_none());
+
+        // The birthday is still ongoing in the other guild
+        let removed = birthdays.remove(1).await.unwrap().unwrap();
+
+        assert_eq!(removed.ongoing_roles, vec![(11, 21)]);
+    }
+
+    #[tokio::test]
+    async fn pending_birthdays_expire() {
+        use chrono::{Duration, TimeZone, Utc};
+
+        let db = open_test_database();
+        let birthdays = db.birthdays();
+        let now = Utc.with_ymd_and_hms(2026, 6, 15, 12, 0, 0).unwrap();
+        let pending = |message_id, expires_at| PendingBirthday {
+            message_id,
+            channel_id: 2,
+            guild_id: 3,
+            author_id: 4,
+            user_id: 5,
+            birthday: Birthday::new(10, 6, "-7".parse().unwrap()).unwrap(),
+            details: None,
+            is_privileged: true,
+            expires_at,
+        };
+
+        birthdays.add_pending(pending(1, now - Duration::seconds(1))).await.unwrap();
+        birthdays.add_pending(pending(2, now + Duration::seconds(30))).await.unwrap();
+
+        let expired = birthdays.take_expired_pending(now).await.unwrap();
+
+        assert_eq!(expired.iter().map(|pending| pending.message_id).collect::<Vec<_>>(), [1]);
+        assert!(birthdays.get_pending(1).await.unwrap().is_none());
+
+        let kept = birthdays.get_pending(2).await.unwrap().unwrap();
+
+        assert_eq!((kept.author_id, kept.user_id, kept.is_privileged), (4, 5, true));
+        assert_eq!(kept.details, None);
+        assert_eq!(kept.birthday, Birthday::new(10, 6, "-7".parse().unwrap()).unwrap());
+        assert_eq!(kept.expires_at, now + Duration::seconds(30));
+        assert!(birthdays.remove_pending(2).await.unwrap());
+        assert!(!birthdays.remove_pending(2).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn replaced_birthdays_keep_their_details_unless_given() {
+        let db = open_test_database();
+        let birthdays = db.birthdays();
+        let birthday = Birthday::new(10, 6, "-7".parse().unwrap()).unwrap();
+        let moved = Birthday::new(10, 7, "-7".parse().unwrap()).unwrap();
+        let details =
+            BirthdayDetails { birth_year: Some(2000), visibility: BirthdayVisibility::Staff };
+
+        assert!(birthdays.set(1, birthday, Some(details), false).await.unwrap());
+        assert!(!birthdays.set(1, moved, None, false).await.unwrap());
+        assert!(birthdays.set(1, moved, None, true).await.unwrap());
+        assert_eq!(birthdays.get(1).await.unwrap(), Some((moved, details)));
+
+        assert!(birthdays.set_visibility(1, BirthdayVisibility::Hidden).await.unwrap());
+        assert!(!birthdays.set_visibility(2, BirthdayVisibility::Hidden).await.unwrap());
+
+        birthdays.set(1, birthday, Some(BirthdayDetails::default()), true).await.unwrap();
+
+        assert_eq!(birthdays.get(1).await.unwrap(), Some((birthday, BirthdayDetails::default())));
+    }
+}
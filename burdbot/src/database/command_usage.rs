@@ -0,0 +1,154 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::params;
+
+use super::Database;
+
+/// One use of a prefix or slash command.
+pub struct NewCommandUsage {
+    pub command: String,
+    pub guild_id: Option<u64>,
+    pub channel_id: u64,
+    pub user_id: u64,
+    /// `None` if the command succeeded.
+    pub error_kind: Option<&'static str>,
+    pub duration_ms: u64,
+    pub used_at: DateTime<Utc>,
+}
+
+pub struct CommandStats {
+    pub command: String,
+    pub uses: u32,
+    pub errors: u32,
+}
+
+/// Every command use, kept to see which features get used.
+pub struct CommandUsage<'a>(pub(super) &'a Database);
+
+impl CommandUsage<'_> {
+    pub async fn record(&self, usage: NewCommandUsage) -> rusqlite::Result<()> {
+        self.0
+            .call(move |connection| {
+                let mut statement = connection.prepare_cached(
+                    "
+                    INSERT INTO command_usage
+                        (command, guild_id, channel_id, user_id, error_kind, duration_ms, used_at)
+                        VALUES (?, ?, ?, ?, ?, ?, ?);
+                    ",
+                )?;
+
+                statement.execute(params![
+                    usage.command,
+                    usage.guild_id,
+                    usage.channel_id,
+                    usage.user_id,
+                    usage.error_kind,
+                    usage.duration_ms,
+                    usage.used_at.timestamp()
+                ])?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// The uses and errors of every command used in `guild_id` since `since`, most used first.
+    pub async fn command_stats(
+        &self, guild_id: u64, since: DateTime<Utc>,
+    ) -> rusqlite::Result<Vec<CommandStats>> {
+        self.0
+            .call(move |connection| {
+                let mut statement = connection.prepare_cached(
+                    "
+                    SELECT command, COUNT(*) AS uses, COUNT(error_kind) AS errors
+                    FROM command_usage
+                    WHERE guild_id = ? AND used_at >= ?
+                    GROUP BY command
+                    ORDER BY uses DESC, command;
+                    ",
+                )?;
+
+                let rows = statement.query_map(params![guild_id, since.timestamp()], |row| {
+                    Ok(CommandStats {
+                        command: row.get("command")?,
+                        uses: row.get("uses")?,
+                        errors: row.get("errors")?,
+                    })
+                })?;
+
+                rows.collect()
+            })
+            .await
+    }
+
+    /// How many commands were used in `guild_id` on each UTC day since `since`.
+    /// Days without any uses are left out.
+    pub async fn daily_uses(
+        &self, guild_id: u64, since: DateTime<Utc>,
+    ) -> rusqlite::Result<Vec<(NaiveDate, u32)>> {
+        self.0
+            .call(move |connection| {
+                let mut statement = connection.prepare_cached(
+                    "
+                    SELECT date(used_at, 'unixepoch') AS day, COUNT(*) AS uses
+                    FROM command_usage
+                    WHERE guild_id = ? AND used_at >= ?
+                    GROUP BY day
+                    ORDER BY day;
+                    ",
+                )?;
+
+                let rows = statement.query_map(params![guild_id, since.timestamp()], |row| {
+                    Ok((row.get("day")?, row.get("uses")?))
+                })?;
+
+                rows.collect()
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::open_test_database;
+
+    #[tokio::test]
+    async fn command_usage_is_aggregated_per_guild() {
+        use chrono::{NaiveDate, TimeZone, Utc};
+
+        let db = open_test_database();
+        let usage = |command: &str, guild_id, error_kind, day| NewCommandUsage {
+            command: command.to_owned(),
+            guild_id: Some(guild_id),
+            channel_id: 2,
+            user_id: 3,
+            error_kind,
+            duration_ms: 10,
+            used_at: Utc.with_ymd_and_hms(2026, 5, day, 12, 0, 0).unwrap(),
+        };
+
+        for usage in [
+            usage("pronounce", 1, None, 1),
+            usage("pronounce", 1, Some("discord"), 2),
+            usage("stafflog", 1, None, 2),
+            usage("stafflog", 1, None, 10),
+            usage("pronounce", 5, None, 2),
+        ] {
+            db.command_usage().record(usage).await.unwrap();
+        }
+
+        let since = Utc.with_ymd_and_hms(2026, 5, 2, 0, 0, 0).unwrap();
+        let stats = db.command_usage().command_stats(1, since).await.unwrap();
+        let stats = stats
+            .iter()
+            .map(|stats| (stats.command.as_str(), stats.uses, stats.errors))
+            .collect::<Vec<_>>();
+
+        assert_eq!(stats, vec![("stafflog", 2, 0), ("pronounce", 1, 1)]);
+
+        let days = db.command_usage().daily_uses(1, since).await.unwrap();
+        let may = |day| NaiveDate::from_ymd_opt(2026, 5, day).unwrap();
+
+        assert_eq!(days, vec![(may(2), 2), (may(10), 1)]);
+    }
+}
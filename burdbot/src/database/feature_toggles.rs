@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+
+use super::Database;
+
+/// Per-guild features that can be turned on and off.
+pub struct FeatureToggles<'a>(pub(super) &'a Database);
+
+impl FeatureToggles<'_> {
+    pub async fn vocaroo_servers(&self) -> rusqlite::Result<HashSet<u64>> {
+        self.0
+            .call(|connection| {
+                let mut statement = connection.prepare_cached(
+                    "
+                    SELECT guild_id FROM vocaroo_enabled;
+                    ",
+                )?;
+
+                statement.query_map([], |row| row.get::<_, u64>(0))?.collect()
+            })
+            .await
+    }
+
+    /// Returns whether it was previously disabled.
+    pub async fn enable_vocaroo(&self, guild_id: u64) -> rusqlite::Result<bool> {
+        self.0
+            .call(move |connection| {
+                let mut statement = connection.prepare_cached(
+                    "
+                    INSERT OR IGNORE INTO vocaroo_enabled
+                        VALUES (?);
+                    ",
+                )?;
+
+                Ok(statement.execute([guild_id])? > 0)
+            })
+            .await
+    }
+
+    /// Returns whether it was previously enabled.
+    pub async fn disable_vocaroo(&self, guild_id: u64) -> rusqlite::Result<bool> {
+        self.0
+            .call(move |connection| {
+                let mut statement = connection.prepare_cached(
+                    "
+                    DELETE FROM vocaroo_enabled
+                    WHERE guild_id = ?;
+                    ",
+                )?;
+
+                Ok(statement.execute([guild_id])? > 0)
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::open_test_database;
+
+    #[tokio::test]
+    async fn feature_toggles_round_trip() {
+        let db = open_test_database();
+
+        assert!(db.feature_toggles().enable_vocaroo(1).await.unwrap());
+        assert!(!db.feature_toggles().enable_vocaroo(1).await.unwrap());
+        assert!(db.feature_toggles().enable_vocaroo(2).await.unwrap());
+        assert!(db.feature_toggles().disable_vocaroo(2).await.unwrap());
+        assert!(!db.feature_toggles().disable_vocaroo(2).await.unwrap());
+
+        let servers = db.feature_toggles().vocaroo_servers().await.unwrap();
+
+        assert_eq!(servers.into_iter().collect::<Vec<_>>(), vec![1]);
+    }
+}
@@ -0,0 +1,59 @@
+use rusqlite::params;
+
+use super::Database;
+
+/// Raw guild config rows. Parsing the values is left to [`crate::guild_config`].
+pub struct GuildConfigs<'a>(pub(super) &'a Database);
+
+impl GuildConfigs<'_> {
+    /// Gets every `(guild_id, key, value)` row.
+    pub async fn get_all(&self) -> rusqlite::Result<Vec<(u64, String, String)>> {
+        self.0
+            .call(|connection| {
+                let mut statement = connection.prepare_cached(
+                    "
+                    SELECT guild_id, key, value FROM guild_config;
+                    ",
+                )?;
+
+                statement
+                    .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                    .collect()
+            })
+            .await
+    }
+
+    pub async fn set(&self, guild_id: u64, key: String, value: String) -> rusqlite::Result<()> {
+        self.0
+            .call(move |connection| {
+                let mut statement = connection.prepare_cached(
+                    "
+                    INSERT OR REPLACE INTO guild_config
+                        VALUES (?, ?, ?);
+                    ",
+                )?;
+
+                statement.execute(params![guild_id, key, value])?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    pub async fn unset(&self, guild_id: u64, key: String) -> rusqlite::Result<()> {
+        self.0
+            .call(move |connection| {
+                let mut statement = connection.prepare_cached(
+                    "
+                    DELETE FROM guild_config
+                    WHERE guild_id = ? AND key = ?;
+                    ",
+                )?;
+
+                statement.execute(params![guild_id, key])?;
+
+                Ok(())
+            })
+            .await
+    }
+}
@@ -0,0 +1,688 @@
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use regex::Regex;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
+use rusqlite::{OptionalExtension, Row, ToSql, Transaction, params};
+use serenity::model::id::MessageId;
+use strum_macros::{Display, EnumIter, EnumString};
+
+use super::Database;
+
+fn get_message_id_from_link(link: &str) -> u64 {
+    lazy_static! {
+        static ref MESSAGE_ID_REGEX: Regex = Regex::new(r"\d+/*$").expect("Bad message ID regex.");
+    }
+
+    let mat = MESSAGE_ID_REGEX.find(link).expect("MESSAGE_ID_REGEX couldn't match link.");
+
+    let mut unsanitized_message_id = mat.as_str();
+
+    if let Some(slash_pos) = unsanitized_message_id.find('/') {
+        unsanitized_message_id = &unsanitized_message_id[0..slash_pos];
+    }
+
+    unsanitized_message_id.parse().expect(
+        "Message ID could not be parsed in link. \
+    This should never happen.",
+    )
+}
+
+/// What kind of action a staff log entry is about.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Display, EnumString, EnumIter)]
+#[strum(serialize_all = "kebab-case", ascii_case_insensitive)]
+pub enum StaffLogCategory {
+    Note,
+    Warning,
+    Mute,
+    Kick,
+    Ban,
+    ChannelBan,
+}
+
+impl StaffLogCategory {
+    pub fn name(self) -> &'static str {
+        match self {
+            StaffLogCategory::Note => "Note",
+            StaffLogCategory::Warning => "Warning",
+            StaffLogCategory::Mute => "Mute",
+            StaffLogCategory::Kick => "Kick",
+            StaffLogCategory::Ban => "Ban",
+            StaffLogCategory::ChannelBan => "Channel ban",
+        }
+    }
+
+    pub fn badge(self) -> &'static str {
+        match self {
+            StaffLogCategory::Note => "📝",
+            StaffLogCategory::Warning => "⚠️",
+            StaffLogCategory::Mute => "🔇",
+            StaffLogCategory::Kick => "👢",
+            StaffLogCategory::Ban => "🔨",
+            StaffLogCategory::ChannelBan => "🚫",
+        }
+    }
+
+    /// The severity of entries that aren't given one.
+    pub fn default_severity(self) -> StaffLogSeverity {
+        match self {
+            StaffLogCategory::Note => StaffLogSeverity::Low,
+            StaffLogCategory::Warning | StaffLogCategory::Mute | StaffLogCategory::ChannelBan => {
+                StaffLogSeverity::Medium
+            },
+            StaffLogCategory::Kick | StaffLogCategory::Ban => StaffLogSeverity::High,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Display, EnumString, EnumIter)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum StaffLogSeverity {
+    Low,
+    Medium,
+    High,
+}
+
+impl ToSql for StaffLogCategory {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_string()))
+    }
+}
+
+impl FromSql for StaffLogCategory {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str()?.parse().map_err(|error| FromSqlError::Other(Box::new(error)))
+    }
+}
+
+impl ToSql for StaffLogSeverity {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_string()))
+    }
+}
+
+impl FromSql for StaffLogSeverity {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str()?.parse().map_err(|error| FromSqlError::Other(Box::new(error)))
+    }
+}
+
+/// Who removed a staff log entry and when. Removed entries are kept so that their numbers
+/// aren't reused and they can be restored.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StaffLogDeletion {
+    pub deleted_by: u64,
+    pub deleted_at: DateTime<Utc>,
+}
+
+pub struct StaffLog {
+    pub entry_id: i64,
+    pub original_link: String,
+    pub last_edited_link: Option<String>,
+    pub reason: String,
+    pub category: StaffLogCategory,
+    pub severity: StaffLogSeverity,
+    /// `None` unless the entry was removed.
+    pub deletion: Option<StaffLogDeletion>,
+}
+
+impl StaffLog {
+    pub fn get_original_time(&self) -> i64 {
+        let message_id = get_message_id_from_link(self.original_link.as_str());
+
+        MessageId::from(message_id).created_at().timestamp()
+    }
+
+    pub fn get_edited_time(&self) -> Option<i64> {
+        self.last_edited_link.as_ref().map(|last_edited_link| {
+            let message_id = get_message_id_from_link(last_edited_link.as_str());
+
+            MessageId::from(message_id).created_at().timestamp()
+        })
+    }
+}
+
+fn read_timestamp(row: &Row, column: &str) -> rusqlite::Result<Option<DateTime<Utc>>> {
+    let Some(timestamp) = row.get::<_, Option<i64>>(column)? else {
+        return Ok(None);
+    };
+    let index = row.as_ref().column_index(column)?;
+
+    DateTime::from_timestamp(timestamp, 0)
+        .map(Some)
+        .ok_or(rusqlite::Error::IntegralValueOutOfRange(index, timestamp))
+}
+
+fn read_staff_log(row: &Row) -> rusqlite::Result<StaffLog> {
+    let deleted_by = row.get::<_, Option<u64>>("deleted_by")?;
+    let deleted_at = read_timestamp(row, "deleted_at")?;
+
+    Ok(StaffLog {
+        entry_id: row.get("entry_id")?,
+        original_link: row.get("original_link")?,
+        last_edited_link: row.get("last_edited_link")?,
+        reason: row.get("reason")?,
+        category: row.get("category")?,
+        severity: row.get("severity")?,
+        deletion: deleted_by
+            .zip(deleted_at)
+            .map(|(deleted_by, deleted_at)| StaffLogDeletion { deleted_by, deleted_at }),
+    })
+}
+
+/// One version of a staff log entry's reason.
+pub struct StaffLogRevision {
+    /// `None` for entries logged before revisions were kept, whose author is unknown.
+    pub author_id: Option<u64>,
+    /// The message that the entry was logged or edited with.
+    pub link: String,
+    pub reason: String,
+    pub revised_at: DateTime<Utc>,
+}
+
+/// Revisions are only ever added, so that entries keep their whole history.
+fn add_revision(
+    transaction: &Transaction, log_id: i64, revision: &StaffLogRevision,
+) -> rusqlite::Result<()> {
+    transaction
+        .prepare_cached(
+            "
+            INSERT INTO staff_log_revisions (log_id, author_id, link, reason, revised_at)
+                VALUES (?, ?, ?, ?, ?);
+            ",
+        )?
+        .execute(params![
+            log_id,
+            revision.author_id,
+            revision.link,
+            revision.reason,
+            revision.revised_at.timestamp()
+        ])?;
+
+    Ok(())
+}
+
+/// Filters for searching every staff log entry in a guild. Removed entries are never found, and
+/// filters that aren't set match every entry.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StaffLogSearch {
+    /// Words that the reason has to have, in any order. Words that only start with them count.
+    pub keywords: Option<String>,
+    /// Who logged the entry. Entries from before revisions were kept have no known author.
+    pub author_id: Option<u64>,
+    pub category: Option<StaffLogCategory>,
+    /// Only entries logged at or after this.
+    pub logged_after: Option<DateTime<Utc>>,
+    /// Only entries logged before this.
+    pub logged_before: Option<DateTime<Utc>>,
+    /// A user whose entries are never found, like the one searching.
+    pub excluded_user_id: Option<u64>,
+}
+
+/// A page of search results, newest first.
+pub struct StaffLogSearchPage {
+    /// Each entry along with the user it's about.
+    pub logs: Vec<(u64, StaffLog)>,
+    /// How many entries matched across every page.
+    pub total: usize,
+}
+
+/// Quotes every word so that FTS5 syntax in them is searched for literally.
+fn full_text_query(keywords: &str) -> Option<String> {
+    let words = keywords
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>();
+
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+/// Shared by the queries counting and getting search results. The first parameter is the guild,
+/// followed by the filters in the order of `StaffLogSearch`'s fields.
+const SEARCH_CONDITIONS: &str = "
+    FROM staff_logs
+    WHERE guild_id = ?1 AND deleted_at IS NULL
+        AND (?2 IS NULL OR log_id IN
+        (
+            SELECT rowid
+            FROM staff_log_search
+            WHERE staff_log_search MATCH ?2
+        ))
+        AND (?3 IS NULL OR ?3 =
+        (
+            SELECT author_id
+            FROM staff_log_revisions
+            WHERE log_id = staff_logs.log_id
+            ORDER BY revision_id
+            LIMIT 1
+        ))
+        AND (?4 IS NULL OR category = ?4)
+        AND (?5 IS NULL OR logged_at >= ?5)
+        AND (?6 IS NULL OR logged_at < ?6)
+        AND (?7 IS NULL OR user_id != ?7)
+";
+
+/// Staff logs, which belong to the guild they were written in and are numbered per user
+/// there starting from 1. Removing an entry only hides it, so entry numbers never change or
+/// get reused.
+pub struct StaffLogs<'a>(pub(super) &'a Database);
+
+impl StaffLogs<'_> {
+    /// The user's entries, along with the removed ones if `include_deleted` is set.
+    pub async fn get(
+        &self, guild_id: u64, user_id: u64, include_deleted: bool,
+    ) -> rusqlite::Result<Vec<StaffLog>> {
+        self.0
+            .call(move |connection| {
+                connection
+                    .prepare_cached(
+                        "
+                        SELECT entry_id, original_link, last_edited_link, reason, category,
+                            severity, deleted_by, deleted_at
+                        FROM staff_logs
+                        WHERE guild_id = ? AND user_id = ? AND (? OR deleted_at IS NULL)
+                        ORDER BY entry_id;
+                        ",
+                    )?
+                    .query_map(params![guild_id, user_id, include_deleted], read_staff_log)?
+                    .collect()
+            })
+            .await
+    }
+
+    /// Adds an entry after every other one of the user's, removed or not, with `revision` as its
+    /// first version. Its link leads to the message that the log was made with. Returns the
+    /// new entry's number.
+    pub async fn add(
+        &self, guild_id: u64, user_id: u64, category: StaffLogCategory, severity: StaffLogSeverity,
+        revision: StaffLogRevision,
+    ) -> rusqlite::Result<i64> {
+        self.0
+            .call(move |connection| {
+                let transaction = connection.transaction()?;
+                let (log_id, entry_id) = transaction
+                    .prepare_cached(
+                        "
+                        INSERT INTO staff_logs (
+                            guild_id, user_id, entry_id, original_link, reason, category, severity,
+                            logged_at
+                        )
+                            SELECT ?1, ?2, COALESCE(MAX(entry_id), 0) + 1, ?3, ?4, ?5, ?6, ?7
+                            FROM staff_logs
+                            WHERE guild_id = ?1 AND user_id = ?2
+                            RETURNING log_id, entry_id;
+                        ",
+                    )?
+                    .query_row(
+                        params![
+                            guild_id,
+                            user_id,
+                            revision.link,
+                            revision.reason,
+                            category,
+                            severity,
+                            revision.revised_at.timestamp()
+                        ],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )?;
+
+                add_revision(&transaction, log_id, &revision)?;
+                transaction.commit()?;
+
+                Ok(entry_id)
+            })
+            .await
+    }
+
+    /// Changes an entry's reason, keeping the old one in its history. Returns false if the entry
+    /// doesn't exist or was removed.
+    pub async fn edit(
+        &self, guild_id: u64, user_id: u64, entry_id: i64, revision: StaffLogRevision,
+    ) -> rusqlite::Result<bool> {
+        self.0
+            .call(move |connection| {
+                let transaction = connection.transaction()?;
+                let log_id = transaction
+                    .prepare_cached(
+                        "
+                        UPDATE staff_logs
+                            SET(last_edited_link, reason) = (?, ?)
+                            WHERE guild_id = ? AND user_id = ? AND entry_id = ?
+                                AND deleted_at IS NULL
+                            RETURNING log_id;
+                        ",
+                    )?
+                    .query_row(
+                        params![revision.link, revision.reason, guild_id, user_id, entry_id],
+                        |row| row.get(0),
+                    )
+                    .optional()?;
+
+                let Some(log_id) = log_id else {
+                    return Ok(false);
+                };
+
+                add_revision(&transaction, log_id, &revision)?;
+                transaction.commit()?;
+
+                Ok(true)
+            })
+            .await
+    }
+
+    /// Every version of an entry, oldest first. Empty if the entry doesn't exist.
+    pub async fn history(
+        &self, guild_id: u64, user_id: u64, entry_id: i64,
+    ) -> rusqlite::Result<Vec<StaffLogRevision>> {
+        self.0
+            .call(move |connection| {
+                connection
+                    .prepare_cached(
+                        "
+                        SELECT author_id, link, reason, revised_at
+                        FROM staff_log_revisions
+                        WHERE log_id =
+                        (
+                            SELECT log_id
+                            FROM staff_logs
+                            WHERE guild_id = ? AND user_id = ? AND entry_id = ?
+                        )
+                        ORDER BY revision_id;
+                        ",
+                    )?
+                    .query_map(params![guild_id, user_id, entry_id], |row| {
+                        let revised_at = row.get("revised_at")?;
+                        let revised_at_index = row.as_ref().column_index("revised_at")?;
+
+                        Ok(StaffLogRevision {
+                            author_id: row.get("author_id")?,
+                            link: row.get("link")?,
+                            reason: row.get("reason")?,
+                            revised_at: DateTime::from_timestamp(revised_at, 0).ok_or(
+                                rusqlite::Error::IntegralValueOutOfRange(
+                                    revised_at_index, revised_at,
+                                ),
+                            )?,
+                        })
+                    })?
+                    .collect()
+            })
+            .await
+    }
+
+    /// Hides an entry, keeping who removed it and when. Returns false if the entry doesn't exist
+    /// or was already removed.
+    pub async fn remove(
+        &self, guild_id: u64, user_id: u64, entry_id: i64, deletion: StaffLogDeletion,
+    ) -> rusqlite::Result<bool> {
+        self.0
+            .call(move |connection| {
+                let rows_changed = connection
+                    .prepare_cached(
+                        "
+                        UPDATE staff_logs
+                            SET (deleted_by, deleted_at) = (?, ?)
+                            WHERE guild_id = ? AND user_id = ? AND entry_id = ?
+                                AND deleted_at IS NULL;
+                        ",
+                    )?
+                    .execute(params![
+                        deletion.deleted_by,
+                        deletion.deleted_at.timestamp(),
+                        guild_id,
+                        user_id,
+                        entry_id
+                    ])?;
+
+                Ok(rows_changed != 0)
+            })
+            .await
+    }
+
+    /// Brings back a removed entry. Returns false if the entry doesn't exist or wasn't removed.
+    pub async fn restore(
+        &self, guild_id: u64, user_id: u64, entry_id: i64,
+    ) -> rusqlite::Result<bool> {
+        self.0
+            .call(move |connection| {
+                let rows_changed = connection
+                    .prepare_cached(
+                        "
+                        UPDATE staff_logs
+                            SET (deleted_by, deleted_at) = (NULL, NULL)
+                            WHERE guild_id = ? AND user_id = ? AND entry_id = ?
+                                AND deleted_at IS NOT NULL;
+                        ",
+                    )?
+                    .execute(params![guild_id, user_id, entry_id])?;
+
+                Ok(rows_changed != 0)
+            })
+            .await
+    }
+
+    /// Finds the guild's entries that match every filter in `search`, skipping `offset` of them
+    /// and returning at most `limit`.
+    pub async fn search(
+        &self, guild_id: u64, search: StaffLogSearch, limit: usize, offset: usize,
+    ) -> rusqlite::Result<StaffLogSearchPage> {
+        self.0
+            .call(move |connection| {
+                let query = search.keywords.as_deref().and_then(full_text_query);
+                let logged_after = search.logged_after.map(|logged_after| logged_after.timestamp());
+                let logged_before =
+                    search.logged_before.map(|logged_before| logged_before.timestamp());
+                let filters = params![
+                    guild_id, query, search.author_id, search.category, logged_after,
+                    logged_before, search.excluded_user_id
+                ];
+                let mut page_params = filters.to_vec();
+
+                page_params.extend(params![limit, offset]);
+
+                let total = connection
+                    .prepare_cached(&format!("SELECT COUNT(*) {SEARCH_CONDITIONS};"))?
+                    .query_row(filters, |row| row.get(0))?;
+                let logs = connection
+                    .prepare_cached(&format!(
+                        "
+                        SELECT user_id, entry_id, original_link, last_edited_link, reason,
+                            category, severity, deleted_by, deleted_at
+                        {SEARCH_CONDITIONS}
+                        ORDER BY logged_at DESC, log_id DESC
+                        LIMIT ?8 OFFSET ?9;
+                        "
+                    ))?
+                    .query_map(page_params.as_slice(), |row| {
+                        Ok((row.get("user_id")?, read_staff_log(row)?))
+                    })?
+                    .collect::<rusqlite::Result<_>>()?;
+
+                Ok(StaffLogSearchPage { logs, total })
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::open_test_database;
+
+    fn staff_log_revision(link: String, reason: &str) -> StaffLogRevision {
+        StaffLogRevision {
+            author_id: Some(9),
+            link,
+            reason: reason.to_owned(),
+            revised_at: chrono::Utc::now(),
+        }
+    }
+
+    /// Adds a note about user 5.
+    async fn add_note(
+        staff_logs: &StaffLogs<'_>, guild_id: u64, revision: StaffLogRevision,
+    ) -> i64 {
+        let (category, severity) = (StaffLogCategory::Note, StaffLogSeverity::Low);
+
+        staff_logs.add(guild_id, 5, category, severity, revision).await.unwrap()
+    }
+
+    fn deletion() -> StaffLogDeletion {
+        StaffLogDeletion { deleted_by: 9, deleted_at: chrono::Utc::now() }
+    }
+
+    #[tokio::test]
+    async fn removed_staff_logs_keep_their_numbers() {
+        let db = open_test_database();
+        let staff_logs = db.staff_logs();
+        let link = "https://discord.com/channels/1/2/3".to_owned();
+
+        for reason in ["First", "Second", "Third"] {
+            add_note(&staff_logs, 1, staff_log_revision(link.clone(), reason)).await;
+        }
+
+        assert!(staff_logs.remove(1, 5, 3, deletion()).await.unwrap());
+        assert!(!staff_logs.remove(1, 5, 3, deletion()).await.unwrap());
+        assert!(staff_logs.remove(1, 5, 2, deletion()).await.unwrap());
+        assert!(
+            !staff_logs.edit(1, 5, 2, staff_log_revision(link.clone(), "Edited")).await.unwrap()
+        );
+
+        // Numbers of removed entries aren't reused.
+        assert_eq!(add_note(&staff_logs, 1, staff_log_revision(link, "Fourth")).await, 4);
+
+        let entries = |logs: Vec<StaffLog>| {
+            logs.into_iter().map(|log| (log.entry_id, log.deletion.is_some())).collect::<Vec<_>>()
+        };
+
+        assert_eq!(entries(staff_logs.get(1, 5, false).await.unwrap()), [(1, false), (4, false)]);
+        assert_eq!(
+            entries(staff_logs.get(1, 5, true).await.unwrap()),
+            [(1, false), (2, true), (3, true), (4, false)]
+        );
+
+        assert!(staff_logs.restore(1, 5, 2).await.unwrap());
+        assert!(!staff_logs.restore(1, 5, 2).await.unwrap());
+        assert!(!staff_logs.restore(1, 5, 1).await.unwrap());
+        assert_eq!(
+            entries(staff_logs.get(1, 5, false).await.unwrap()),
+            [(1, false), (2, false), (4, false)]
+        );
+    }
+
+    #[tokio::test]
+    async fn staff_logs_are_scoped_to_guilds() {
+        let db = open_test_database();
+        let staff_logs = db.staff_logs();
+        let link = |guild_id| format!("https://discord.com/channels/{guild_id}/2/3");
+
+        let (category, severity) = (StaffLogCategory::Mute, StaffLogSeverity::High);
+
+        staff_logs
+            .add(1, 5, category, severity, staff_log_revision(link(1), "Spam"))
+            .await
+            .unwrap();
+        add_note(&staff_logs, 2, staff_log_revision(link(2), "Elsewhere")).await;
+
+        assert!(!staff_logs.edit(3, 5, 1, staff_log_revision(link(3), "Edited")).await.unwrap());
+        assert!(!staff_logs.remove(3, 5, 1, deletion()).await.unwrap());
+        assert!(staff_logs.remove(2, 5, 1, deletion()).await.unwrap());
+
+        let logs = staff_logs.get(1, 5, true).await.unwrap();
+
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].reason, "Spam");
+        assert_eq!((logs[0].category, logs[0].severity), (category, severity));
+        assert!(staff_logs.get(2, 5, false).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn staff_log_edits_keep_every_revision() {
+        let db = open_test_database();
+        let staff_logs = db.staff_logs();
+        let link = |message_id| format!("https://discord.com/channels/1/2/{message_id}");
+
+        add_note(&staff_logs, 1, staff_log_revision(link(3), "First")).await;
+        staff_logs.edit(1, 5, 1, staff_log_revision(link(4), "Second")).await.unwrap();
+        staff_logs.edit(1, 5, 1, staff_log_revision(link(5), "Third")).await.unwrap();
+
+        let logs = staff_logs.get(1, 5, false).await.unwrap();
+        let history = staff_logs.history(1, 5, 1).await.unwrap();
+        let reasons = history.iter().map(|revision| revision.reason.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(logs[0].reason, "Third");
+        assert_eq!(logs[0].last_edited_link.as_deref(), Some(link(5).as_str()));
+        assert_eq!(reasons, vec!["First", "Second", "Third"]);
+        assert_eq!(history[1].link, link(4));
+        assert!(staff_logs.history(1, 5, 2).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn staff_logs_can_be_searched_across_a_guild() {
+        use chrono::{TimeZone, Utc};
+
+        let db = open_test_database();
+        let staff_logs = &db.staff_logs();
+        let logged_on = |day| Utc.with_ymd_and_hms(2026, 9, day, 12, 0, 0).unwrap();
+        let revision = |day, reason: &str| StaffLogRevision {
+            revised_at: logged_on(day),
+            ..staff_log_revision("https://discord.com/channels/1/2/3".to_owned(), reason)
+        };
+        let (warning, note) = (StaffLogCategory::Warning, StaffLogCategory::Note);
+
+        for (guild_id, user_id, category, day, reason) in [
+            (1, 5, warning, 1, "Spam in general"),
+            (1, 6, warning, 10, "Spamming memes"),
+            (1, 7, note, 20, "Spam links"),
+            (2, 5, warning, 10, "Spam elsewhere"),
+        ] {
+            let severity = category.default_severity();
+
+            staff_logs
+                .add(guild_id, user_id, category, severity, revision(day, reason))
+                .await
+                .unwrap();
+        }
+
+        // Edited reasons are indexed again.
+        staff_logs.edit(1, 6, 1, revision(11, "Being rude")).await.unwrap();
+
+        let search = |search: StaffLogSearch, limit, offset| async move {
+            let page = staff_logs.search(1, search, limit, offset).await.unwrap();
+            let users = page.logs.iter().map(|(user_id, _)| *user_id).collect::<Vec<_>>();
+
+            (users, page.total)
+        };
+        let spam = StaffLogSearch { keywords: Some("SPAM".to_owned()), ..Default::default() };
+
+        assert_eq!(search(spam.clone(), 10, 0).await, (vec![7, 5], 2));
+        assert_eq!(search(spam.clone(), 1, 1).await, (vec![5], 2));
+        assert_eq!(
+            search(
+                StaffLogSearch { keywords: Some("rude".to_owned()), ..Default::default() },
+                10,
+                0
+            )
+            .await,
+            (vec![6], 1)
+        );
+        assert_eq!(
+            search(StaffLogSearch { category: Some(warning), ..spam.clone() }, 10, 0).await,
+            (vec![5], 1)
+        );
+        assert_eq!(
+            search(StaffLogSearch { logged_after: Some(logged_on(2)), ..spam.clone() }, 10, 0)
+                .await,
+            (vec![7], 1)
+        );
+        assert_eq!(
+            search(StaffLogSearch { logged_before: Some(logged_on(2)), ..spam.clone() }, 10, 0)
+                .await,
+            (vec![5], 1)
+        );
+        assert_eq!(
+            search(StaffLogSearch { excluded_user_id: Some(7), ..spam.clone() }, 10, 0).await,
+            (vec![5], 1)
+        );
+        assert_eq!(search(StaffLogSearch { author_id: Some(8), ..spam }, 10, 0).await, (vec![], 0));
+    }
+}
@@ -0,0 +1,81 @@
+//! The Discord operations that command logic uses.
+//!
+//! Commands go through [`DiscordHttp`] instead of calling serenity's [`Http`] directly,
+//! so whole command flows can be tested against a fake without talking to Discord.
+
+#[cfg(test)]
+pub(crate) mod recording;
+
+use serenity::all::{
+    ChannelId, CreateMessage, EditMember, GuildId, Member, Message, MessageId, RoleId, Timestamp,
+    UserId,
+};
+use serenity::async_trait;
+use serenity::http::Http;
+
+#[async_trait]
+pub trait DiscordHttp: Send + Sync {
+    async fn send_message(
+        &self, channel_id: ChannelId, message: CreateMessage,
+    ) -> serenity::Result<Message>;
+
+    async fn add_member_role(
+        &self, guild_id: GuildId, user_id: UserId, role_id: RoleId, reason: Option<&str>,
+    ) -> serenity::Result<()>;
+
+    async fn remove_member_role(
+        &self, guild_id: GuildId, user_id: UserId, role_id: RoleId, reason: Option<&str>,
+    ) -> serenity::Result<()>;
+
+    async fn get_member(&self, guild_id: GuildId, user_id: UserId) -> serenity::Result<Member>;
+
+    /// Stops a member from communicating in the guild until `until`.
+    async fn time_out_member(
+        &self, guild_id: GuildId, user_id: UserId, until: Timestamp,
+    ) -> serenity::Result<()>;
+
+    async fn delete_message(
+        &self, channel_id: ChannelId, message_id: MessageId,
+    ) -> serenity::Result<()>;
+}
+
+#[async_trait]
+impl DiscordHttp for Http {
+    async fn send_message(
+        &self, channel_id: ChannelId, message: CreateMessage,
+    ) -> serenity::Result<Message> {
+        channel_id.send_message(self, message).await
+    }
+
+    async fn add_member_role(
+        &self, guild_id: GuildId, user_id: UserId, role_id: RoleId, reason: Option<&str>,
+    ) -> serenity::Result<()> {
+        Http::add_member_role(self, guild_id, user_id, role_id, reason).await
+    }
+
+    async fn remove_member_role(
+        &self, guild_id: GuildId, user_id: UserId, role_id: RoleId, reason: Option<&str>,
+    ) -> serenity::Result<()> {
+        Http::remove_member_role(self, guild_id, user_id, role_id, reason).await
+    }
+
+    async fn get_member(&self, guild_id: GuildId, user_id: UserId) -> serenity::Result<Member> {
+        Http::get_member(self, guild_id, user_id).await
+    }
+
+    async fn time_out_member(
+        &self, guild_id: GuildId, user_id: UserId, until: Timestamp,
+    ) -> serenity::Result<()> {
+        let builder = EditMember::new().disable_communication_until_datetime(until);
+
+        guild_id.edit_member(self, user_id, builder).await?;
+
+        Ok(())
+    }
+
+    async fn delete_message(
+        &self, channel_id: ChannelId, message_id: MessageId,
+    ) -> serenity::Result<()> {
+        Http::delete_message(self, channel_id, message_id, None).await
+    }
+}
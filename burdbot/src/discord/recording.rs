@@ -0,0 +1,168 @@
+//! A fake [`DiscordHttp`] that records every call made to it.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serenity::all::{
+    ChannelId, CreateMessage, GuildId, Member, Message, MessageId, RoleId, Timestamp, UserId,
+};
+use serenity::async_trait;
+
+use super::DiscordHttp;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiscordCall {
+    /// The message is kept as the JSON that would have been sent to Discord.
+    SendMessage {
+        channel_id: ChannelId,
+        message: serde_json::Value,
+    },
+    AddMemberRole {
+        guild_id: GuildId,
+        user_id: UserId,
+        role_id: RoleId,
+    },
+    RemoveMemberRole {
+        guild_id: GuildId,
+        user_id: UserId,
+        role_id: RoleId,
+    },
+    GetMember {
+        guild_id: GuildId,
+        user_id: UserId,
+    },
+    TimeOutMember {
+        guild_id: GuildId,
+        user_id: UserId,
+        until: Timestamp,
+    },
+    DeleteMessage {
+        channel_id: ChannelId,
+        message_id: MessageId,
+    },
+}
+
+type FailurePredicate = Box<dyn Fn(&DiscordCall) -> bool + Send + Sync>;
+
+/// Records calls instead of making them. Every call succeeds unless it matches
+/// [`RecordingHttp::fail_when`], and only members added with
+/// [`RecordingHttp::with_member`] can be fetched.
+#[derive(Default)]
+pub struct RecordingHttp {
+    calls: Mutex<Vec<DiscordCall>>,
+    members: HashMap<(GuildId, UserId), Member>,
+    fail_when: Option<FailurePredicate>,
+    next_message_id: AtomicU64,
+}
+
+impl RecordingHttp {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_member(mut self, member: Member) -> Self {
+        self.members.insert((member.guild_id, member.user.id), member);
+        self
+    }
+
+    /// Makes the calls matching `predicate` fail. They still get recorded.
+    pub fn fail_when(
+        mut self, predicate: impl Fn(&DiscordCall) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.fail_when = Some(Box::new(predicate));
+        self
+    }
+
+    pub fn calls(&self) -> Vec<DiscordCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// The messages sent to `channel_id`, in order.
+    pub fn sent_to(&self, channel_id: ChannelId) -> Vec<serde_json::Value> {
+        self.calls()
+            .into_iter()
+            .filter_map(|call| match call {
+                DiscordCall::SendMessage { channel_id: id, message } if id == channel_id => {
+                    Some(message)
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[allow(
+        clippy::result_large_err,
+        reason = "The fake returns the same errors as the `DiscordHttp` methods it stands in for."
+    )]
+    fn record(&self, call: DiscordCall) -> serenity::Result<()> {
+        let fails = self.fail_when.as_ref().is_some_and(|predicate| predicate(&call));
+
+        self.calls.lock().unwrap().push(call);
+
+        if fails { Err(serenity::Error::Other("Failed by RecordingHttp")) } else { Ok(()) }
+    }
+}
+
+#[async_trait]
+impl DiscordHttp for RecordingHttp {
+    async fn send_message(
+        &self, channel_id: ChannelId, message: CreateMessage,
+    ) -> serenity::Result<Message> {
+        let json = serde_json::to_value(&message).expect("Messages should serialize.");
+
+        self.record(DiscordCall::SendMessage { channel_id, message: json })?;
+
+        let mut sent = Message::default();
+
+        sent.id = MessageId::new(self.next_message_id.fetch_add(1, Ordering::Relaxed) + 1);
+        sent.channel_id = channel_id;
+
+        Ok(sent)
+    }
+
+    async fn add_member_role(
+        &self, guild_id: GuildId, user_id: UserId, role_id: RoleId, _: Option<&str>,
+    ) -> serenity::Result<()> {
+        self.record(DiscordCall::AddMemberRole { guild_id, user_id, role_id })
+    }
+
+    async fn remove_member_role(
+        &self, guild_id: GuildId, user_id: UserId, role_id: RoleId, _: Option<&str>,
+    ) -> serenity::Result<()> {
+        self.record(DiscordCall::RemoveMemberRole { guild_id, user_id, role_id })
+    }
+
+    async fn get_member(&self, guild_id: GuildId, user_id: UserId) -> serenity::Result<Member> {
+        self.record(DiscordCall::GetMember { guild_id, user_id })?;
+
+        self.members
+            .get(&(guild_id, user_id))
+            .cloned()
+            .ok_or(serenity::Error::Other("Unknown member"))
+    }
+
+    async fn time_out_member(
+        &self, guild_id: GuildId, user_id: UserId, until: Timestamp,
+    ) -> serenity::Result<()> {
+        self.record(DiscordCall::TimeOutMember { guild_id, user_id, until })
+    }
+
+    async fn delete_message(
+        &self, channel_id: ChannelId, message_id: MessageId,
+    ) -> serenity::Result<()> {
+        self.record(DiscordCall::DeleteMessage { channel_id, message_id })
+    }
+}
+
+/// A member of `guild_id` with the given roles, for [`RecordingHttp::with_member`].
+pub fn test_member(guild_id: u64, user_id: u64, roles: &[u64]) -> Member {
+    let mut member = Member::default();
+
+    member.guild_id = GuildId::new(guild_id);
+    member.user.id = UserId::new(user_id);
+    member.user.name = format!("user{user_id}");
+    member.roles = roles.iter().copied().map(RoleId::new).collect();
+
+    member
+}
@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use futures::join;
 use log::info;
-use serenity::all::ChunkGuildFilter;
+use serenity::all::{ChunkGuildFilter, Interaction};
 use serenity::async_trait;
 use serenity::client::{Context, EventHandler};
 use serenity::model::channel::Message;
@@ -13,7 +13,7 @@ use serenity::model::prelude::Ready;
 use tokio::time;
 
 use crate::commands::{custom, vocaroo};
-use crate::{logger, spanish_english};
+use crate::{birthday_tracker, guild_config, logger, metrics, spanish_english};
 
 #[cfg(feature = "songbird")]
 use {
@@ -49,16 +49,24 @@ async fn chunk_guilds(ctx: &Context, guilds: &[GuildId]) {
 #[async_trait]
 impl EventHandler for BurdBotEventHandler {
     async fn ready(&self, context: Context, _ready: Ready) {
+        let _timer = metrics::EVENT_HANDLER_SECONDS.start_timer(&["ready"]);
+
         crate::on_ready();
 
         #[cfg(feature = "songbird")]
-        join!(session_tracker::on_ready(&context), vocaroo::on_ready(&context));
+        join!(
+            session_tracker::on_ready(&context),
+            vocaroo::on_ready(&context),
+            guild_config::on_ready(&context)
+        );
 
         #[cfg(not(feature = "songbird"))]
-        vocaroo::on_ready(&context).await;
+        join!(vocaroo::on_ready(&context), guild_config::on_ready(&context));
     }
 
     async fn message(&self, ctx: Context, new_message: Message) {
+        let _timer = metrics::EVENT_HANDLER_SECONDS.start_timer(&["message"]);
+
         join!(
             spanish_english::on_message_receive(&ctx, &new_message),
             vocaroo::on_message_received(&ctx, &new_message),
@@ -66,7 +74,18 @@ impl EventHandler for BurdBotEventHandler {
         );
     }
 
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        let _timer = metrics::EVENT_HANDLER_SECONDS.start_timer(&["interaction_create"]);
+
+        // Slash commands are handled by the framework.
+        if let Interaction::Component(component) = &interaction {
+            birthday_tracker::on_component_interaction(&ctx, component).await;
+        }
+    }
+
     async fn cache_ready(&self, context: Context, guilds: Vec<GuildId>) {
+        let _timer = metrics::EVENT_HANDLER_SECONDS.start_timer(&["cache_ready"]);
+
         chunk_guilds(&context, guilds.as_slice()).await;
 
         crate::on_cache_ready(&context);
@@ -20,14 +20,17 @@ use scraper::Html;
 use scraper::Selector;
 use std::fmt::Display;
 use std::string::FromUtf8Error;
+use strum::EnumMessage;
 use strum::EnumProperty;
 use strum::IntoEnumIterator;
 use strum::ParseError;
 use strum_macros::EnumIter;
+use strum_macros::EnumMessage;
 use strum_macros::EnumProperty;
 use strum_macros::EnumString;
 use thiserror::Error;
 
+use crate::metrics;
 use crate::util::anti_scraper_download_file;
 use crate::util::anti_scraper_get_html;
 
@@ -127,7 +130,19 @@ enum Language {
     Unknown,
 }
 
-#[derive(Debug, Default, Hash, PartialEq, Eq, Clone, Copy, EnumIter, EnumString, EnumProperty)]
+#[derive(
+    Debug,
+    Default,
+    Hash,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    EnumIter,
+    EnumString,
+    EnumProperty,
+    EnumMessage,
+)]
 pub enum Country {
     #[strum(
         serialize = "🇦🇷",
@@ -243,6 +258,11 @@ pub enum Country {
 }
 
 impl Country {
+    /// The country's name, which is the serialization after its flag.
+    pub fn name(self) -> &'static str {
+        self.get_serializations().last().copied().unwrap_or_default()
+    }
+
     fn get_language(self) -> Language {
         match self.get_str("language") {
             Some("s") => Language::Spanish,
@@ -398,7 +418,18 @@ async fn get_all_recordings(
 }
 
 async fn get_pronunciation_from_link(forvo_recording: &str) -> Result<Vec<u8>> {
-    Ok(anti_scraper_download_file(forvo_recording).await?)
+    match anti_scraper_download_file(forvo_recording).await {
+        Ok(recording) => {
+            metrics::DOWNLOAD_BYTES.observe(&["forvo"], recording.len() as f64);
+
+            Ok(recording)
+        },
+        Err(err) => {
+            metrics::DOWNLOAD_FAILURES.inc(&["forvo"]);
+
+            Err(err.into())
+        },
+    }
 }
 
 fn recording_to_distance<T>(
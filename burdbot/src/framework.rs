@@ -0,0 +1,129 @@
+//! Runs the prefix commands and the slash commands side by side.
+//!
+//! Serenity only takes one framework, so [`BurdBotFramework`] hands every event to
+//! both the [`StandardFramework`] with the prefix commands and a poise framework with
+//! the slash commands. Poise is given no prefix so that it never handles messages.
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use log::{error, info};
+use serenity::all::{Context, FullEvent, UserId};
+use serenity::async_trait;
+use serenity::client::Client;
+use serenity::framework::standard::CommandError;
+use serenity::framework::{Framework, StandardFramework};
+
+use crate::commands::{self, SlashContext, error_util};
+use crate::metrics;
+
+type PoiseFramework = poise::Framework<(), CommandError>;
+type FrameworkError<'a> = poise::FrameworkError<'a, (), CommandError>;
+
+pub struct BurdBotFramework {
+    standard: StandardFramework,
+    poise: PoiseFramework,
+}
+
+impl BurdBotFramework {
+    pub fn new(standard: StandardFramework, owners: HashSet<UserId>) -> Self {
+        let options = poise::FrameworkOptions {
+            commands: commands::slash_commands(),
+            owners,
+            initialize_owners: false,
+            prefix_options: poise::PrefixFrameworkOptions {
+                mention_as_prefix: false,
+                ..Default::default()
+            },
+            on_error: |error| Box::pin(on_error(error)),
+            pre_command: |ctx| {
+                Box::pin(async move {
+                    metrics::COMMANDS.inc(&[ctx.command().name.as_str()]);
+                    ctx.set_invocation_data(Instant::now()).await;
+                })
+            },
+            post_command: |ctx| {
+                Box::pin(async move {
+                    info!("Ran /{} successfully.", ctx.command().name);
+
+                    record_usage(ctx, None).await;
+                })
+            },
+            ..Default::default()
+        };
+
+        let poise = PoiseFramework::builder()
+            .options(options)
+            .setup(|ctx, _, framework| {
+                Box::pin(async move {
+                    poise::builtins::register_globally(ctx, &framework.options().commands).await?;
+
+                    Ok(())
+                })
+            })
+            .build();
+
+        BurdBotFramework { standard, poise }
+    }
+}
+
+async fn record_usage(ctx: SlashContext<'_>, error: Option<&CommandError>) {
+    let start = ctx.invocation_data::<Instant>().await.map(|start| *start);
+    let duration = start.map_or(Duration::ZERO, |start| start.elapsed());
+
+    commands::record_command_usage(
+        ctx.serenity_context(),
+        &ctx.command().name,
+        ctx.guild_id(),
+        ctx.channel_id(),
+        ctx.author().id,
+        error,
+        duration,
+    )
+    .await;
+}
+
+async fn on_error(error: FrameworkError<'_>) {
+    match error {
+        poise::FrameworkError::Command { error, ctx, .. } => {
+            error!("Error in /{}: {error:?}", ctx.command().name);
+
+            metrics::COMMAND_FAILURES.inc(&[ctx.command().name.as_str()]);
+            record_usage(ctx, Some(&error)).await;
+
+            if let Err(err) = ctx.say(error_util::GENERIC_FAIL).await {
+                info!("Couldn't reply to a failed slash command: {err:?}");
+            }
+        },
+        // Poise doesn't reply when a check fails, so the interaction would be left hanging.
+        poise::FrameworkError::CommandCheckFailed { error: None, ctx, .. } => {
+            let reply = poise::CreateReply::default()
+                .content("You don't have permission to use this command.")
+                .ephemeral(true);
+
+            if let Err(err) = ctx.send(reply).await {
+                info!("Couldn't reply to a failed slash command check: {err:?}");
+            }
+        },
+        error => {
+            if let Err(err) = poise::builtins::on_error(error).await {
+                error!("Error while handling a slash command error: {err:?}");
+            }
+        },
+    }
+}
+
+#[async_trait]
+impl Framework for BurdBotFramework {
+    async fn init(&mut self, client: &Client) {
+        self.standard.init(client).await;
+        self.poise.init(client).await;
+    }
+
+    async fn dispatch(&self, ctx: Context, event: FullEvent) {
+        futures::join!(
+            self.standard.dispatch(ctx.clone(), event.clone()),
+            self.poise.dispatch(ctx, event)
+        );
+    }
+}
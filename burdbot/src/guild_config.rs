@@ -0,0 +1,424 @@
+//! Per-guild configuration.
+//!
+//! Each guild has a set of key/value pairs in the `guild_config` table. Some of these
+//! are read on every message, so all of them are cached in the type map on ready and
+//! the cache is updated whenever a value is set.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use lazy_static::lazy_static;
+use log::{error, warn};
+use regex::Regex;
+use rusqlite::Connection;
+use serenity::all::{ChannelId, GuildId, Mentionable, RoleId};
+use serenity::client::Context;
+use serenity::prelude::TypeMapKey;
+use strum::EnumProperty;
+use strum_macros::{Display, EnumIter, EnumProperty, EnumString};
+use thiserror::Error;
+
+use crate::birthday_tracker::{self, LeapDayPolicy};
+use crate::config;
+use crate::database::{self, Database};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Display, EnumString, EnumIter, EnumProperty)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum GuildConfigKey {
+    #[strum(props(description = "Channel where staff get notified, such as for banned images."))]
+    StaffChannel,
+    #[strum(props(description = "Role pinged in staff notifications."))]
+    StaffRole,
+    #[strum(props(description = "Channel where channel bans and unbans are logged."))]
+    ModLogChannel,
+    #[strum(props(description = "Roles allowed to use server helper commands."))]
+    HelperRoles,
+    #[strum(props(
+        description = "Lets anyone use server helper commands. Meant for test servers."
+    ))]
+    HelperCheckBypass,
+    #[strum(props(description = "Role that bans someone from the memes channel."))]
+    MemesBanRole,
+    #[strum(props(description = "Role that bans someone from the sesión de lectura channel."))]
+    LecturaBanRole,
+    #[strum(props(description = "Channel where music bot commands get a warning."))]
+    MusicChannel,
+    #[strum(props(description = "Channel music bot commands should be redirected to."))]
+    MusicBotChannel,
+    #[strum(props(description = "Prefix for text commands, instead of the default one."))]
+    Prefix,
+    #[strum(props(description = "Day Feb 29 birthdays get in other years, feb28 or mar1."))]
+    LeapDayPolicy,
+    #[strum(props(description = "Channel where birthdays get announced."))]
+    BirthdayChannel,
+    #[strum(props(
+        description = "English birthday announcement. Can use {mention}, {name} and {age}."
+    ))]
+    BirthdayMessage,
+    #[strum(props(
+        description = "Spanish birthday announcement. Can use {mention}, {name} and {age}."
+    ))]
+    BirthdayMessageSpanish,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GuildConfigValueKind {
+    Channel,
+    Role,
+    Roles,
+    Bool,
+    Prefix,
+    LeapDayPolicy,
+    Template,
+}
+
+impl GuildConfigKey {
+    pub fn kind(self) -> GuildConfigValueKind {
+        match self {
+            Self::StaffChannel
+            | Self::ModLogChannel
+            | Self::MusicChannel
+            | Self::MusicBotChannel
+            | Self::BirthdayChannel => GuildConfigValueKind::Channel,
+            Self::StaffRole | Self::MemesBanRole | Self::LecturaBanRole => {
+                GuildConfigValueKind::Role
+            },
+            Self::HelperRoles => GuildConfigValueKind::Roles,
+            Self::HelperCheckBypass => GuildConfigValueKind::Bool,
+            Self::Prefix => GuildConfigValueKind::Prefix,
+            Self::LeapDayPolicy => GuildConfigValueKind::LeapDayPolicy,
+            Self::BirthdayMessage | Self::BirthdayMessageSpanish => GuildConfigValueKind::Template,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        self.get_str("description").unwrap_or_default()
+    }
+}
+
+#[derive(Error, Debug, Clone)]
+#[error("'{value}' is not a valid {kind}.")]
+pub struct GuildConfigParseError {
+    pub value: String,
+    pub kind: &'static str,
+}
+
+impl GuildConfigParseError {
+    fn new(value: &str, kind: &'static str) -> Self {
+        Self { value: value.to_owned(), kind }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuildConfigValue {
+    Channel(ChannelId),
+    Role(RoleId),
+    Roles(Vec<RoleId>),
+    Bool(bool),
+    Prefix(String),
+    LeapDayPolicy(LeapDayPolicy),
+    Template(String),
+}
+
+/// Longest prefix a guild can set, so that it can't crowd out the commands.
+const MAX_PREFIX_LENGTH: usize = 5;
+
+fn parse_id(value: &str) -> Option<u64> {
+    lazy_static! {
+        static ref ID_MATCHER: Regex = Regex::new(r"^(?:<#|<@&)?(\d{17,20})>?$").unwrap();
+    }
+
+    ID_MATCHER.captures(value.trim())?.get(1)?.as_str().parse().ok()
+}
+
+impl GuildConfigValue {
+    /// Parses a value of the given kind. Accepts both the stored format and
+    /// what a user would type, so IDs can also be given as mentions.
+    pub fn parse(kind: GuildConfigValueKind, value: &str) -> Result<Self, GuildConfigParseError> {
+        match kind {
+            GuildConfigValueKind::Channel => parse_id(value)
+                .map(|id| Self::Channel(ChannelId::new(id)))
+                .ok_or_else(|| GuildConfigParseError::new(value, "channel")),
+            GuildConfigValueKind::Role => parse_id(value)
+                .map(|id| Self::Role(RoleId::new(id)))
+                .ok_or_else(|| GuildConfigParseError::new(value, "role")),
+            GuildConfigValueKind::Roles => value
+                .split([',', ' '])
+                .filter(|s| !s.is_empty())
+                .map(|s| parse_id(s).map(RoleId::new))
+                .collect::<Option<Vec<_>>>()
+                .filter(|roles| !roles.is_empty())
+                .map(Self::Roles)
+                .ok_or_else(|| GuildConfigParseError::new(value, "list of roles")),
+            GuildConfigValueKind::Bool => match value.trim().to_lowercase().as_str() {
+                "1" | "true" | "yes" | "on" => Ok(Self::Bool(true)),
+                "0" | "false" | "no" | "off" => Ok(Self::Bool(false)),
+                _ => Err(GuildConfigParseError::new(value, "true or false value")),
+            },
+            GuildConfigValueKind::Prefix => {
+                let prefix = value.trim();
+
+                if prefix.is_empty()
+                    || prefix.contains(char::is_whitespace)
+                    || prefix.chars().count() > MAX_PREFIX_LENGTH
+                {
+                    return Err(GuildConfigParseError::new(value, "prefix"));
+                }
+
+                Ok(Self::Prefix(prefix.to_owned()))
+            },
+            GuildConfigValueKind::LeapDayPolicy => value
+                .parse()
+                .map(Self::LeapDayPolicy)
+                .map_err(|_| GuildConfigParseError::new(value, "leap day policy")),
+            GuildConfigValueKind::Template => {
+                let template = value.trim();
+
+                if !birthday_tracker::is_valid_birthday_message(template) {
+                    return Err(GuildConfigParseError::new(value, "message template"));
+                }
+
+                Ok(Self::Template(template.to_owned()))
+            },
+        }
+    }
+
+    fn channel(self) -> Option<ChannelId> {
+        if let Self::Channel(id) = self { Some(id) } else { None }
+    }
+
+    fn role(self) -> Option<RoleId> {
+        if let Self::Role(id) = self { Some(id) } else { None }
+    }
+
+    fn roles(self) -> Option<Vec<RoleId>> {
+        if let Self::Roles(ids) = self { Some(ids) } else { None }
+    }
+
+    fn prefix(self) -> Option<String> {
+        if let Self::Prefix(prefix) = self { Some(prefix) } else { None }
+    }
+
+    fn leap_day_policy(self) -> Option<LeapDayPolicy> {
+        if let Self::LeapDayPolicy(policy) = self { Some(policy) } else { None }
+    }
+
+    fn template(self) -> Option<String> {
+        if let Self::Template(template) = self { Some(template) } else { None }
+    }
+
+    /// The format the value is stored as in the database
+    fn to_db_string(&self) -> String {
+        match self {
+            Self::Channel(id) => id.to_string(),
+            Self::Role(id) => id.to_string(),
+            Self::Roles(ids) => ids.iter().map(RoleId::to_string).collect::<Vec<_>>().join(","),
+            Self::Bool(value) => u8::from(*value).to_string(),
+            Self::Prefix(prefix) => prefix.clone(),
+            Self::LeapDayPolicy(policy) => policy.to_db_string().to_owned(),
+            Self::Template(template) => template.clone(),
+        }
+    }
+}
+
+impl Display for GuildConfigValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Channel(id) => write!(f, "{}", id.mention()),
+            Self::Role(id) => write!(f, "{}", id.mention()),
+            Self::Roles(ids) => {
+                let mentions = ids.iter().map(|id| id.mention().to_string()).collect::<Vec<_>>();
+
+                write!(f, "{}", mentions.join(" "))
+            },
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Prefix(prefix) => write!(f, "``{prefix}``"),
+            Self::LeapDayPolicy(policy) => write!(f, "{policy}"),
+            Self::Template(template) => write!(f, "``{template}``"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GuildConfig {
+    pub staff_channel: Option<ChannelId>,
+    pub staff_role: Option<RoleId>,
+    pub mod_log_channel: Option<ChannelId>,
+    pub helper_roles: Vec<RoleId>,
+    pub helper_check_bypass: bool,
+    pub memes_ban_role: Option<RoleId>,
+    pub lectura_ban_role: Option<RoleId>,
+    pub music_channel: Option<ChannelId>,
+    pub music_bot_channel: Option<ChannelId>,
+    pub prefix: Option<String>,
+    pub leap_day_policy: LeapDayPolicy,
+    pub birthday_channel: Option<ChannelId>,
+    pub birthday_message: Option<String>,
+    pub birthday_message_spanish: Option<String>,
+}
+
+impl GuildConfig {
+    pub fn get(&self, key: GuildConfigKey) -> Option<GuildConfigValue> {
+        match key {
+            GuildConfigKey::StaffChannel => self.staff_channel.map(GuildConfigValue::Channel),
+            GuildConfigKey::StaffRole => self.staff_role.map(GuildConfigValue::Role),
+            GuildConfigKey::ModLogChannel => self.mod_log_channel.map(GuildConfigValue::Channel),
+            GuildConfigKey::HelperRoles => Some(self.helper_roles.clone())
+                .filter(|roles| !roles.is_empty())
+                .map(GuildConfigValue::Roles),
+            GuildConfigKey::HelperCheckBypass => {
+                Some(GuildConfigValue::Bool(self.helper_check_bypass))
+            },
+            GuildConfigKey::MemesBanRole => self.memes_ban_role.map(GuildConfigValue::Role),
+            GuildConfigKey::LecturaBanRole => self.lectura_ban_role.map(GuildConfigValue::Role),
+            GuildConfigKey::MusicChannel => self.music_channel.map(GuildConfigValue::Channel),
+            GuildConfigKey::MusicBotChannel => {
+                self.music_bot_channel.map(GuildConfigValue::Channel)
+            },
+            GuildConfigKey::Prefix => self.prefix.clone().map(GuildConfigValue::Prefix),
+            GuildConfigKey::LeapDayPolicy => {
+                Some(GuildConfigValue::LeapDayPolicy(self.leap_day_policy))
+            },
+            GuildConfigKey::BirthdayChannel => self.birthday_channel.map(GuildConfigValue::Channel),
+            GuildConfigKey::BirthdayMessage => {
+                self.birthday_message.clone().map(GuildConfigValue::Template)
+            },
+            GuildConfigKey::BirthdayMessageSpanish => {
+                self.birthday_message_spanish.clone().map(GuildConfigValue::Template)
+            },
+        }
+    }
+
+    /// Sets a key to the given value, or unsets it if `None`.
+    /// Values of the wrong kind for the key are treated as unset.
+    fn set(&mut self, key: GuildConfigKey, value: Option<GuildConfigValue>) {
+        match key {
+            GuildConfigKey::StaffChannel => self.staff_channel = value.and_then(|v| v.channel()),
+            GuildConfigKey::StaffRole => self.staff_role = value.and_then(|v| v.role()),
+            GuildConfigKey::ModLogChannel => self.mod_log_channel = value.and_then(|v| v.channel()),
+            GuildConfigKey::HelperRoles => {
+                self.helper_roles = value.and_then(|v| v.roles()).unwrap_or_default()
+            },
+            GuildConfigKey::HelperCheckBypass => {
+                self.helper_check_bypass = value == Some(GuildConfigValue::Bool(true))
+            },
+            GuildConfigKey::MemesBanRole => self.memes_ban_role = value.and_then(|v| v.role()),
+            GuildConfigKey::LecturaBanRole => self.lectura_ban_role = value.and_then(|v| v.role()),
+            GuildConfigKey::MusicChannel => self.music_channel = value.and_then(|v| v.channel()),
+            GuildConfigKey::MusicBotChannel => {
+                self.music_bot_channel = value.and_then(|v| v.channel())
+            },
+            GuildConfigKey::Prefix => self.prefix = value.and_then(|v| v.prefix()),
+            GuildConfigKey::LeapDayPolicy => {
+                self.leap_day_policy = value.and_then(|v| v.leap_day_policy()).unwrap_or_default()
+            },
+            GuildConfigKey::BirthdayChannel => {
+                self.birthday_channel = value.and_then(|v| v.channel())
+            },
+            GuildConfigKey::BirthdayMessage => {
+                self.birthday_message = value.and_then(|v| v.template())
+            },
+            GuildConfigKey::BirthdayMessageSpanish => {
+                self.birthday_message_spanish = value.and_then(|v| v.template())
+            },
+        }
+    }
+}
+
+struct GuildConfigCache;
+
+impl TypeMapKey for GuildConfigCache {
+    type Value = HashMap<GuildId, GuildConfig>;
+}
+
+/// Reads every guild's configuration from the database, for code without a [`Context`] like
+/// the birthday updater. Prefer [`get`], which uses the cache.
+pub async fn get_all_guild_configs(
+    db: &Database,
+) -> rusqlite::Result<HashMap<GuildId, GuildConfig>> {
+    let mut configs: HashMap<GuildId, GuildConfig> = HashMap::new();
+
+    for (guild_id, key, value) in db.guild_configs().get_all().await? {
+        let Ok(key) = key.parse::<GuildConfigKey>() else {
+            warn!("Unknown guild config key '{key}' found for guild {guild_id}. Ignoring it.");
+            continue;
+        };
+
+        match GuildConfigValue::parse(key.kind(), value.as_str()) {
+            Ok(value) => configs.entry(GuildId::new(guild_id)).or_default().set(key, Some(value)),
+            Err(err) => warn!("Bad value for guild config key {key} in guild {guild_id}: {err}"),
+        }
+    }
+
+    Ok(configs)
+}
+
+/// Seeds the configuration for the Spanish-English server and the test server
+/// with the values that used to be hardcoded. Only run when the table is first created.
+pub(crate) fn seed_guild_configs(connection: &Connection) -> rusqlite::Result<()> {
+    let seed_statements = "
+        INSERT OR IGNORE INTO guild_config VALUES
+            (243838819743432704, 'staff_channel', '913886469809115206'),
+            (243838819743432704, 'staff_role', '642782671109488641'),
+            (243838819743432704, 'mod_log_channel', '873845572975603792'),
+            (243838819743432704, 'helper_roles', '243854949522472971,258806166770024449,258819531193974784'),
+            (243838819743432704, 'memes_ban_role', '863822767702409216'),
+            (243838819743432704, 'lectura_ban_role', '1467650674945822937'),
+            (243838819743432704, 'music_channel', '263643662808776704'),
+            (243838819743432704, 'music_bot_channel', '247135634265735168'),
+            (720900352018219039, 'helper_check_bypass', '1');
+    ";
+
+    connection.execute_batch(seed_statements)
+}
+
+pub async fn on_ready(ctx: &Context) {
+    match get_all_guild_configs(&database::get(ctx).await).await {
+        Ok(configs) => {
+            let mut data = ctx.data.write().await;
+
+            data.insert::<GuildConfigCache>(configs);
+        },
+        Err(err) => error!("Failed to load guild configs: {err:?}"),
+    }
+}
+
+/// Gets the configuration for a guild. Unconfigured guilds get the default configuration.
+pub async fn get(ctx: &Context, guild_id: GuildId) -> GuildConfig {
+    let data = ctx.data.read().await;
+
+    data.get::<GuildConfigCache>()
+        .and_then(|configs| configs.get(&guild_id).cloned())
+        .unwrap_or_default()
+}
+
+/// Gets the prefix for text commands in a guild, falling back to the default one
+/// for DMs and guilds that haven't set their own.
+pub async fn get_prefix(ctx: &Context, guild_id: Option<GuildId>) -> String {
+    let data = ctx.data.read().await;
+
+    guild_id
+        .and_then(|guild_id| data.get::<GuildConfigCache>()?.get(&guild_id)?.prefix.clone())
+        .unwrap_or_else(|| config::get().prefix.clone())
+}
+
+/// Sets or unsets (if `value` is `None`) a key for a guild, persisting it.
+pub async fn set(
+    ctx: &Context, guild_id: GuildId, key: GuildConfigKey, value: Option<GuildConfigValue>,
+) -> rusqlite::Result<()> {
+    let db = database::get(ctx).await;
+
+    match &value {
+        Some(value) => {
+            db.guild_configs().set(guild_id.get(), key.to_string(), value.to_db_string()).await?
+        },
+        None => db.guild_configs().unset(guild_id.get(), key.to_string()).await?,
+    };
+
+    // Only locked after the write, since every message needs to read the cache for its prefix.
+    let mut data = ctx.data.write().await;
+
+    data.entry::<GuildConfigCache>().or_default().entry(guild_id).or_default().set(key, value);
+
+    Ok(())
+}
@@ -23,11 +23,12 @@ use chrono::Utc;
 use digest::{Digest, Output};
 use log::info;
 use reqwest::Client;
-use rusqlite::{Connection, Row, params};
+use rusqlite::Row;
 use serenity::all::{GuildId, Message};
 use strum_macros::Display;
 
-use crate::{BURDBOT_DB, error::SerenitySQLiteResult};
+use crate::database::{Database, NewBannedImage};
+use crate::error::SerenitySQLiteResult;
 
 /// Sets the byte limit until the image hash becomes a blocking task.
 /// Currently 9MB
@@ -135,7 +136,8 @@ impl<T: Digest> ImageChecker<T> {
     // - the entry already exists in the checker (Duplicate)
     // An Err indicates some internal error occurred.
     pub async fn add_image(
-        &self, desc: &str, guild_id: GuildId, message: &Message, hash_type: impl Into<u16>,
+        &self, db: &Database, desc: &str, guild_id: GuildId, message: &Message,
+        hash_type: impl Into<u16>,
     ) -> SerenitySQLiteResult<ImageOpOutcome> {
         let message_images = MessageImages(message);
         let images = message_images.to_vec();
@@ -146,38 +148,28 @@ impl<T: Digest> ImageChecker<T> {
 
         let (url, width, height) = images[0];
         let hash = self.calc_image_hash(url).await?;
-        let link = message.id.link(message.channel_id, Some(guild_id));
-        let connection = Connection::open(BURDBOT_DB)?;
-        let insertion_statement = "
-                INSERT OR IGNORE INTO fxhash_image_checksums
-                    VALUES (?, ?, ?, ?, ?, ?, ?);
-        ";
-
-        let rows_updated = connection.execute(
-            insertion_statement,
-            params!(link, width, height, desc, &hash[..], hash_type.into(), guild_id.get()),
-        )?;
-
-        // If no rows updated, there was a duplicate
-        Ok(if rows_updated == 0 { ImageOpOutcome::Duplicate } else { ImageOpOutcome::Success })
+        let image = NewBannedImage {
+            link_ref: message.id.link(message.channel_id, Some(guild_id)),
+            width,
+            height,
+            description: desc.to_owned(),
+            hash: hash.to_vec(),
+            hash_type: hash_type.into(),
+        };
+
+        let was_added = db.banned_images().add(guild_id.get(), image).await?;
+
+        Ok(if was_added { ImageOpOutcome::Success } else { ImageOpOutcome::Duplicate })
     }
 
     // Removes an image from the image checker for the guild.
     // Returns NotFound if not found in guild, otherwise Success, unless an internal error occurs
-    pub fn remove_image(
-        &self, guild_id: GuildId, msg_link: &str,
+    pub async fn remove_image(
+        &self, db: &Database, guild_id: GuildId, msg_link: &str,
     ) -> SerenitySQLiteResult<ImageOpOutcome> {
-        let connection = Connection::open(BURDBOT_DB)?;
-        let deletion_statement = "
-                DELETE FROM fxhash_image_checksums
-                    WHERE link_reference = ? AND guild_id = ?;
-        ";
+        let was_removed = db.banned_images().remove(guild_id.get(), msg_link.to_owned()).await?;
 
-        let rows_updated =
-            connection.execute(deletion_statement, params!(msg_link, guild_id.get()))?;
-
-        // If no rows updated, then it wasn't in the checker
-        Ok(if rows_updated == 0 { ImageOpOutcome::NotFound } else { ImageOpOutcome::Success })
+        Ok(if was_removed { ImageOpOutcome::Success } else { ImageOpOutcome::NotFound })
     }
 
     // Checks if an image passes the filters for the guild.
@@ -185,28 +177,13 @@ impl<T: Digest> ImageChecker<T> {
     // Returns Ok(None) if no image found and no error.
     // Err if there was an internal error
     pub async fn check_image(
-        &self, guild_id: GuildId, image: (&str, u32, u32),
+        &self, db: &Database, guild_id: GuildId, image: (&str, u32, u32),
     ) -> SerenitySQLiteResult<Option<String>> {
         let (url, width, height) = image;
-        let rows;
 
         info!("Got attachments {image:?}");
 
-        {
-            let connection = Connection::open(BURDBOT_DB)?;
-            let mut img_query = connection.prepare(
-                "
-                SELECT hash, link_reference FROM fxhash_image_checksums
-                WHERE guild_id = ?1 AND width = ?2 AND height = ?3;
-                ",
-            )?;
-
-            rows = img_query
-                .query_and_then(params![guild_id.get(), width, height], |row| {
-                    Ok((row.get::<_, Vec<u8>>(0)?, row.get::<_, String>(1)?))
-                })?
-                .collect::<rusqlite::Result<Vec<(Vec<u8>, String)>>>()?;
-        }
+        let rows = db.banned_images().get_hashes(guild_id.get(), width, height).await?;
 
         // Means no images with matching dimension found
         if rows.is_empty() {
@@ -226,22 +203,11 @@ impl<T: Digest> ImageChecker<T> {
     }
 
     // Gets the images stored for a guild
-    pub fn get_images(&self, guild_id: GuildId) -> SerenitySQLiteResult<Vec<ImageResult>> {
+    pub async fn get_images(
+        &self, db: &Database, guild_id: GuildId,
+    ) -> SerenitySQLiteResult<Vec<ImageResult>> {
         // TODO: expand later to filter by width and height, or by link
 
-        let connection = Connection::open(BURDBOT_DB)?;
-        let mut image_query = connection.prepare_cached(
-            "
-                SELECT link_reference, width, height, description, hash, hash_type FROM fxhash_image_checksums
-                WHERE guild_id = ?1;
-        ",
-        )?;
-
-        // query_and_then returns a vector of rusqlite::Result<ImageResult>, so collect into one
-        let result = image_query
-            .query_and_then(params![guild_id.get()], |row| ImageResult::try_from(row))?
-            .collect::<rusqlite::Result<Vec<ImageResult>>>()?;
-
-        Ok(result)
+        Ok(db.banned_images().get_all(guild_id.get()).await?)
     }
 }
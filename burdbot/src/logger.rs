@@ -17,7 +17,7 @@ use serenity::model::id::{ChannelId, UserId};
 use std::str;
 use tokio::time;
 
-use crate::DELIBURD_ID;
+use crate::{config, metrics};
 
 pub struct LogSender {
     cache: Arc<Cache>,
@@ -44,13 +44,14 @@ impl Debug for LogSender {
 static DELIBURD_CHANNEL_ID: OnceCell<Option<ChannelId>> = OnceCell::new();
 
 async fn get_deliburd_channel_id(cache_and_http: impl CacheHttp) -> Option<ChannelId> {
-    let channel = UserId::from(DELIBURD_ID).create_dm_channel(cache_and_http).await;
+    let owner_id = config::get().owner_id;
+    let channel = UserId::from(owner_id).create_dm_channel(cache_and_http).await;
 
     match channel {
         Ok(channel) => Some(channel.id),
         Err(err) => {
             eprintln!(
-                "Couldn't create DM channel with {DELIBURD_ID} to send logs to. Error: {err}\nSending logs to fallback file instead."
+                "Couldn't create DM channel with {owner_id} to send logs to. Error: {err}\nSending logs to fallback file instead."
             );
 
             None
@@ -208,6 +209,12 @@ impl Write for DiscordLogger {
 
         write_buffer.extend_from_slice(&buf[..bytes_to_write]);
 
+        // After a partial write the caller retries with the rest, so the bytes are only
+        // dropped once nothing fits.
+        if bytes_to_write == 0 {
+            metrics::LOGGER_DROPPED_BYTES.inc_by(buf.len() as u64);
+        }
+
         Ok(bytes_to_write)
     }
 
@@ -8,10 +8,17 @@ pub mod vocaroo;
 
 mod birthday_tracker;
 mod commands;
+mod config;
+mod database;
+mod discord;
 mod error;
 mod event_handler;
+mod framework;
+mod guild_config;
 mod image_checker;
 mod logger;
+mod metrics;
+mod migrations;
 mod spanish_english;
 mod util;
 
@@ -20,26 +27,29 @@ mod session_tracker;
 
 use async_ctrlc::CtrlC;
 use chrono::{Timelike, Utc};
+use database::Database;
 use event_handler::BurdBotEventHandler;
+use framework::BurdBotFramework;
+use lazy_static::lazy_static;
 use log::{LevelFilter, info, warn};
 use logger::{DiscordLogger, LogSender};
 use rusqlite::Connection;
 use serenity::Client;
 use serenity::all::ShardManager;
-use serenity::all::standard::{BucketBuilder, Configuration};
+use serenity::all::standard::Configuration;
 use serenity::client::Context;
 use serenity::framework::StandardFramework;
 use serenity::framework::standard::CommandResult;
 use serenity::framework::standard::macros::hook;
-use serenity::http::Http;
 use serenity::model::channel::Message;
-use serenity::model::id::UserId;
+use serenity::model::id::{MessageId, UserId};
 use serenity::prelude::GatewayIntents;
 use simplelog::{CombinedLogger, ConfigBuilder, WriteLogger};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::env;
-use std::sync::Arc;
-use std::time::Duration;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc::{self, UnboundedReceiver};
 use tokio::time;
 
@@ -52,95 +62,82 @@ use {
 #[cfg(feature = "songbird")]
 pub(crate) const IS_SESSION_TRACKER_ENABLED: bool = false;
 
-pub(crate) const BURDBOT_TOKEN_NAME: &str = "BURDBOT_TOKEN";
-pub(crate) const BURDBOT_DB: &str = "burdbot.db";
-pub(crate) const DELIBURD_ID: u64 = 367538590520967181;
-pub(crate) const PREFIX: &str = ",";
-const BURDBOT_LOGGER_BUFFER_SIZE: usize = (1 << 10) * 32; // 32KB
-const DEFAULT_LOGGER_BUFFER_SIZE: usize = 1 << 10; // 1KB
-const LOGGER_WRITE_COOLDOWN: Duration = Duration::from_secs(15);
-const LOGGER_FAILED_FILE: &str = "failed-to-send-logs.txt";
-const LOGGER_FILE_NAME: &str = "log.txt";
-const RETRY_CONNECTION_INTERVAL: u64 = 30;
-
-fn create_sql_tables() {
-    let mut connection = Connection::open(BURDBOT_DB).unwrap();
-    let transaction = connection.transaction().unwrap();
-    let table_statements = "
-        CREATE TABLE IF NOT EXISTS times (
-            user_id INTEGER PRIMARY KEY,
-            time INTEGER NOT NULL
-        );
+lazy_static! {
+    /// When each running prefix command started, by the message that invoked it.
+    static ref COMMAND_START_TIMES: Mutex<HashMap<MessageId, Instant>> =
+        Mutex::new(HashMap::new());
+}
 
-        CREATE TABLE IF NOT EXISTS bday (
-            user_id INTEGER PRIMARY KEY,
-            guild_id INTEGER NOT NULL,
-            bday_date TEXT NOT NULL
-        );
+const CONFIG_FLAG: &str = "--config";
+const DRY_RUN_MIGRATIONS_FLAG: &str = "--dry-run-migrations";
+const USAGE: &str = "Usage: burdbot [--config <PATH>] [--dry-run-migrations]";
 
-        CREATE TABLE IF NOT EXISTS bday_user_list (
-            user_id INTEGER,
-            bday_over_date TEXT NOT NULL,
-            FOREIGN KEY (user_id) REFERENCES bday(user_id) ON DELETE CASCADE
-        );
-
-        CREATE TABLE IF NOT EXISTS bday_role_list (
-            guild_id INTEGER PRIMARY KEY,
-            role_id INTEGER NOT NULL
-        );
+struct CliArgs {
+    config_path: Option<PathBuf>,
+    dry_run_migrations: bool,
+}
 
-        CREATE TABLE IF NOT EXISTS staff_logs (
-            user_id INTEGER NOT NULL,
-            entry_id INTEGER NOT NULL,
-            original_link TEXT NOT NULL,
-            last_edited_link TEXT,
-            reason TEXT NOT NULL
-        );
+fn parse_cli_args() -> CliArgs {
+    let mut cli_args = CliArgs { config_path: None, dry_run_migrations: false };
+    let mut args = env::args().skip(1);
 
-        CREATE TABLE IF NOT EXISTS vocaroo_enabled (
-            guild_id INTEGER PRIMARY KEY
-        );
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            CONFIG_FLAG => {
+                let path = args.next().unwrap_or_else(|| panic!("Missing config path.\n{USAGE}"));
 
-        CREATE TABLE IF NOT EXISTS fxhash_image_checksums (
-            link_reference TEXT PRIMARY KEY,
-            width INTEGER NOT NULL,
-            height INTEGER NOT NULL,
-            description TEXT NOT NULL,
-            hash BLOB NOT NULL,
-            hash_type INTEGER NOT NULL,
-            guild_id INTEGER NOT NULL
-        );
+                cli_args.config_path = Some(PathBuf::from(path));
+            },
+            DRY_RUN_MIGRATIONS_FLAG => cli_args.dry_run_migrations = true,
+            _ => panic!("Unknown argument {arg}.\n{USAGE}"),
+        }
+    }
 
-        CREATE INDEX IF NOT EXISTS fxhash_checksum_index
-            on fxhash_image_checksums (guild_id, width, height);
+    cli_args
+}
 
-        CREATE INDEX IF NOT EXISTS bday_date_index
-            on bday (bday_date);
+fn migrate_database(connection: &mut Connection, dry_run: bool) {
+    let db_path = config::get().database.display();
+    let applied = match migrations::run_migrations(connection, dry_run) {
+        Ok(applied) => applied,
+        Err(err) => panic!("Couldn't migrate {db_path}: {err}"),
+    };
 
-        CREATE INDEX IF NOT EXISTS bday_over_date_index
-            on bday_user_list (bday_over_date);
+    if !dry_run {
+        return;
+    }
 
-        CREATE INDEX IF NOT EXISTS staff_log_index
-            on staff_logs (user_id);
-    ";
+    if applied.is_empty() {
+        println!("{db_path} is up to date at schema version {}.", migrations::latest_version());
+    }
 
-    transaction.execute_batch(table_statements).unwrap();
-    transaction.commit().unwrap();
+    for migration in applied {
+        println!("Would apply migration {}: {}", migration.version, migration.description);
+    }
 }
 
 pub(crate) fn on_cache_ready(ctx: &Context) {
-    setup_birthday_tracker(ctx.http.clone());
+    setup_birthday_tracker(ctx.clone());
+    birthday_tracker::setup_birthday_confirmation_expiry(ctx.clone());
 }
 
-fn setup_birthday_tracker(http: Arc<Http>) {
+fn setup_birthday_tracker(ctx: Context) {
     tokio::spawn(async move {
+        let db = database::get(&ctx).await;
+
+        // Updates could have been missed while the bot was down.
+        if let Err(error) = birthday_tracker::reconcile_birthday_roles(&ctx, &db).await {
+            birthday_tracker::handle_update_birthday_roles_error(&error);
+        }
+
         loop {
-            let seconds = 3600 - Utc::now().num_seconds_from_midnight() % 3600; // Get time in seconds until next hour.
+            let interval = birthday_tracker::UPDATE_INTERVAL;
+            let seconds = interval - Utc::now().num_seconds_from_midnight() % interval; // Get time in seconds until next update.
             let sleep_time = Duration::from_secs(seconds.into());
 
             time::sleep(sleep_time).await;
 
-            if let Err(error) = birthday_tracker::update_birthday_roles(&http).await {
+            if let Err(error) = birthday_tracker::update_birthday_roles(&ctx, &db).await {
                 birthday_tracker::handle_update_birthday_roles_error(&error);
             }
         }
@@ -153,8 +150,37 @@ async fn on_unrecognized_command(ctx: &Context, msg: &Message, _: &str) {
 }*/
 
 #[hook]
-async fn on_post_command(_: &Context, _: &Message, cmd: &str, result: CommandResult) {
-    info!("Result of {}{}: {:?}", PREFIX, cmd, result);
+async fn on_pre_command(_: &Context, msg: &Message, _: &str) -> bool {
+    COMMAND_START_TIMES.lock().unwrap().insert(msg.id, Instant::now());
+
+    true
+}
+
+#[hook]
+async fn on_post_command(ctx: &Context, msg: &Message, cmd: &str, result: CommandResult) {
+    let prefix = guild_config::get_prefix(ctx, msg.guild_id).await;
+
+    info!("Result of {prefix}{cmd}: {result:?}");
+
+    let start = COMMAND_START_TIMES.lock().unwrap().remove(&msg.id);
+    let duration = start.map_or(Duration::ZERO, |start| start.elapsed());
+    let error = result.as_ref().err();
+
+    commands::record_command_usage(
+        ctx, cmd, msg.guild_id, msg.channel_id, msg.author.id, error, duration,
+    )
+    .await;
+
+    metrics::COMMANDS.inc(&[cmd]);
+
+    if result.is_err() {
+        metrics::COMMAND_FAILURES.inc(&[cmd]);
+    }
+}
+
+#[hook]
+async fn dynamic_prefix(ctx: &Context, msg: &Message) -> Option<String> {
+    Some(guild_config::get_prefix(ctx, msg.guild_id).await)
 }
 
 async fn on_terminate(
@@ -179,19 +205,38 @@ async fn on_terminate(
 
 #[tokio::main]
 async fn main() {
+    let cli_args = parse_cli_args();
+
+    match config::Config::load(cli_args.config_path.as_deref()) {
+        Ok(config) => config::init(config),
+        Err(err) => panic!("{err}"),
+    }
+
+    let config = config::get();
+    let mut connection = Connection::open(&config.database).unwrap();
+
+    if cli_args.dry_run_migrations {
+        migrate_database(&mut connection, true);
+
+        return;
+    }
+
+    migrate_database(&mut connection, false);
+
+    let db = Database::new(connection);
+
     let mut owners_set = HashSet::with_capacity(1);
-    owners_set.insert(UserId::from(367538590520967181));
+    owners_set.insert(UserId::from(config.owner_id));
 
-    let framework = StandardFramework::new()
-        .bucket("default", BucketBuilder::new_user().delay(1).limit(5).time_span(10))
-        .await
-        .bucket("intense", BucketBuilder::new_user().delay(2).limit(2).time_span(10))
-        .await
-        .bucket("db_operations", BucketBuilder::new_user().delay(3).limit(3).time_span(10))
-        .await
-        .bucket("very_intense", BucketBuilder::new_user().delay(10).limit(4).time_span(600))
-        .await
+    let mut framework = StandardFramework::new();
+
+    for (name, bucket) in config.buckets.iter() {
+        framework = framework.bucket(name, bucket.to_builder()).await;
+    }
+
+    let framework = framework
         //.unrecognised_command(on_unrecognized_command)
+        .before(on_pre_command)
         .after(on_post_command)
         .help(&commands::HELP)
         .group(&commands::BIRTHDAY_GROUP)
@@ -199,27 +244,33 @@ async fn main() {
         .group(&commands::VOCAROO_GROUP)
         .group(&commands::CUSTOM_GROUP)
         .group(&commands::ADMINISTRATIVE_GROUP)
-        .group(&commands::LANGUAGE_GROUP);
+        .group(&commands::LANGUAGE_GROUP)
+        .group(&commands::SERVERCONFIG_GROUP);
 
     framework.configure(
         Configuration::new()
-            .prefix(PREFIX)
+            // No static prefix, otherwise the default one would work alongside a guild's own.
+            .prefix("")
+            .dynamic_prefix(dynamic_prefix)
             .with_whitespace(true)
             .case_insensitivity(true)
-            .owners(owners_set),
+            .owners(owners_set.clone()),
     );
 
+    let framework = BurdBotFramework::new(framework, owners_set);
+
     #[cfg(feature = "songbird")]
     let songbird_config = Config::default().decode_mode(DecodeMode::Decode);
 
-    create_sql_tables();
-
-    let token = env::var(BURDBOT_TOKEN_NAME).unwrap();
+    let token = env::var(&config.token_env_var).unwrap_or_else(|_| {
+        panic!("The {} environment variable must be set.", config.token_env_var)
+    });
 
     #[cfg(feature = "songbird")]
     let mut client = Client::builder(token, GatewayIntents::all())
         .framework(framework)
         .event_handler(BurdBotEventHandler)
+        .type_map_insert::<Database>(db)
         .register_songbird_from_config(songbird_config)
         .await
         .expect("Couldn't build client.");
@@ -228,6 +279,7 @@ async fn main() {
     let mut client = Client::builder(token, GatewayIntents::all())
         .framework(framework)
         .event_handler(BurdBotEventHandler)
+        .type_map_insert::<Database>(db)
         .await
         .expect("Couldn't build client.");
 
@@ -265,10 +317,10 @@ async fn main() {
             DiscordLogger::new(
                 cache.clone(),
                 http.clone(),
-                BURDBOT_LOGGER_BUFFER_SIZE,
-                LOGGER_FAILED_FILE,
-                LOGGER_FILE_NAME,
-                LOGGER_WRITE_COOLDOWN,
+                config.logger.burdbot_buffer_size,
+                &config.logger.failed_file,
+                &config.logger.file_name,
+                config.logger.write_cooldown(),
                 log_sender_mpsc_send.clone(),
             ),
         ),
@@ -278,10 +330,10 @@ async fn main() {
             DiscordLogger::new(
                 cache.clone(),
                 http.clone(),
-                DEFAULT_LOGGER_BUFFER_SIZE,
-                LOGGER_FAILED_FILE,
-                LOGGER_FILE_NAME,
-                LOGGER_WRITE_COOLDOWN,
+                config.logger.default_buffer_size,
+                &config.logger.failed_file,
+                &config.logger.file_name,
+                config.logger.write_cooldown(),
                 log_sender_mpsc_send,
             ),
         ),
@@ -294,12 +346,17 @@ async fn main() {
         on_terminate(shard_manager, log_sender_mpsc_recv).await;
     });
 
+    if config.metrics.enabled {
+        tokio::spawn(metrics::serve(&config.metrics.address));
+    }
+
     while let Err(err) = client.start().await {
         warn!(
-            "Error encountered starting Discord bot client: {err}\nRetrying in {RETRY_CONNECTION_INTERVAL} seconds."
+            "Error encountered starting Discord bot client: {err}\nRetrying in {} seconds.",
+            config.retry_connection_interval
         );
 
-        time::sleep(Duration::from_secs(RETRY_CONNECTION_INTERVAL)).await;
+        time::sleep(Duration::from_secs(config.retry_connection_interval)).await;
     }
 }
 
@@ -0,0 +1,432 @@
+//! Prometheus-style metrics.
+//!
+//! Metrics are statics that get updated wherever the thing they measure happens.
+//! [`serve`] exposes all of them in Prometheus' text format at `/metrics` so the bot's
+//! health can be scraped and graphed.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use log::{error, info};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+const SIZE_BUCKETS: &[f64] =
+    &[1024.0, 16384.0, 65536.0, 262144.0, 1048576.0, 4194304.0, 8388608.0, 26214400.0];
+
+pub static COMMANDS: CounterVec =
+    CounterVec::new("burdbot_commands_total", "Commands run, by command.", &["command"]);
+pub static COMMAND_FAILURES: CounterVec = CounterVec::new(
+    "burdbot_command_failures_total",
+    "Commands that returned an error, by command.",
+    &["command"],
+);
+pub static EVENT_HANDLER_SECONDS: HistogramVec = HistogramVec::new(
+    "burdbot_event_handler_seconds",
+    "Time spent handling gateway events, by event.",
+    &["event"],
+    LATENCY_BUCKETS,
+);
+pub static DOWNLOAD_BYTES: HistogramVec = HistogramVec::new(
+    "burdbot_download_bytes",
+    "Sizes of downloaded recordings, by source.",
+    &["source"],
+    SIZE_BUCKETS,
+);
+pub static DOWNLOAD_FAILURES: CounterVec = CounterVec::new(
+    "burdbot_download_failures_total",
+    "Recordings that failed to download, by source.",
+    &["source"],
+);
+pub static BANNED_IMAGE_HITS: Counter =
+    Counter::new("burdbot_banned_image_hits_total", "Messages found with a banned image.");
+pub static BIRTHDAY_ROLE_UPDATES: CounterVec = CounterVec::new(
+    "burdbot_birthday_role_updates_total",
+    "Birthday roles added or removed by the periodic update, by action and result.",
+    &["action", "result"],
+);
+pub static LOGGER_DROPPED_BYTES: Counter = Counter::new(
+    "burdbot_logger_dropped_bytes_total",
+    "Log bytes dropped because the Discord logger's buffer was full.",
+);
+
+static METRICS: &[&dyn Metric] = &[
+    &COMMANDS, &COMMAND_FAILURES, &EVENT_HANDLER_SECONDS, &DOWNLOAD_BYTES, &DOWNLOAD_FAILURES,
+    &BANNED_IMAGE_HITS, &BIRTHDAY_ROLE_UPDATES, &LOGGER_DROPPED_BYTES,
+];
+
+trait Metric: Sync {
+    fn render(&self, out: &mut String);
+}
+
+fn write_header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// Formats label pairs as `{name="value",...}`, or nothing if there are none.
+fn format_labels<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    let labels = pairs
+        .into_iter()
+        .map(|(name, value)| {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+
+            format!("{name}=\"{value}\"")
+        })
+        .collect::<Vec<_>>();
+
+    if labels.is_empty() { String::new() } else { format!("{{{}}}", labels.join(",")) }
+}
+
+fn label_pairs<'a>(
+    names: &'a [&'static str], values: &'a [String],
+) -> impl Iterator<Item = (&'a str, &'a str)> {
+    names.iter().copied().zip(values.iter().map(String::as_str))
+}
+
+fn to_label_values(names: &[&'static str], values: &[&str]) -> Vec<String> {
+    debug_assert_eq!(names.len(), values.len(), "Wrong number of label values.");
+
+    values.iter().map(|&value| value.to_owned()).collect()
+}
+
+pub struct Counter {
+    name: &'static str,
+    help: &'static str,
+    value: AtomicU64,
+}
+
+impl Counter {
+    const fn new(name: &'static str, help: &'static str) -> Self {
+        Counter { name, help, value: AtomicU64::new(0) }
+    }
+
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    pub fn inc_by(&self, amount: u64) {
+        self.value.fetch_add(amount, Ordering::Relaxed);
+    }
+}
+
+impl Metric for Counter {
+    fn render(&self, out: &mut String) {
+        write_header(out, self.name, self.help, "counter");
+
+        let _ = writeln!(out, "{} {}", self.name, self.value.load(Ordering::Relaxed));
+    }
+}
+
+/// A counter for each combination of label values.
+pub struct CounterVec {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    values: Mutex<BTreeMap<Vec<String>, u64>>,
+}
+
+impl CounterVec {
+    const fn new(name: &'static str, help: &'static str, labels: &'static [&'static str]) -> Self {
+        CounterVec { name, help, labels, values: Mutex::new(BTreeMap::new()) }
+    }
+
+    /// Increments the counter for `label_values`, given in the same order as the label names.
+    pub fn inc(&self, label_values: &[&str]) {
+        let key = to_label_values(self.labels, label_values);
+        let mut values = self.values.lock().unwrap_or_else(PoisonError::into_inner);
+
+        *values.entry(key).or_default() += 1;
+    }
+}
+
+impl Metric for CounterVec {
+    fn render(&self, out: &mut String) {
+        write_header(out, self.name, self.help, "counter");
+
+        let values = self.values.lock().unwrap_or_else(PoisonError::into_inner);
+
+        for (label_values, value) in values.iter() {
+            let labels = format_labels(label_pairs(self.labels, label_values));
+
+            let _ = writeln!(out, "{}{labels} {value}", self.name);
+        }
+    }
+}
+
+#[derive(Default)]
+struct HistogramData {
+    /// Observations in each bucket, not including the ones in smaller buckets.
+    bucket_counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+/// A histogram for each combination of label values.
+pub struct HistogramVec {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    buckets: &'static [f64],
+    values: Mutex<BTreeMap<Vec<String>, HistogramData>>,
+}
+
+impl HistogramVec {
+    const fn new(
+        name: &'static str, help: &'static str, labels: &'static [&'static str],
+        buckets: &'static [f64],
+    ) -> Self {
+        HistogramVec { name, help, labels, buckets, values: Mutex::new(BTreeMap::new()) }
+    }
+
+    pub fn observe(&self, label_values: &[&str], value: f64) {
+        let key = to_label_values(self.labels, label_values);
+        let mut values = self.values.lock().unwrap_or_else(PoisonError::into_inner);
+        let data = values.entry(key).or_default();
+
+        data.bucket_counts.resize(self.buckets.len(), 0);
+
+        if let Some(bucket) = self.buckets.iter().position(|&bound| value <= bound) {
+            data.bucket_counts[bucket] += 1;
+        }
+
+        data.sum += value;
+        data.count += 1;
+    }
+
+    /// Starts timing something. The elapsed seconds get observed once the timer is dropped.
+    pub fn start_timer<'a>(&'a self, label_values: &'a [&'a str]) -> HistogramTimer<'a> {
+        HistogramTimer { histogram: self, label_values, start: Instant::now() }
+    }
+}
+
+impl Metric for HistogramVec {
+    fn render(&self, out: &mut String) {
+        write_header(out, self.name, self.help, "histogram");
+
+        let values = self.values.lock().unwrap_or_else(PoisonError::into_inner);
+
+        for (label_values, data) in values.iter() {
+            let mut cumulative_count = 0;
+
+            for (bound, count) in self.buckets.iter().zip(&data.bucket_counts) {
+                let bound = bound.to_string();
+                let labels = label_pairs(self.labels, label_values).chain([("le", &*bound)]);
+
+                cumulative_count += count;
+
+                let _ = writeln!(
+                    out,
+                    "{}_bucket{} {cumulative_count}",
+                    self.name,
+                    format_labels(labels)
+                );
+            }
+
+            let labels = label_pairs(self.labels, label_values).chain([("le", "+Inf")]);
+            let _ = writeln!(out, "{}_bucket{} {}", self.name, format_labels(labels), data.count);
+
+            let labels = format_labels(label_pairs(self.labels, label_values));
+            let _ = writeln!(out, "{}_sum{labels} {}", self.name, data.sum);
+            let _ = writeln!(out, "{}_count{labels} {}", self.name, data.count);
+        }
+    }
+}
+
+pub struct HistogramTimer<'a> {
+    histogram: &'a HistogramVec,
+    label_values: &'a [&'a str],
+    start: Instant,
+}
+
+impl Drop for HistogramTimer<'_> {
+    fn drop(&mut self) {
+        self.histogram.observe(self.label_values, self.start.elapsed().as_secs_f64());
+    }
+}
+
+/// Renders every metric in Prometheus' text format.
+pub fn render() -> String {
+    let mut out = String::new();
+
+    for metric in METRICS {
+        metric.render(&mut out);
+    }
+
+    out
+}
+
+/// The most bytes read of a request's line and headers before giving up on it.
+const MAX_REQUEST_HEAD_SIZE: usize = 8 * 1024;
+
+/// How long a client gets to send its request's line and headers, so that connections that
+/// never finish one don't stay open forever.
+const REQUEST_HEAD_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Reads a request up to the blank line after its headers, however many reads that takes.
+/// Returns `None` if the connection closes first, the headers are too long or they take longer
+/// than `timeout` to arrive.
+async fn read_request_head(
+    reader: &mut (impl AsyncRead + Unpin), timeout: Duration,
+) -> std::io::Result<Option<String>> {
+    tokio::time::timeout(timeout, read_until_blank_line(reader)).await.unwrap_or(Ok(None))
+}
+
+async fn read_until_blank_line(
+    reader: &mut (impl AsyncRead + Unpin),
+) -> std::io::Result<Option<String>> {
+    let mut head = Vec::new();
+    let mut buffer = [0; 1024];
+
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_HEAD_SIZE {
+            return Ok(None);
+        }
+
+        let read = reader.read(&mut buffer).await?;
+
+        if read == 0 {
+            return Ok(None);
+        }
+
+        head.extend_from_slice(&buffer[..read]);
+    }
+
+    Ok(Some(String::from_utf8_lossy(&head).into_owned()))
+}
+
+async fn respond(mut stream: TcpStream) -> std::io::Result<()> {
+    let Some(request) = read_request_head(&mut stream, REQUEST_HEAD_TIMEOUT).await? else {
+        return stream.shutdown().await;
+    };
+    let (status, content_type, body) = match request.split_whitespace().take(2).collect::<Vec<_>>()
+        [..]
+    {
+        ["GET", "/metrics"] => ("200 OK", "text/plain; version=0.0.4", render()),
+        _ => ("404 Not Found", "text/plain", "Not found. Metrics are at /metrics.\n".to_owned()),
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+        Connection: close\r\n\r\n{body}",
+        body.len()
+    );
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Serves the metrics over HTTP at `address` until the bot stops.
+pub async fn serve(address: &str) {
+    let listener = match TcpListener::bind(address).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            error!("Couldn't start the metrics endpoint at {address}: {err:?}");
+
+            return;
+        },
+    };
+
+    info!("Serving metrics at http://{address}/metrics");
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                error!("Couldn't accept a metrics connection: {err:?}");
+                continue;
+            },
+        };
+
+        tokio::spawn(async move {
+            if let Err(err) = respond(stream).await {
+                info!("Couldn't respond to a metrics request: {err:?}");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_labelled_counters() {
+        let counter = CounterVec::new("test_total", "Test counter.", &["command"]);
+
+        counter.inc(&["pronounce"]);
+        counter.inc(&["pronounce"]);
+        counter.inc(&["say \"hi\""]);
+
+        let mut out = String::new();
+
+        counter.render(&mut out);
+
+        assert_eq!(
+            out,
+            "# HELP test_total Test counter.\n\
+            # TYPE test_total counter\n\
+            test_total{command=\"pronounce\"} 2\n\
+            test_total{command=\"say \\\"hi\\\"\"} 1\n"
+        );
+    }
+
+    #[test]
+    fn renders_cumulative_histogram_buckets() {
+        let histogram =
+            HistogramVec::new("test_bytes", "Test histogram.", &["source"], &[1.0, 10.0]);
+
+        histogram.observe(&["forvo"], 0.5);
+        histogram.observe(&["forvo"], 5.0);
+        histogram.observe(&["forvo"], 50.0);
+
+        let mut out = String::new();
+
+        histogram.render(&mut out);
+
+        assert!(out.contains("test_bytes_bucket{source=\"forvo\",le=\"1\"} 1\n"));
+        assert!(out.contains("test_bytes_bucket{source=\"forvo\",le=\"10\"} 2\n"));
+        assert!(out.contains("test_bytes_bucket{source=\"forvo\",le=\"+Inf\"} 3\n"));
+        assert!(out.contains("test_bytes_sum{source=\"forvo\"} 55.5\n"));
+        assert!(out.contains("test_bytes_count{source=\"forvo\"} 3\n"));
+    }
+
+    #[tokio::test]
+    async fn request_heads_can_span_reads() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+
+        tokio::spawn(async move {
+            client.write_all(b"GET /met").await.unwrap();
+            client.write_all(b"rics HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+        });
+
+        let head = read_request_head(&mut server, REQUEST_HEAD_TIMEOUT).await.unwrap().unwrap();
+
+        assert!(head.starts_with("GET /metrics HTTP/1.1\r\n"));
+
+        let long_header = format!("GET / HTTP/1.1\r\nX: {}\r\n\r\n", "a".repeat(10_000));
+
+        assert_eq!(
+            read_request_head(&mut long_header.as_bytes(), REQUEST_HEAD_TIMEOUT).await.unwrap(),
+            None
+        );
+        assert_eq!(
+            read_request_head(&mut &b"GET /metrics"[..], REQUEST_HEAD_TIMEOUT).await.unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn unfinished_request_heads_time_out() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+
+        client.write_all(b"GET /metrics HTTP/1.1\r\n").await.unwrap();
+
+        let head = read_request_head(&mut server, Duration::from_millis(50)).await.unwrap();
+
+        assert_eq!(head, None);
+    }
+}
@@ -0,0 +1,1044 @@
+//! Versioned schema migrations.
+//!
+//! The schema version is kept in SQLite's `user_version` pragma. Every migration
+//! after the current version gets applied in order within a single transaction,
+//! so either the whole upgrade goes through or nothing does.
+//!
+//! Databases from before migrations existed have a `user_version` of 0. The first
+//! migration only uses `IF NOT EXISTS` statements so it's a no-op for them.
+//!
+//! To add a migration, append it to [`MIGRATIONS`] with the next version number.
+//! Never edit or reorder a migration that has already been released.
+
+use std::num::NonZeroU64;
+
+use chrono::{Datelike, FixedOffset, NaiveDate, NaiveDateTime, Utc};
+use log::info;
+use rusqlite::{Connection, Transaction, params};
+use serenity::all::MessageId;
+use thiserror::Error;
+
+use crate::birthday_tracker::BirthdayTimeZone;
+
+pub type Result<T> = std::result::Result<T, MigrationError>;
+
+#[derive(Error, Debug)]
+pub enum MigrationError {
+    #[error("SQLite error encountered while migrating: {0:?}")]
+    SQLiteError(#[from] rusqlite::Error),
+    #[error(
+        "The database is at schema version {found}, but the latest version this build knows \
+        of is {latest}. Refusing to run on a database from a newer version of BurdBot."
+    )]
+    UnknownVersion { found: u32, latest: u32 },
+    #[error(
+        "These rows of `{table}` can't be migrated. Fix or remove them, then try again:\n{}",
+        .rows.join("\n")
+    )]
+    InvalidRows { table: &'static str, rows: Vec<String> },
+}
+
+enum MigrationStep {
+    Sql(&'static str),
+    Rust(fn(&Transaction<'_>) -> Result<()>),
+}
+
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    step: MigrationStep,
+}
+
+impl Migration {
+    fn apply(&self, transaction: &Transaction<'_>) -> Result<()> {
+        match self.step {
+            MigrationStep::Sql(sql) => Ok(transaction.execute_batch(sql)?),
+            MigrationStep::Rust(migrate) => migrate(transaction),
+        }
+    }
+}
+
+const INITIAL_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS times (
+        user_id INTEGER PRIMARY KEY,
+        time INTEGER NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS bday (
+        user_id INTEGER PRIMARY KEY,
+        guild_id INTEGER NOT NULL,
+        bday_date TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS bday_user_list (
+        user_id INTEGER,
+        bday_over_date TEXT NOT NULL,
+        FOREIGN KEY (user_id) REFERENCES bday(user_id) ON DELETE CASCADE
+    );
+
+    CREATE TABLE IF NOT EXISTS bday_role_list (
+        guild_id INTEGER PRIMARY KEY,
+        role_id INTEGER NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS staff_logs (
+        user_id INTEGER NOT NULL,
+        entry_id INTEGER NOT NULL,
+        original_link TEXT NOT NULL,
+        last_edited_link TEXT,
+        reason TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS vocaroo_enabled (
+        guild_id INTEGER PRIMARY KEY
+    );
+
+    CREATE TABLE IF NOT EXISTS fxhash_image_checksums (
+        link_reference TEXT PRIMARY KEY,
+        width INTEGER NOT NULL,
+        height INTEGER NOT NULL,
+        description TEXT NOT NULL,
+        hash BLOB NOT NULL,
+        hash_type INTEGER NOT NULL,
+        guild_id INTEGER NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS fxhash_checksum_index
+        on fxhash_image_checksums (guild_id, width, height);
+
+    CREATE INDEX IF NOT EXISTS bday_date_index
+        on bday (bday_date);
+
+    CREATE INDEX IF NOT EXISTS bday_over_date_index
+        on bday_user_list (bday_over_date);
+
+    CREATE INDEX IF NOT EXISTS staff_log_index
+        on staff_logs (user_id);
+";
+
+/// Creates the guild config table. The settings that used to be hardcoded only get
+/// seeded when the table is new so that keys an admin has unset don't come back.
+fn create_guild_config(transaction: &Transaction<'_>) -> Result<()> {
+    let has_guild_config = transaction.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'guild_config');",
+        [],
+        |row| row.get::<_, bool>(0),
+    )?;
+
+    if has_guild_config {
+        return Ok(());
+    }
+
+    transaction.execute_batch(
+        "
+        CREATE TABLE guild_config (
+            guild_id INTEGER NOT NULL,
+            key TEXT NOT NULL,
+            value TEXT NOT NULL,
+            PRIMARY KEY (guild_id, key)
+        );
+        ",
+    )?;
+
+    Ok(crate::guild_config::seed_guild_configs(transaction)?)
+}
+
+const COMMAND_USAGE: &str = "
+    CREATE TABLE command_usage (
+        id INTEGER PRIMARY KEY,
+        command TEXT NOT NULL,
+        guild_id INTEGER,
+        channel_id INTEGER NOT NULL,
+        user_id INTEGER NOT NULL,
+        error_kind TEXT,
+        duration_ms INTEGER NOT NULL,
+        used_at INTEGER NOT NULL
+    );
+
+    CREATE INDEX command_usage_guild_index
+        on command_usage (guild_id, used_at);
+";
+
+/// Birthday times gain minutes, for time zones that are offset by part of an hour.
+const BIRTHDAY_MINUTES: &str = "
+    UPDATE bday SET bday_date = bday_date || '-00';
+    UPDATE bday_user_list SET bday_over_date = bday_over_date || '-00';
+";
+
+/// Feb 29 birthdays get moved to another day in other years, so they have to be told apart.
+const LEAP_DAY_BIRTHDAYS: &str = "
+    ALTER TABLE bday ADD COLUMN leap_day INTEGER NOT NULL DEFAULT 0;
+";
+
+/// The birthday updater runs several times within a birthday's first day, and every run used
+/// to add another ongoing row for it.
+const UNIQUE_ONGOING_BIRTHDAYS: &str = "
+    DELETE FROM bday_user_list
+    WHERE rowid NOT IN (SELECT MAX(rowid) FROM bday_user_list GROUP BY user_id);
+
+    CREATE UNIQUE INDEX bday_user_list_user_index
+        on bday_user_list (user_id);
+";
+
+const LOCAL_BIRTHDAY_TABLES: &str = "
+    DROP TABLE bday_user_list;
+    DROP TABLE bday;
+
+    CREATE TABLE bday (
+        user_id INTEGER PRIMARY KEY,
+        guild_id INTEGER NOT NULL,
+        month INTEGER NOT NULL,
+        day INTEGER NOT NULL,
+        time_zone TEXT NOT NULL
+    );
+
+    CREATE TABLE bday_user_list (
+        user_id INTEGER PRIMARY KEY,
+        ends_at INTEGER NOT NULL,
+        FOREIGN KEY (user_id) REFERENCES bday(user_id) ON DELETE CASCADE
+    );
+
+    CREATE INDEX bday_user_list_ends_at_index
+        on bday_user_list (ends_at);
+";
+
+/// Birthdays belong to users instead of the guild they were set in, and are ongoing separately
+/// in each guild. Ongoing birthdays stay in the guild their birthday was set in.
+const BIRTHDAYS_IN_EVERY_GUILD: &str = "
+    ALTER TABLE bday_user_list RENAME TO old_bday_user_list;
+
+    CREATE TABLE bday_user_list (
+        user_id INTEGER NOT NULL,
+        guild_id INTEGER NOT NULL,
+        ends_at INTEGER NOT NULL,
+        PRIMARY KEY (user_id, guild_id),
+        FOREIGN KEY (user_id) REFERENCES bday(user_id) ON DELETE CASCADE
+    );
+
+    INSERT INTO bday_user_list (user_id, guild_id, ends_at)
+        SELECT old_bday_user_list.user_id, bday.guild_id, old_bday_user_list.ends_at
+        FROM old_bday_user_list
+            INNER JOIN bday ON old_bday_user_list.user_id = bday.user_id;
+
+    DROP TABLE old_bday_user_list;
+
+    CREATE INDEX bday_user_list_ends_at_index
+        on bday_user_list (ends_at);
+
+    ALTER TABLE bday DROP COLUMN guild_id;
+
+    CREATE TABLE bday_opt_outs (
+        user_id INTEGER NOT NULL,
+        guild_id INTEGER NOT NULL,
+        PRIMARY KEY (user_id, guild_id)
+    );
+";
+
+/// Birthday announcements already posted, so that they don't get posted again.
+const BIRTHDAY_ANNOUNCEMENTS: &str = "
+    CREATE TABLE bday_announcements (
+        user_id INTEGER NOT NULL,
+        guild_id INTEGER NOT NULL,
+        ends_at INTEGER NOT NULL,
+        PRIMARY KEY (user_id, guild_id, ends_at)
+    );
+";
+
+/// Birthdays waiting for their author to press a button, so that they survive restarts.
+const BIRTHDAY_CONFIRMATIONS: &str = "
+    CREATE TABLE bday_confirmations (
+        message_id INTEGER PRIMARY KEY,
+        channel_id INTEGER NOT NULL,
+        guild_id INTEGER NOT NULL,
+        author_id INTEGER NOT NULL,
+        user_id INTEGER NOT NULL,
+        month INTEGER NOT NULL,
+        day INTEGER NOT NULL,
+        time_zone TEXT NOT NULL,
+        is_privileged INTEGER NOT NULL,
+        expires_at INTEGER NOT NULL
+    );
+
+    CREATE INDEX bday_confirmations_expires_at_index
+        on bday_confirmations (expires_at);
+";
+
+/// Birth years are optional. Visibility is kept with pending birthdays too, and is null there
+/// when the existing birthday's details should be kept.
+const BIRTHDAY_DETAILS: &str = "
+    ALTER TABLE bday ADD COLUMN birth_year INTEGER;
+    ALTER TABLE bday ADD COLUMN visibility TEXT NOT NULL DEFAULT 'public';
+
+    ALTER TABLE bday_confirmations ADD COLUMN birth_year INTEGER;
+    ALTER TABLE bday_confirmations ADD COLUMN visibility TEXT;
+";
+
+/// Staff logs used to be shared between guilds.
+const GUILD_STAFF_LOG_TABLES: &str = "
+    ALTER TABLE staff_logs RENAME TO old_staff_logs;
+
+    CREATE TABLE staff_logs (
+        guild_id INTEGER NOT NULL,
+        user_id INTEGER NOT NULL,
+        entry_id INTEGER NOT NULL,
+        original_link TEXT NOT NULL,
+        last_edited_link TEXT,
+        reason TEXT NOT NULL
+    );
+";
+
+/// Staff logs get IDs that don't change when earlier entries are removed, so that their
+/// revisions can refer to them. They're never reused, so revisions of removed entries stay
+/// apart from newer ones.
+const STAFF_LOG_REVISIONS: &str = "
+    ALTER TABLE staff_logs RENAME TO old_staff_logs;
+
+    CREATE TABLE staff_logs (
+        log_id INTEGER PRIMARY KEY AUTOINCREMENT,
+        guild_id INTEGER NOT NULL,
+        user_id INTEGER NOT NULL,
+        entry_id INTEGER NOT NULL,
+        original_link TEXT NOT NULL,
+        last_edited_link TEXT,
+        reason TEXT NOT NULL
+    );
+
+    INSERT INTO staff_logs (guild_id, user_id, entry_id, original_link, last_edited_link, reason)
+        SELECT guild_id, user_id, entry_id, original_link, last_edited_link, reason
+        FROM old_staff_logs
+        ORDER BY guild_id, user_id, entry_id;
+
+    DROP TABLE old_staff_logs;
+
+    CREATE INDEX staff_log_index
+        on staff_logs (guild_id, user_id);
+
+    CREATE TABLE staff_log_revisions (
+        revision_id INTEGER PRIMARY KEY,
+        log_id INTEGER NOT NULL,
+        author_id INTEGER,
+        link TEXT NOT NULL,
+        reason TEXT NOT NULL,
+        revised_at INTEGER NOT NULL
+    );
+
+    CREATE INDEX staff_log_revisions_log_index
+        on staff_log_revisions (log_id);
+";
+
+/// Removed staff log entries are kept, so entry numbers can be unique.
+const STAFF_LOG_DELETIONS: &str = "
+    ALTER TABLE staff_logs ADD COLUMN deleted_by INTEGER;
+    ALTER TABLE staff_logs ADD COLUMN deleted_at INTEGER;
+
+    DROP INDEX staff_log_index;
+
+    CREATE UNIQUE INDEX staff_log_entry_index
+        on staff_logs (guild_id, user_id, entry_id);
+";
+
+/// Entries from before categories existed are low severity notes.
+const STAFF_LOG_CATEGORIES: &str = "
+    ALTER TABLE staff_logs ADD COLUMN category TEXT NOT NULL DEFAULT 'note';
+    ALTER TABLE staff_logs ADD COLUMN severity TEXT NOT NULL DEFAULT 'low';
+";
+
+/// Staff log reasons get a full-text index for searching, which the triggers keep up to date
+/// with the table. Entries also keep when they were logged, so searches can be limited to dates.
+const STAFF_LOG_SEARCH: &str = "
+    ALTER TABLE staff_logs ADD COLUMN logged_at INTEGER NOT NULL DEFAULT 0;
+
+    CREATE INDEX staff_log_logged_at_index
+        on staff_logs (guild_id, logged_at);
+
+    CREATE VIRTUAL TABLE staff_log_search USING fts5(
+        reason,
+        content = 'staff_logs',
+        content_rowid = 'log_id'
+    );
+
+    CREATE TRIGGER staff_log_search_insert AFTER INSERT ON staff_logs BEGIN
+        INSERT INTO staff_log_search (rowid, reason) VALUES (new.log_id, new.reason);
+    END;
+
+    CREATE TRIGGER staff_log_search_delete AFTER DELETE ON staff_logs BEGIN
+        INSERT INTO staff_log_search (staff_log_search, rowid, reason)
+            VALUES ('delete', old.log_id, old.reason);
+    END;
+
+    CREATE TRIGGER staff_log_search_update AFTER UPDATE OF reason ON staff_logs BEGIN
+        INSERT INTO staff_log_search (staff_log_search, rowid, reason)
+            VALUES ('delete', old.log_id, old.reason);
+        INSERT INTO staff_log_search (rowid, reason) VALUES (new.log_id, new.reason);
+    END;
+
+    INSERT INTO staff_log_search (staff_log_search) VALUES ('rebuild');
+";
+
+/// Parses a `MM-DD-HH-MM` time of year.
+fn parse_time_of_year(time_of_year: &str) -> Option<(u32, u32, u32, u32)> {
+    let parts =
+        time_of_year.split('-').map(|part| part.parse().ok()).collect::<Option<Vec<u32>>>()?;
+
+    match parts[..] {
+        [month, day, hour, minute] => Some((month, day, hour, minute)),
+        _ => None,
+    }
+}
+
+/// Recovers the local date and UTC offset of a birthday from the UTC time it starts at, or says
+/// why it can't be. Offsets used to go from -11:00 to +14:00, so starts before 10:00 are
+/// negative offsets on the same day and ones after 11:00 are positive offsets on the next day.
+/// Starts from 10:00 to 11:00 could be either, which would change the date, so they're refused.
+fn to_local_birthday(
+    bday_date: &str, is_leap_day: bool,
+) -> std::result::Result<(u32, u32, String), String> {
+    let invalid = || "isn't a valid time of year".to_owned();
+    let time_zone = |offset_minutes: i32| {
+        FixedOffset::east_opt(offset_minutes * 60)
+            .map(|offset| BirthdayTimeZone::Offset(offset).to_string())
+            .ok_or_else(invalid)
+    };
+    let (month, day, hour, minute) = parse_time_of_year(bday_date).ok_or_else(invalid)?;
+    let start_minutes = (hour * 60 + minute) as i32;
+    // A leap year, so that starts on Feb 29 can be read
+    let start_date = NaiveDate::from_ymd_opt(2024, month, day).ok_or_else(invalid)?;
+    let (date, offset_minutes) = match start_minutes {
+        ..600 => (start_date, -start_minutes),
+        600..=660 => {
+            let (behind, ahead) = (time_zone(-start_minutes)?, time_zone(24 * 60 - start_minutes)?);
+
+            return Err(format!("could be {behind} on that day or {ahead} on the next"));
+        },
+        _ => (start_date.succ_opt().ok_or_else(invalid)?, 24 * 60 - start_minutes),
+    };
+    let (month, day) = match (date.month(), date.day()) {
+        _ if is_leap_day => (2, 29),
+        // Only Feb 29 birthdays were marked, so this was worked out for Mar 1 in a leap year.
+        (2, 29) => (3, 1),
+        month_day => month_day,
+    };
+    Ok((month, day, time_zone(offset_minutes)?))
+}
+
+/// The occurrence of a `MM-DD-HH-MM` time of year in UTC that's closest to `now`.
+fn nearest_occurrence(time_of_year: &str, now: NaiveDateTime) -> Option<i64> {
+    let (month, day, hour, minute) = parse_time_of_year(time_of_year)?;
+
+    (now.year() - 1..=now.year() + 1)
+        .filter_map(|year| NaiveDate::from_ymd_opt(year, month, day)?.and_hms_opt(hour, minute, 0))
+        .min_by_key(|date_time| (*date_time - now).abs())
+        .map(|date_time| date_time.and_utc().timestamp())
+}
+
+/// Birthdays used to be stored as the UTC time of year they start at, worked out once from
+/// the offset given, so they couldn't follow daylight saving time. They're now stored as the
+/// local date and time zone, and ongoing birthdays get the exact time they end at. Nothing is
+/// migrated if any of the dates are invalid or ambiguous.
+fn store_local_birthdays(transaction: &Transaction<'_>) -> Result<()> {
+    let bdays = transaction
+        .prepare("SELECT user_id, guild_id, bday_date, leap_day FROM bday;")?
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, u64>(0)?,
+                row.get::<_, u64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, bool>(3)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let ongoing_bdays = transaction
+        .prepare("SELECT user_id, bday_over_date FROM bday_user_list;")?
+        .query_map([], |row| Ok((row.get::<_, u64>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut local_bdays = Vec::with_capacity(bdays.len());
+    let mut invalid_rows = Vec::new();
+
+    for (user_id, guild_id, bday_date, is_leap_day) in bdays {
+        match to_local_birthday(&bday_date, is_leap_day) {
+            Ok(local_bday) => local_bdays.push((user_id, guild_id, local_bday)),
+            Err(reason) => {
+                invalid_rows.push(format!("user_id {user_id}: bday_date '{bday_date}' {reason}"))
+            },
+        }
+    }
+
+    if !invalid_rows.is_empty() {
+        return Err(MigrationError::InvalidRows { table: "bday", rows: invalid_rows });
+    }
+
+    transaction.execute_batch(LOCAL_BIRTHDAY_TABLES)?;
+
+    for (user_id, guild_id, (month, day, time_zone)) in local_bdays {
+        transaction.execute(
+            "INSERT INTO bday (user_id, guild_id, month, day, time_zone) VALUES (?, ?, ?, ?, ?);",
+            params![user_id, guild_id, month, day, time_zone],
+        )?;
+    }
+
+    let now = Utc::now().naive_utc();
+
+    for (user_id, bday_over_date) in ongoing_bdays {
+        if let Some(ends_at) = nearest_occurrence(&bday_over_date, now) {
+            transaction.execute(
+                "INSERT INTO bday_user_list (user_id, ends_at) VALUES (?, ?);",
+                params![user_id, ends_at],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets the guild ID out of a message link like `https://discord.com/channels/1/2/3`.
+fn guild_id_from_link(link: &str) -> Option<u64> {
+    let (_, path) = link.split_once("/channels/")?;
+
+    path.split('/').next()?.parse().ok()
+}
+
+/// Staff logs used to be numbered per user across every guild, so they're moved to the guild
+/// their original message was sent in. They keep their numbers, which are still unique there.
+/// Nothing is migrated if any of the links don't lead to a guild.
+fn scope_staff_logs_to_guilds(transaction: &Transaction<'_>) -> Result<()> {
+    transaction.execute_batch(GUILD_STAFF_LOG_TABLES)?;
+
+    let logs = transaction
+        .prepare(
+            "
+            SELECT user_id, entry_id, original_link, last_edited_link, reason
+            FROM old_staff_logs
+            ORDER BY user_id, entry_id;
+            ",
+        )?
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, u64>(0)?,
+                row.get::<_, u32>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let mut guild_logs = Vec::with_capacity(logs.len());
+    let mut invalid_rows = Vec::new();
+
+    for (user_id, entry_id, original_link, last_edited_link, reason) in logs {
+        match guild_id_from_link(&original_link) {
+            Some(guild_id) => guild_logs
+                .push((guild_id, user_id, entry_id, original_link, last_edited_link, reason)),
+            None => invalid_rows.push(format!(
+                "user_id {user_id}, entry_id {entry_id}: original_link '{original_link}'"
+            )),
+        }
+    }
+
+    if !invalid_rows.is_empty() {
+        return Err(MigrationError::InvalidRows { table: "staff_logs", rows: invalid_rows });
+    }
+
+    for (guild_id, user_id, entry_id, original_link, last_edited_link, reason) in guild_logs {
+        transaction.execute(
+            "INSERT INTO staff_logs VALUES (?, ?, ?, ?, ?, ?);",
+            params![guild_id, user_id, entry_id, original_link, last_edited_link, reason],
+        )?;
+    }
+
+    transaction.execute_batch(
+        "
+        DROP TABLE old_staff_logs;
+
+        CREATE INDEX staff_log_index
+            on staff_logs (guild_id, user_id);
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// When the message a link leads to was sent.
+fn message_time_from_link(link: &str) -> Option<i64> {
+    let message_id = link.trim_end_matches('/').rsplit('/').next()?.parse::<NonZeroU64>().ok()?;
+
+    Some(MessageId::from(message_id).created_at().timestamp())
+}
+
+/// Starts keeping every version of staff log reasons. Only the latest version of existing
+/// entries is known, so that's their first revision, with an unknown author.
+fn add_staff_log_revisions(transaction: &Transaction<'_>) -> Result<()> {
+    transaction.execute_batch(STAFF_LOG_REVISIONS)?;
+
+    let logs = transaction
+        .prepare("SELECT log_id, original_link, last_edited_link, reason FROM staff_logs;")?
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    for (log_id, original_link, last_edited_link, reason) in logs {
+        let link = last_edited_link.unwrap_or(original_link);
+        let revised_at = message_time_from_link(&link).unwrap_or_else(|| Utc::now().timestamp());
+
+        transaction.execute(
+            "INSERT INTO staff_log_revisions (log_id, link, reason, revised_at) \
+                VALUES (?, ?, ?, ?);",
+            params![log_id, link, reason, revised_at],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Indexes staff logs for searching. Existing entries were logged when their original message
+/// was sent, or when their first known revision was if the link is broken.
+fn add_staff_log_search(transaction: &Transaction<'_>) -> Result<()> {
+    transaction.execute_batch(STAFF_LOG_SEARCH)?;
+
+    let logs = transaction
+        .prepare(
+            "
+            SELECT log_id, original_link,
+            (
+                SELECT MIN(revised_at)
+                FROM staff_log_revisions
+                WHERE log_id = staff_logs.log_id
+            )
+            FROM staff_logs;
+            ",
+        )?
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<i64>>(2)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    for (log_id, original_link, first_revised_at) in logs {
+        let logged_at = message_time_from_link(&original_link).or(first_revised_at).unwrap_or(0);
+
+        transaction.execute(
+            "UPDATE staff_logs SET logged_at = ? WHERE log_id = ?;",
+            params![logged_at, log_id],
+        )?;
+    }
+
+    Ok(())
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Initial schema",
+        step: MigrationStep::Sql(INITIAL_SCHEMA),
+    },
+    Migration {
+        version: 2,
+        description: "Add per-guild configuration",
+        step: MigrationStep::Rust(create_guild_config),
+    },
+    Migration {
+        version: 3,
+        description: "Add command usage analytics",
+        step: MigrationStep::Sql(COMMAND_USAGE),
+    },
+    Migration {
+        version: 4,
+        description: "Store birthday times to the minute",
+        step: MigrationStep::Sql(BIRTHDAY_MINUTES),
+    },
+    Migration {
+        version: 5,
+        description: "Mark Feb 29 birthdays",
+        step: MigrationStep::Sql(LEAP_DAY_BIRTHDAYS),
+    },
+    Migration {
+        version: 6,
+        description: "Keep one ongoing birthday per user",
+        step: MigrationStep::Sql(UNIQUE_ONGOING_BIRTHDAYS),
+    },
+    Migration {
+        version: 7,
+        description: "Store birthdays as local dates with time zones",
+        step: MigrationStep::Rust(store_local_birthdays),
+    },
+    Migration {
+        version: 8,
+        description: "Let birthdays count in every guild",
+        step: MigrationStep::Sql(BIRTHDAYS_IN_EVERY_GUILD),
+    },
+    Migration {
+        version: 9,
+        description: "Remember posted birthday announcements",
+        step: MigrationStep::Sql(BIRTHDAY_ANNOUNCEMENTS),
+    },
+    Migration {
+        version: 10,
+        description: "Persist pending birthday confirmations",
+        step: MigrationStep::Sql(BIRTHDAY_CONFIRMATIONS),
+    },
+    Migration {
+        version: 11,
+        description: "Add birth years and visibility to birthdays",
+        step: MigrationStep::Sql(BIRTHDAY_DETAILS),
+    },
+    Migration {
+        version: 12,
+        description: "Scope staff logs to guilds",
+        step: MigrationStep::Rust(scope_staff_logs_to_guilds),
+    },
+    Migration {
+        version: 13,
+        description: "Keep staff log revisions",
+        step: MigrationStep::Rust(add_staff_log_revisions),
+    },
+    Migration {
+        version: 14,
+        description: "Keep removed staff logs",
+        step: MigrationStep::Sql(STAFF_LOG_DELETIONS),
+    },
+    Migration {
+        version: 15,
+        description: "Add categories and severities to staff logs",
+        step: MigrationStep::Sql(STAFF_LOG_CATEGORIES),
+    },
+    Migration {
+        version: 16,
+        description: "Index staff logs for searching",
+        step: MigrationStep::Rust(add_staff_log_search),
+    },
+];
+
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+fn get_version(connection: &Connection) -> rusqlite::Result<u32> {
+    connection.query_row("PRAGMA user_version;", [], |row| row.get(0))
+}
+
+/// Applies every pending migration in a single transaction and returns the ones applied.
+/// If `dry_run` is set, the migrations are still run so that any errors show up,
+/// but the transaction is rolled back afterwards.
+pub fn run_migrations(
+    connection: &mut Connection, dry_run: bool,
+) -> Result<Vec<&'static Migration>> {
+    let transaction = connection.transaction()?;
+    let current_version = get_version(&transaction)?;
+    let latest = latest_version();
+
+    if current_version > latest {
+        return Err(MigrationError::UnknownVersion { found: current_version, latest });
+    }
+
+    let pending = MIGRATIONS.iter().filter(|m| m.version > current_version).collect::<Vec<_>>();
+
+    for migration in &pending {
+        info!(
+            "{} migration {}: {}",
+            if dry_run { "Dry running" } else { "Applying" },
+            migration.version,
+            migration.description
+        );
+
+        migration.apply(&transaction)?;
+        transaction.pragma_update(None, "user_version", migration.version)?;
+    }
+
+    if dry_run {
+        transaction.rollback()?;
+    } else {
+        transaction.commit()?;
+    }
+
+    Ok(pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+
+    use super::*;
+
+    const LEGACY_SCHEMA: &str = include_str!("migrations/legacy_schema.sql");
+
+    fn table_names(connection: &Connection) -> Vec<String> {
+        let mut statement = connection
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name;")
+            .unwrap();
+
+        statement.query_map([], |row| row.get(0)).unwrap().map(|name| name.unwrap()).collect()
+    }
+
+    fn legacy_connection() -> Connection {
+        let connection = Connection::open_in_memory().unwrap();
+
+        connection.execute_batch(LEGACY_SCHEMA).unwrap();
+
+        connection
+    }
+
+    #[test]
+    fn migration_versions_are_ordered() {
+        for (idx, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version as usize, idx + 1, "{}", migration.description);
+        }
+    }
+
+    #[test]
+    fn migrates_empty_database() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        let applied = run_migrations(&mut connection, false).unwrap();
+
+        assert_eq!(applied.len(), MIGRATIONS.len());
+        assert_eq!(get_version(&connection).unwrap(), latest_version());
+        assert!(table_names(&connection).contains(&"guild_config".to_owned()));
+
+        // Seeded, since the table was created by the migration
+        let seeded: u32 = connection
+            .query_row("SELECT COUNT(*) FROM guild_config;", [], |row| row.get(0))
+            .unwrap();
+
+        assert!(seeded > 0);
+    }
+
+    #[test]
+    fn migrations_are_idempotent() {
+        let mut connection = Connection::open_in_memory().unwrap();
+
+        run_migrations(&mut connection, false).unwrap();
+
+        assert!(run_migrations(&mut connection, false).unwrap().is_empty());
+        assert_eq!(get_version(&connection).unwrap(), latest_version());
+    }
+
+    #[test]
+    fn migrates_legacy_database() {
+        let mut connection = legacy_connection();
+        let tables_before = table_names(&connection);
+
+        assert_eq!(get_version(&connection).unwrap(), 0);
+
+        run_migrations(&mut connection, false).unwrap();
+
+        assert_eq!(get_version(&connection).unwrap(), latest_version());
+
+        for table in tables_before {
+            assert!(table_names(&connection).contains(&table), "{table} was dropped");
+        }
+
+        let staff_logs = connection
+            .prepare(
+                "SELECT guild_id, entry_id, reason FROM staff_logs WHERE user_id = 2 \
+                    ORDER BY guild_id, entry_id;",
+            )
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<(u64, u32, String)>>>()
+            .unwrap();
+
+        assert_eq!(
+            staff_logs,
+            [
+                (720900352018219039, 1, "Spam".to_owned()),
+                (720900352018219039, 2, "Being a bad burd".to_owned()),
+            ]
+        );
+
+        let (author_id, link, revised_at) = connection
+            .query_row(
+                "SELECT author_id, link, revised_at FROM staff_log_revisions \
+                    WHERE reason = 'Being a bad burd';",
+                [],
+                |row| {
+                    Ok((
+                        row.get::<_, Option<u64>>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, i64>(2)?,
+                    ))
+                },
+            )
+            .unwrap();
+
+        // Only the last edit of older entries is known.
+        assert_eq!(author_id, None);
+        assert!(link.ends_with("/913886469809115208"));
+        assert_eq!(revised_at, 1637957913);
+
+        let kinds = connection
+            .query_row(
+                "SELECT group_concat(DISTINCT category || ' ' || severity) FROM staff_logs;",
+                [],
+                |row| row.get::<_, String>(0),
+            )
+            .unwrap();
+
+        assert_eq!(kinds, "note low");
+
+        let found = connection
+            .query_row(
+                "SELECT logged_at FROM staff_logs WHERE log_id IN \
+                    (SELECT rowid FROM staff_log_search WHERE staff_log_search MATCH 'burd');",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap();
+
+        // Entries from before the index existed are found, logged when their message was sent.
+        assert_eq!(found, 1637957913);
+
+        let bday = |user_id: u32| {
+            connection
+                .query_row(
+                    "SELECT month, day, time_zone FROM bday WHERE user_id = ?;",
+                    [user_id],
+                    |row| {
+                        Ok((row.get::<_, u32>(0)?, row.get::<_, u32>(1)?, row.get::<_, String>(2)?))
+                    },
+                )
+                .unwrap()
+        };
+
+        // Started at 7:00 and 14:00 UTC
+        assert_eq!(bday(1), (10, 6, "UTC-7".to_owned()));
+        assert_eq!(bday(2), (3, 1, "UTC+10".to_owned()));
+
+        let ongoing_guild_ids = connection
+            .prepare("SELECT guild_id FROM bday_user_list WHERE user_id = 1;")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<u64>>>()
+            .unwrap();
+
+        assert_eq!(ongoing_guild_ids, [720900352018219039]);
+
+        // Existing config shouldn't get reseeded
+        let config_rows: u32 = connection
+            .query_row("SELECT COUNT(*) FROM guild_config;", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(config_rows, 1);
+    }
+
+    #[test]
+    fn staff_logs_move_to_their_guild() {
+        let mut connection = legacy_connection();
+
+        connection
+            .execute(
+                "INSERT INTO staff_logs VALUES (2, 3, ?, NULL, 'Spam elsewhere');",
+                ["https://discord.com/channels/952706419237384262/952706419237384265/963886469809115206"],
+            )
+            .unwrap();
+
+        run_migrations(&mut connection, false).unwrap();
+
+        let staff_logs = connection
+            .prepare(
+                "SELECT guild_id, entry_id FROM staff_logs WHERE user_id = 2 ORDER BY entry_id;",
+            )
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<(u64, u32)>>>()
+            .unwrap();
+
+        // Keeping their numbers, even though that leaves gaps in each guild
+        assert_eq!(
+            staff_logs,
+            [(720900352018219039, 1), (720900352018219039, 2), (952706419237384262, 3)]
+        );
+    }
+
+    #[test]
+    fn invalid_staff_log_links_fail_the_migration() {
+        let mut connection = legacy_connection();
+
+        connection
+            .execute_batch("INSERT INTO staff_logs VALUES (2, 3, 'Somewhere', NULL, 'Lost');")
+            .unwrap();
+
+        let Err(MigrationError::InvalidRows { table, rows }) =
+            run_migrations(&mut connection, false)
+        else {
+            panic!("The invalid staff log should have been refused");
+        };
+
+        assert_eq!(table, "staff_logs");
+        assert_eq!(rows, ["user_id 2, entry_id 3: original_link 'Somewhere'"]);
+        assert_eq!(get_version(&connection).unwrap(), 0);
+    }
+
+    #[test]
+    fn local_birthdays_keep_their_date() {
+        let local_birthday = |bday_date| to_local_birthday(bday_date, false).unwrap();
+
+        assert_eq!(local_birthday("06-14-09-59"), (6, 14, "UTC-9:59".to_owned()));
+        // UTC-11:30 wasn't allowed, so this is UTC+12:30 on the next day.
+        assert_eq!(local_birthday("06-14-11-30"), (6, 15, "UTC+12:30".to_owned()));
+        assert!(to_local_birthday("06-14-10-00", false).is_err());
+        assert!(to_local_birthday("06-14-11-00", false).is_err());
+    }
+
+    #[test]
+    fn invalid_birthdays_fail_the_migration() {
+        let mut connection = legacy_connection();
+
+        connection
+            .execute_batch(
+                "
+                INSERT INTO bday VALUES (3, 720900352018219039, '02-30-07');
+                INSERT INTO bday VALUES (4, 720900352018219039, 'soon');
+                INSERT INTO bday VALUES (5, 720900352018219039, '06-14-10');
+                ",
+            )
+            .unwrap();
+
+        let Err(MigrationError::InvalidRows { table, rows }) =
+            run_migrations(&mut connection, false)
+        else {
+            panic!("The invalid birthdays should have been refused");
+        };
+
+        assert_eq!(table, "bday");
+        assert_eq!(
+            rows,
+            [
+                "user_id 3: bday_date '02-30-07-00' isn't a valid time of year",
+                "user_id 4: bday_date 'soon-00' isn't a valid time of year",
+                "user_id 5: bday_date '06-14-10-00' could be UTC-10 on that day or UTC+14 on the \
+                    next",
+            ]
+        );
+        assert_eq!(get_version(&connection).unwrap(), 0);
+    }
+
+    #[test]
+    fn dry_run_changes_nothing() {
+        let mut connection = legacy_connection();
+        let tables_before = table_names(&connection);
+        let pending = run_migrations(&mut connection, true).unwrap();
+
+        assert_eq!(pending.len(), MIGRATIONS.len());
+        assert_eq!(get_version(&connection).unwrap(), 0);
+        assert_eq!(table_names(&connection), tables_before);
+    }
+
+    #[test]
+    fn refuses_newer_database() {
+        let mut connection = Connection::open_in_memory().unwrap();
+
+        connection.pragma_update(None, "user_version", latest_version() + 1).unwrap();
+
+        assert!(matches!(
+            run_migrations(&mut connection, false),
+            Err(MigrationError::UnknownVersion { .. })
+        ));
+    }
+}
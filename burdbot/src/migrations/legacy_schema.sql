@@ -0,0 +1,84 @@
+-- The schema made by create_sql_tables() before versioned migrations, with some sample rows.
+-- Used to check that migrations work on existing databases. Don't update this when adding migrations.
+
+CREATE TABLE IF NOT EXISTS times (
+    user_id INTEGER PRIMARY KEY,
+    time INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS bday (
+    user_id INTEGER PRIMARY KEY,
+    guild_id INTEGER NOT NULL,
+    bday_date TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS bday_user_list (
+    user_id INTEGER,
+    bday_over_date TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES bday(user_id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS bday_role_list (
+    guild_id INTEGER PRIMARY KEY,
+    role_id INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS staff_logs (
+    user_id INTEGER NOT NULL,
+    entry_id INTEGER NOT NULL,
+    original_link TEXT NOT NULL,
+    last_edited_link TEXT,
+    reason TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS vocaroo_enabled (
+    guild_id INTEGER PRIMARY KEY
+);
+
+CREATE TABLE IF NOT EXISTS fxhash_image_checksums (
+    link_reference TEXT PRIMARY KEY,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    description TEXT NOT NULL,
+    hash BLOB NOT NULL,
+    hash_type INTEGER NOT NULL,
+    guild_id INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS guild_config (
+    guild_id INTEGER NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (guild_id, key)
+);
+
+CREATE INDEX IF NOT EXISTS fxhash_checksum_index
+    on fxhash_image_checksums (guild_id, width, height);
+
+CREATE INDEX IF NOT EXISTS bday_date_index
+    on bday (bday_date);
+
+CREATE INDEX IF NOT EXISTS bday_over_date_index
+    on bday_user_list (bday_over_date);
+
+CREATE INDEX IF NOT EXISTS staff_log_index
+    on staff_logs (user_id);
+
+INSERT INTO bday VALUES (1, 720900352018219039, '10-06-07');
+INSERT INTO bday VALUES (2, 720900352018219039, '02-28-14');
+INSERT INTO bday_user_list VALUES (1, '10-07-07');
+INSERT INTO bday_role_list VALUES (720900352018219039, 728359316498808895);
+
+INSERT INTO staff_logs VALUES (
+    2, 1, 'https://discord.com/channels/720900352018219039/720900352597033053/913886469809115206',
+    NULL, 'Spam'
+);
+INSERT INTO staff_logs VALUES (
+    2, 2, 'https://discord.com/channels/720900352018219039/720900352597033053/913886469809115207',
+    'https://discord.com/channels/720900352018219039/720900352597033053/913886469809115208',
+    'Being a bad burd'
+);
+
+INSERT INTO vocaroo_enabled VALUES (720900352018219039);
+
+INSERT INTO guild_config VALUES (720900352018219039, 'helper_check_bypass', '1');
@@ -3,7 +3,6 @@
 // use std::sync::Arc;
 // use std::time::Duration;
 
-use serenity::all::{ChannelId, GuildId, RoleId};
 // use log::error;
 // use serenity::client::Cache
 // use serenity::http::Http;
@@ -15,19 +14,18 @@ use serenity::all::{ChannelId, GuildId, RoleId};
 // use tokio::task::JoinHandle;
 // use tokio::time;
 use serenity::client::Context;
-use serenity::framework::standard::Reason;
 use serenity::framework::standard::macros::check;
+use serenity::framework::standard::{CommandResult, Reason};
 use serenity::model::channel::Message;
+use serenity::model::id::{GuildId, UserId};
+use serenity::model::mention::Mentionable;
 
-use crate::util;
+use crate::commands::SlashContext;
+use crate::{guild_config, util};
 
 const BOT_PREFIXES: [&str; 5] = ["-", "--", "---", "!", "!!"];
-const MUSIC_CHANNEL_ID: u64 = 263643662808776704;
 // const ENGLISH_CLASS_CATEGORY_ID: u64 = 878362687837442098;
 // const ENGLISH_TEACHER_ROLE_ID: u64 = 878223433899577364;
-pub const SPANISH_ENGLISH_SERVER_ID: GuildId = GuildId::new(243838819743432704);
-pub const SPANISH_ENGLISH_STAFF_CHANNEL_ID: ChannelId = ChannelId::new(913886469809115206);
-pub const SPANISH_ENGLISH_STAFF_ROLE: RoleId = RoleId::new(642782671109488641);
 // const ENGLISH_CLASS_STAGE_ID: u64 = 878363153455538246;
 
 // struct Teachers;
@@ -266,9 +264,13 @@ pub async fn on_message_receive(ctx: &Context, message: &Message) {
 }
 
 async fn do_music_check(ctx: &Context, message: &Message) {
-    let channel_id = message.channel_id.get();
+    let Some(guild_id) = message.guild_id else {
+        return;
+    };
+
+    let config = guild_config::get(ctx, guild_id).await;
 
-    if channel_id != MUSIC_CHANNEL_ID {
+    if config.music_channel != Some(message.channel_id) {
         return;
     }
 
@@ -276,8 +278,14 @@ async fn do_music_check(ctx: &Context, message: &Message) {
 
     for prefix in BOT_PREFIXES {
         if content.starts_with(prefix) {
-            let msg_str = "Please put music bot commands in <#247135634265735168> as they do not work here. \
-            Por favor, poné los comandos de música en <#247135634265735168>. No funcionan por acá.";
+            let music_bot_channel = match config.music_bot_channel {
+                Some(id) => id.mention().to_string(),
+                None => "the music bot channel".to_owned(),
+            };
+            let msg_str = format!(
+                "Please put music bot commands in {music_bot_channel} as they do not work here. \
+            Por favor, poné los comandos de música en {music_bot_channel}. No funcionan por acá."
+            );
 
             util::send_message(ctx, message.channel_id, msg_str, "on_message_receive").await;
 
@@ -286,26 +294,40 @@ async fn do_music_check(ctx: &Context, message: &Message) {
     }
 }
 
+/// Whether the user is a server helper or above, or the server doesn't check for helpers.
+async fn is_helper_or_above(
+    ctx: &Context, guild_id: GuildId, user_id: UserId,
+) -> serenity::Result<bool> {
+    let config = guild_config::get(ctx, guild_id).await;
+
+    if config.helper_check_bypass {
+        // Servers like the test server can make an exemption for everyone
+        return Ok(true);
+    }
+
+    let member = guild_id.member(ctx, user_id).await?;
+
+    Ok(member.roles.iter().any(|id| config.helper_roles.contains(id)))
+}
+
 #[check]
 pub async fn is_server_helper_or_above(ctx: &Context, msg: &Message) -> Result<(), Reason> {
-    if let Some(720900352018219039) = msg.guild_id.map(|i| i.get()) {
-        // If the message comes from the test server, then automatically make an exemption for it
-        return Ok(());
+    let Some(guild_id) = msg.guild_id else {
+        return Err(Reason::Unknown);
+    };
+
+    match is_helper_or_above(ctx, guild_id, msg.author.id).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(Reason::Log("User is lower than a server helper.".to_owned())),
+        Err(_) => Err(Reason::Unknown),
     }
+}
 
-    let author = match msg.member(&ctx).await {
-        Ok(member) => member,
-        Err(_) => return Err(Reason::Unknown),
+/// The slash command version of [`is_server_helper_or_above`].
+pub async fn is_server_helper_or_above_slash(ctx: SlashContext<'_>) -> CommandResult<bool> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(false);
     };
 
-    author
-        .roles
-        .iter()
-        .any(|id| {
-            id.get() == 243854949522472971
-                || id.get() == 258806166770024449
-                || id.get() == 258819531193974784
-        })
-        .then_some(())
-        .ok_or_else(|| Reason::Log("User is lower than a server helper.".to_owned()))
+    Ok(is_helper_or_above(ctx.serenity_context(), guild_id, ctx.author().id).await?)
 }
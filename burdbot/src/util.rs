@@ -1,5 +1,6 @@
 use lazy_static::lazy_static;
 use log::info;
+use poise::CreateReply;
 use regex::Regex;
 use reqwest::IntoUrl;
 use serenity::all::Color;
@@ -22,6 +23,8 @@ use serenity::prelude::ModelError;
 
 use log::error;
 
+use crate::commands::SlashContext;
+
 // Gets the IDs from a message link
 pub fn get_ids_from_msg_link(link: impl AsRef<str>) -> Option<(GuildId, ChannelId, MessageId)> {
     lazy_static! {
@@ -43,16 +46,26 @@ pub fn check_message_sending(res: serenity::Result<Message>, function_name: &str
     }
 }
 
+pub fn message_embed(msg: impl Display) -> CreateEmbed {
+    CreateEmbed::new().color(Color::GOLD).description(msg.to_string())
+}
+
 pub async fn send_message(
     ctx: impl AsRef<Http>, ch: ChannelId, msg: impl Display, function_name: &str,
 ) {
     let ctx = ctx.as_ref();
-    let embed = CreateEmbed::new().color(Color::GOLD).description(msg.to_string());
-    let builder = CreateMessage::new().embed(embed);
+    let builder = CreateMessage::new().embed(message_embed(msg));
 
     check_message_sending(ch.send_message(ctx, builder).await, function_name);
 }
 
+/// Replies to a slash command the same way [`send_message`] sends a message.
+pub async fn send_reply(ctx: SlashContext<'_>, msg: impl Display) -> Result<(), Error> {
+    ctx.send(CreateReply::default().embed(message_embed(msg))).await?;
+
+    Ok(())
+}
+
 pub async fn get_member_permissions<T: AsRef<Cache>>(
     cache: T, guild_id: GuildId, user_id: impl Into<UserId>,
 ) -> Option<Permissions> {
@@ -9,6 +9,8 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use thiserror::Error;
 
+use crate::metrics;
+
 pub type Result<'a, T> = std::result::Result<T, VocarooError<'a>>;
 
 #[non_exhaustive]
@@ -136,5 +138,13 @@ pub async fn download_vocaroos(
         future::join_all(recording_futures).await
     };
 
+    for recording in &recordings {
+        match recording {
+            Ok(bytes) => metrics::DOWNLOAD_BYTES.observe(&["vocaroo"], bytes.len() as f64),
+            Err(VocarooError::OverSizeLimit(..)) => {},
+            Err(_) => metrics::DOWNLOAD_FAILURES.inc(&["vocaroo"]),
+        }
+    }
+
     recordings.into_iter().take(attachment_count_limit)
 }